@@ -164,6 +164,8 @@ build_exceptions! {
     ParquetFileInvalid(1201),
     /// InvalidUtf8String is used when given string is not a valid utf8 string.
     InvalidUtf8String(1202),
+    /// CorruptParquet is used when the checksum of a parquet page mismatches.
+    CorruptParquet(1203),
 
     // Table related errors starts here.
 
@@ -144,6 +144,8 @@ static REMOTE_IO_READ_PARTS: LazyLock<Counter> =
     LazyLock::new(|| register_counter("fuse_remote_io_read_parts"));
 static REMOTE_IO_READ_MILLISECONDS: LazyLock<Histogram> =
     LazyLock::new(|| register_histogram_in_milliseconds("fuse_remote_io_read_milliseconds"));
+static REMOTE_IO_DESERIALIZED_BYTES: LazyLock<Counter> =
+    LazyLock::new(|| register_counter("fuse_remote_io_deserialized_bytes"));
 static REMOTE_IO_DESERIALIZE_MILLISECONDS: LazyLock<Histogram> =
     LazyLock::new(|| register_histogram_in_milliseconds("fuse_remote_io_deserialize_milliseconds"));
 static BLOCK_WRITE_NUMS: LazyLock<Counter> =
@@ -522,6 +524,10 @@ pub fn metrics_inc_remote_io_deserialize_milliseconds(c: u64) {
     REMOTE_IO_DESERIALIZE_MILLISECONDS.observe(c as f64);
 }
 
+pub fn metrics_inc_remote_io_deserialized_bytes(c: u64) {
+    REMOTE_IO_DESERIALIZED_BYTES.inc_by(c);
+}
+
 /// Block metrics.
 pub fn metrics_inc_block_write_nums(c: u64) {
     BLOCK_WRITE_NUMS.inc_by(c);
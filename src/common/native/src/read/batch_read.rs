@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use databend_common_column::bitmap::MutableBitmap;
+use databend_common_expression::types::Bitmap;
 use databend_common_expression::types::DateType;
 use databend_common_expression::types::NumberType;
 use databend_common_expression::types::TimestampType;
@@ -20,6 +22,7 @@ use databend_common_expression::Column;
 use databend_common_expression::TableDataType;
 
 use super::array::*;
+use super::read_basic::read_validity;
 use super::NativeReadBuf;
 use crate::error::Result;
 use crate::nested::create_list;
@@ -205,3 +208,30 @@ pub fn batch_read_column<R: NativeReadBuf>(
     let column = Column::concat_columns(columns.into_iter()).unwrap();
     Ok(column)
 }
+
+/// Read the validity of a non-nested column without decoding the values.
+///
+/// The validity is stored uncompressed at the start of each page, so only it is read
+/// and the rest of the page is skipped. A column that is not nullable is all valid.
+pub fn batch_read_validity(
+    chunk: &[u8],
+    is_nullable: bool,
+    page_metas: &[PageMeta],
+) -> Result<Bitmap> {
+    let num_rows = page_metas.iter().map(|meta| meta.num_values as usize).sum();
+    if !is_nullable {
+        return Ok(Bitmap::new_constant(true, num_rows));
+    }
+
+    let mut validity = MutableBitmap::with_capacity(num_rows);
+    let mut offset = 0;
+    for page_meta in page_metas {
+        let mut page = &chunk[offset..offset + page_meta.length as usize];
+        match read_validity(&mut page)? {
+            Some(bitmap) => validity.extend_from_bitmap(&bitmap),
+            None => validity.extend_constant(page_meta.num_values as usize, true),
+        }
+        offset += page_meta.length as usize;
+    }
+    Ok(validity.into())
+}
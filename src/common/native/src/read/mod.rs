@@ -16,6 +16,8 @@ mod array;
 pub mod batch_read;
 pub mod deserialize;
 use batch_read::batch_read_column;
+use batch_read::batch_read_validity;
+use databend_common_expression::types::Bitmap;
 use databend_common_expression::Column;
 use databend_common_expression::TableDataType;
 use databend_common_expression::TableField;
@@ -99,4 +101,14 @@ impl NativeColumnsReader {
     ) -> Result<Column> {
         batch_read_column(readers, data_type, page_metas)
     }
+
+    /// Read only the validity of the pages of a non-nested column.
+    pub fn batch_read_validity(
+        &self,
+        chunk: &[u8],
+        is_nullable: bool,
+        page_metas: &[PageMeta],
+    ) -> Result<Bitmap> {
+        batch_read_validity(chunk, is_nullable, page_metas)
+    }
 }
@@ -18,10 +18,16 @@ use std::sync::Arc;
 use arrow_schema::Schema as ArrowSchema;
 use databend_common_exception::ErrorCode;
 use databend_common_exception::Result;
+use databend_common_expression::converts::arrow::ARROW_EXT_TYPE_ENUM;
+use databend_common_expression::converts::arrow::ARROW_EXT_TYPE_UUID;
+use databend_common_expression::converts::arrow::ARROW_EXT_TYPE_VARIANT;
 use databend_common_expression::converts::arrow::EXTENSION_KEY;
 use databend_common_expression::FieldIndex;
 use opendal::Operator;
 use parquet::arrow::parquet_to_arrow_schema;
+use parquet::basic::ConvertedType;
+use parquet::basic::LogicalType;
+use parquet::basic::Type as PhysicalType;
 // FIXME(xuanwo): refactor code here.
 #[allow(deprecated)]
 use parquet::file::footer::decode_footer;
@@ -29,8 +35,15 @@ use parquet::file::footer::decode_footer;
 use parquet::file::footer::decode_metadata;
 use parquet::file::metadata::FileMetaData;
 use parquet::file::metadata::ParquetMetaData;
+use parquet::format::FileMetaData as ThriftFileMetaData;
+use parquet::schema::types::Type as ParquetType;
+use parquet::thrift::TSerializable;
+use thrift::protocol::TCompactInputProtocol;
 
 const FOOTER_SIZE: u64 = 8;
+/// The magic of parquet files written with encrypted footer mode.
+/// Files written with plaintext footer mode still use `PAR1`.
+const PARQUET_MAGIC_ENCRYPTED_FOOTER: &[u8; 4] = b"PARE";
 /// The number of bytes read at the end of the parquet file on first read
 const DEFAULT_FOOTER_READ_SIZE: u64 = 64 * 1024;
 
@@ -54,38 +67,67 @@ pub fn read_parquet_schema_sync_rs(
 }
 
 pub fn infer_schema_with_extension(meta: &FileMetaData) -> Result<ArrowSchema> {
-    let mut arrow_schema = parquet_to_arrow_schema(meta.schema_descr(), meta.key_value_metadata())?;
-    // Convert data types to extension types using meta information.
-    // Mainly used for types such as Variant and Bitmap,
-    // as they have the same physical type as String.
-    if let Some(metas) = meta.key_value_metadata() {
-        let mut new_fields = Vec::with_capacity(arrow_schema.fields.len());
-        for field in arrow_schema.fields.iter() {
-            let mut new_field = field.clone();
-            for meta in metas {
-                match &meta.value {
-                    Some(ty) if field.name() == &meta.key => {
-                        let f = arrow_schema::Field::new(
-                            field.name(),
-                            field.data_type().clone(),
-                            field.is_nullable(),
-                        )
-                        .with_metadata(HashMap::from([(
-                            EXTENSION_KEY.to_string(),
-                            ty.to_string(),
-                        )]));
-                        new_field = Arc::new(f);
-                        break;
-                    }
-                    _ => {}
-                }
+    let arrow_schema = parquet_to_arrow_schema(meta.schema_descr(), meta.key_value_metadata())?;
+    let parquet_fields = meta.schema_descr().root_schema().get_fields();
+    let mut new_fields = Vec::with_capacity(arrow_schema.fields.len());
+    for (field, parquet_field) in arrow_schema.fields.iter().zip(parquet_fields) {
+        // Convert data types to extension types using meta information.
+        // Mainly used for types such as Variant and Bitmap,
+        // as they have the same physical type as String.
+        let ext_type = meta.key_value_metadata().and_then(|metas| {
+            metas.iter().find_map(|meta| match &meta.value {
+                Some(ty) if field.name() == &meta.key => Some(ty.clone()),
+                _ => None,
+            })
+        });
+        let ext_type = ext_type.or_else(|| logical_type_extension(parquet_field));
+        match ext_type {
+            Some(ty) => {
+                let f = arrow_schema::Field::new(
+                    field.name(),
+                    field.data_type().clone(),
+                    field.is_nullable(),
+                )
+                .with_metadata(HashMap::from([(EXTENSION_KEY.to_string(), ty)]));
+                new_fields.push(Arc::new(f));
             }
-            new_fields.push(new_field);
+            None => new_fields.push(field.clone()),
         }
-        arrow_schema = ArrowSchema::new_with_metadata(new_fields, arrow_schema.metadata);
     }
 
-    Ok(arrow_schema)
+    Ok(ArrowSchema::new_with_metadata(
+        new_fields,
+        arrow_schema.metadata,
+    ))
+}
+
+// The extension type of the root columns whose logical type is lost or mapped
+// to an unsuitable type when converted to arrow.
+//
+// The unsupported types, like FLOAT16, are left to fail when the column is converted,
+// so the other columns of the file can still be read.
+fn logical_type_extension(parquet_field: &ParquetType) -> Option<String> {
+    if !parquet_field.is_primitive() {
+        return None;
+    }
+    let basic_info = parquet_field.get_basic_info();
+    let ext_type = match (basic_info.logical_type(), basic_info.converted_type()) {
+        // UUID of the external parquet files is stored as FIXED_LEN_BYTE_ARRAY(16),
+        // which is converted to fixed size binary without the logical type.
+        (Some(LogicalType::Uuid), _)
+            if parquet_field.get_physical_type() == PhysicalType::FIXED_LEN_BYTE_ARRAY =>
+        {
+            Some(ARROW_EXT_TYPE_UUID)
+        }
+        // JSON is converted to string, it is parsed into Variant. The text is not kept:
+        // a STRING column loaded from it gets the JSON serialized again from the Variant,
+        // and an invalid JSON value fails the conversion of the whole column.
+        (Some(LogicalType::Json), _) | (None, ConvertedType::JSON) => Some(ARROW_EXT_TYPE_VARIANT),
+        // ENUM is converted to binary, the values are the UTF-8 names.
+        (Some(LogicalType::Enum), _) | (None, ConvertedType::ENUM) => Some(ARROW_EXT_TYPE_ENUM),
+        _ => None,
+    };
+    ext_type.map(|ty| ty.to_string())
 }
 
 /// Layout of Parquet file
@@ -116,20 +158,14 @@ pub async fn read_metadata_async(
         .await?
         .to_vec();
     let buffer_len = buffer.len();
-    #[allow(deprecated)]
-    let metadata_len = decode_footer(
-        &buffer[(buffer_len - FOOTER_SIZE as usize)..]
-            .try_into()
-            .unwrap(),
-    )? as u64;
+    let metadata_len = decode_footer_len(&buffer[(buffer_len - FOOTER_SIZE as usize)..])?;
     check_meta_size(file_size, metadata_len)?;
 
     let footer_len = FOOTER_SIZE + metadata_len;
     if (footer_len as usize) <= buffer_len {
         // The whole metadata is in the bytes we already read
         let offset = buffer_len - footer_len as usize;
-        #[allow(deprecated)]
-        Ok(decode_metadata(&buffer[offset..])?)
+        decode_unencrypted_metadata(&buffer[offset..])
     } else {
         // The end of file read by default is not long enough, read again including the metadata.
         // TBD: which one is better?
@@ -141,8 +177,7 @@ pub async fn read_metadata_async(
             .await?
             .to_vec();
         metadata.extend(buffer);
-        #[allow(deprecated)]
-        Ok(decode_metadata(&metadata)?)
+        decode_unencrypted_metadata(&metadata)
     }
 }
 
@@ -167,20 +202,14 @@ pub fn read_metadata_sync(
         .call()?
         .to_vec();
     let buffer_len = buffer.len();
-    #[allow(deprecated)]
-    let metadata_len = decode_footer(
-        &buffer[(buffer_len - FOOTER_SIZE as usize)..]
-            .try_into()
-            .unwrap(),
-    )? as u64;
+    let metadata_len = decode_footer_len(&buffer[(buffer_len - FOOTER_SIZE as usize)..])?;
     check_meta_size(file_size, metadata_len)?;
 
     let footer_len = FOOTER_SIZE + metadata_len;
     if (footer_len as usize) <= buffer_len {
         // The whole metadata is in the bytes we already read
         let offset = buffer_len - footer_len as usize;
-        #[allow(deprecated)]
-        Ok(decode_metadata(&buffer[offset..])?)
+        decode_unencrypted_metadata(&buffer[offset..])
     } else {
         let mut metadata = blocking
             .read_with(path)
@@ -188,9 +217,61 @@ pub fn read_metadata_sync(
             .call()?
             .to_vec();
         metadata.extend(buffer);
-        #[allow(deprecated)]
-        Ok(decode_metadata(&metadata)?)
+        decode_unencrypted_metadata(&metadata)
+    }
+}
+
+/// Decode the footer and return the length of the metadata.
+///
+/// Parquet modular encryption is not supported, files written with encrypted footer
+/// are rejected here with a clear error, instead of a corrupt footer error. The files written
+/// with plaintext footer are rejected when their metadata is decoded.
+pub fn decode_footer_len(footer: &[u8]) -> Result<u64> {
+    let footer: &[u8; FOOTER_SIZE as usize] = footer.try_into().map_err(|_| {
+        ErrorCode::BadBytes(format!(
+            "Invalid Parquet file. Footer should be {} bytes, but got {} bytes",
+            FOOTER_SIZE,
+            footer.len()
+        ))
+    })?;
+    if &footer[4..] == PARQUET_MAGIC_ENCRYPTED_FOOTER {
+        return Err(ErrorCode::Unimplemented(
+            "Reading Parquet file with encrypted footer is not supported",
+        ));
+    }
+    #[allow(deprecated)]
+    let metadata_len = decode_footer(footer)?;
+    Ok(metadata_len as u64)
+}
+
+/// Decode the metadata, rejecting the files with encrypted columns.
+///
+/// Files written with plaintext footer mode have the `PAR1` magic and a readable footer, only
+/// the crypto metadata of the column chunks tells their data is encrypted. Without the check,
+/// the encrypted pages would fail later with a confusing decode error.
+fn decode_unencrypted_metadata(metadata: &[u8]) -> Result<ParquetMetaData> {
+    let mut prot = TCompactInputProtocol::new(metadata);
+    let file_meta = ThriftFileMetaData::read_from_in_protocol(&mut prot).map_err(|e| {
+        ErrorCode::BadBytes(format!("Invalid Parquet file. Corrupt metadata: {}", e))
+    })?;
+    let encrypted_column = file_meta
+        .row_groups
+        .iter()
+        .flat_map(|row_group| row_group.columns.iter())
+        .find(|column| column.crypto_metadata.is_some());
+    if let Some(column) = encrypted_column {
+        let path = column
+            .meta_data
+            .as_ref()
+            .map(|meta| meta.path_in_schema.join("."))
+            .unwrap_or_default();
+        return Err(ErrorCode::Unimplemented(format!(
+            "Reading Parquet file with encrypted column '{}' is not supported",
+            path
+        )));
     }
+    #[allow(deprecated)]
+    Ok(decode_metadata(metadata)?)
 }
 
 /// check file is large enough to hold footer
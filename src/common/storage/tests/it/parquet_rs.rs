@@ -0,0 +1,486 @@
+// Copyright 2021 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs::File;
+use std::sync::Arc;
+
+use databend_common_exception::ErrorCode;
+use databend_common_exception::Result;
+use databend_common_expression::types::DecimalDataType;
+use databend_common_expression::types::DecimalScalar;
+use databend_common_expression::types::DecimalSize;
+use databend_common_expression::types::NumberDataType;
+use databend_common_expression::types::NumberScalar;
+use databend_common_expression::Column;
+use databend_common_expression::DataBlock;
+use databend_common_expression::DataSchema;
+use databend_common_expression::ScalarRef;
+use databend_common_expression::TableDataType;
+use databend_common_expression::TableField;
+use databend_common_expression::TableSchema;
+use databend_common_storage::parquet_rs::decode_footer_len;
+use databend_common_storage::parquet_rs::infer_schema_with_extension;
+use databend_common_storage::parquet_rs::read_metadata_sync;
+use opendal::services::Fs;
+use opendal::Operator;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::basic::ConvertedType;
+use parquet::basic::LogicalType;
+use parquet::basic::Repetition;
+use parquet::basic::TimeUnit;
+use parquet::basic::Type as PhysicalType;
+use parquet::data_type::ByteArray;
+use parquet::data_type::ByteArrayType;
+use parquet::data_type::FixedLenByteArray;
+use parquet::data_type::FixedLenByteArrayType;
+use parquet::data_type::Int32Type;
+use parquet::data_type::Int64Type;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::format::AesGcmV1;
+use parquet::format::ColumnCryptoMetaData;
+use parquet::format::EncryptionAlgorithm;
+use parquet::format::EncryptionWithFooterKey;
+use parquet::format::FileMetaData as ThriftFileMetaData;
+use parquet::schema::types::Type;
+use parquet::thrift::TSerializable;
+use thrift::protocol::TCompactInputProtocol;
+use thrift::protocol::TCompactOutputProtocol;
+
+fn footer(metadata_len: u32, magic: &[u8; 4]) -> Vec<u8> {
+    let mut footer = metadata_len.to_le_bytes().to_vec();
+    footer.extend_from_slice(magic);
+    footer
+}
+
+#[test]
+fn test_decode_plaintext_footer() -> Result<()> {
+    let metadata_len = decode_footer_len(&footer(1024, b"PAR1"))?;
+    assert_eq!(metadata_len, 1024);
+    Ok(())
+}
+
+#[test]
+fn test_decode_encrypted_footer() {
+    let err = decode_footer_len(&footer(1024, b"PARE")).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UNIMPLEMENTED);
+    assert!(err.message().contains("encrypted footer"));
+}
+
+#[test]
+fn test_decode_invalid_footer() {
+    assert!(decode_footer_len(&footer(1024, b"ABCD")).is_err());
+    assert!(decode_footer_len(b"PAR1").is_err());
+}
+
+// Rewrite the metadata of the file as if its first column was encrypted with the footer key in
+// plaintext footer mode, the pages are kept as they are.
+fn with_encrypted_column(data: &[u8]) -> Vec<u8> {
+    let footer_start = data.len() - 8;
+    let metadata_len = u32::from_le_bytes(data[footer_start..footer_start + 4].try_into().unwrap());
+    let metadata_start = footer_start - metadata_len as usize;
+    let mut prot = TCompactInputProtocol::new(&data[metadata_start..footer_start]);
+    let mut file_meta = ThriftFileMetaData::read_from_in_protocol(&mut prot).unwrap();
+    file_meta.encryption_algorithm = Some(EncryptionAlgorithm::AESGCMV1(AesGcmV1 {
+        aad_prefix: None,
+        aad_file_unique: Some(vec![0; 8]),
+        supply_aad_prefix: None,
+    }));
+    file_meta.row_groups[0].columns[0].crypto_metadata = Some(
+        ColumnCryptoMetaData::ENCRYPTIONWITHFOOTERKEY(EncryptionWithFooterKey {}),
+    );
+
+    let mut metadata = vec![];
+    file_meta
+        .write_to_out_protocol(&mut TCompactOutputProtocol::new(&mut metadata))
+        .unwrap();
+    let mut file = data[..metadata_start].to_vec();
+    file.extend_from_slice(&metadata);
+    file.extend_from_slice(&footer(metadata.len() as u32, b"PAR1"));
+    file
+}
+
+#[test]
+fn test_read_encrypted_file() -> Result<()> {
+    let dir = std::env::temp_dir();
+    let operator = Operator::new(Fs::default().root(dir.to_str().unwrap()))?.finish();
+    let name = format!("encrypted_{}.parquet", std::process::id());
+    let path = dir.join(&name);
+
+    let mut writer = write_file(&path, vec![logical_type_field(
+        "s",
+        PhysicalType::BYTE_ARRAY,
+        LogicalType::String,
+    )]);
+    let mut row_group = writer.next_row_group().unwrap();
+    let mut column = row_group.next_column().unwrap().unwrap();
+    column
+        .typed::<ByteArrayType>()
+        .write_batch(&[ByteArray::from("secret")], None, None)
+        .unwrap();
+    column.close().unwrap();
+    row_group.close().unwrap();
+    writer.close().unwrap();
+    let data = std::fs::read(&path)?;
+
+    // the file without encrypted columns
+    let meta = read_metadata_sync(&name, &operator, None)?;
+    assert_eq!(meta.file_metadata().num_rows(), 1);
+
+    // plaintext footer mode, the footer is readable but the column is encrypted
+    std::fs::write(&path, with_encrypted_column(&data))?;
+    let err = read_metadata_sync(&name, &operator, None).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UNIMPLEMENTED);
+    assert!(err.message().contains("encrypted column 's'"));
+
+    // encrypted footer mode, the footer is replaced by its encrypted counterpart
+    let mut file = data[..data.len() - 8].to_vec();
+    file.extend_from_slice(&footer(16, b"PARE"));
+    std::fs::write(&path, file)?;
+    let err = read_metadata_sync(&name, &operator, None).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UNIMPLEMENTED);
+    assert!(err.message().contains("encrypted footer"));
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+// The parquet file of an external writer with the decimal and UUID columns
+// stored as FIXED_LEN_BYTE_ARRAY(16).
+fn write_fixed_len_byte_array_file(path: &std::path::Path, decimals: &[i128], uuids: &[[u8; 16]]) {
+    let decimal = Type::primitive_type_builder("d", PhysicalType::FIXED_LEN_BYTE_ARRAY)
+        .with_repetition(Repetition::REQUIRED)
+        .with_length(16)
+        .with_logical_type(Some(LogicalType::Decimal {
+            scale: 2,
+            precision: 30,
+        }))
+        .with_precision(30)
+        .with_scale(2)
+        .build()
+        .unwrap();
+    let uuid = Type::primitive_type_builder("u", PhysicalType::FIXED_LEN_BYTE_ARRAY)
+        .with_repetition(Repetition::OPTIONAL)
+        .with_length(16)
+        .with_logical_type(Some(LogicalType::Uuid))
+        .build()
+        .unwrap();
+    let schema = Type::group_type_builder("schema")
+        .with_fields(vec![Arc::new(decimal), Arc::new(uuid)])
+        .build()
+        .unwrap();
+
+    let file = File::create(path).unwrap();
+    let mut writer = SerializedFileWriter::new(file, Arc::new(schema), Default::default()).unwrap();
+    let mut row_group = writer.next_row_group().unwrap();
+
+    let values = decimals
+        .iter()
+        .map(|v| FixedLenByteArray::from(v.to_be_bytes().to_vec()))
+        .collect::<Vec<_>>();
+    let mut column = row_group.next_column().unwrap().unwrap();
+    column
+        .typed::<FixedLenByteArrayType>()
+        .write_batch(&values, None, None)
+        .unwrap();
+    column.close().unwrap();
+
+    // the last row of UUID is NULL
+    let values = uuids
+        .iter()
+        .map(|v| FixedLenByteArray::from(v.to_vec()))
+        .collect::<Vec<_>>();
+    let mut def_levels = vec![1; uuids.len()];
+    def_levels.push(0);
+    let mut column = row_group.next_column().unwrap().unwrap();
+    column
+        .typed::<FixedLenByteArrayType>()
+        .write_batch(&values, Some(&def_levels), None)
+        .unwrap();
+    column.close().unwrap();
+
+    row_group.close().unwrap();
+    writer.close().unwrap();
+}
+
+#[test]
+fn test_read_fixed_len_byte_array() -> Result<()> {
+    let path = std::env::temp_dir().join(format!(
+        "fixed_len_byte_array_{}.parquet",
+        std::process::id()
+    ));
+    let decimals = [12345, -98765, 10i128.pow(28) - 1];
+    let uuids = [[0x12; 16], [
+        0x55, 0x0e, 0x84, 0x00, 0xe2, 0x9b, 0x41, 0xd4, 0xa7, 0x16, 0x44, 0x66, 0x55, 0x44, 0x00,
+        0x00,
+    ]];
+    write_fixed_len_byte_array_file(&path, &decimals, &uuids);
+
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(&path)?)?;
+    let arrow_schema = infer_schema_with_extension(builder.metadata().file_metadata())?;
+    let table_schema = TableSchema::try_from(&arrow_schema)?;
+    let size = DecimalSize {
+        precision: 30,
+        scale: 2,
+    };
+    assert_eq!(
+        table_schema.field(0).data_type(),
+        &TableDataType::Decimal(DecimalDataType::Decimal128(size))
+    );
+    assert_eq!(
+        table_schema.field(1).data_type(),
+        &TableDataType::Nullable(Box::new(TableDataType::String))
+    );
+
+    let batch = builder.build()?.next().unwrap()?;
+    let schema = DataSchema::try_from(&arrow_schema)?;
+    let (block, _) = DataBlock::from_record_batch(&schema, &batch)?;
+    assert_eq!(block.num_rows(), 3);
+
+    let column = block.get_by_offset(0).to_column(block.num_rows());
+    for (i, v) in decimals.iter().enumerate() {
+        assert_eq!(
+            column.index(i),
+            Some(ScalarRef::Decimal(DecimalScalar::Decimal128(*v, size)))
+        );
+    }
+    let column = block.get_by_offset(1).to_column(block.num_rows());
+    assert_eq!(
+        column.index(0),
+        Some(ScalarRef::String("12121212-1212-1212-1212-121212121212"))
+    );
+    assert_eq!(
+        column.index(1),
+        Some(ScalarRef::String("550e8400-e29b-41d4-a716-446655440000"))
+    );
+    assert_eq!(column.index(2), Some(ScalarRef::Null));
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+fn logical_type_field(
+    name: &str,
+    physical_type: PhysicalType,
+    logical_type: LogicalType,
+) -> Arc<Type> {
+    let mut builder = Type::primitive_type_builder(name, physical_type)
+        .with_repetition(Repetition::REQUIRED)
+        .with_logical_type(Some(logical_type));
+    if physical_type == PhysicalType::FIXED_LEN_BYTE_ARRAY {
+        builder = builder.with_length(2);
+    }
+    Arc::new(builder.build().unwrap())
+}
+
+fn write_file(path: &std::path::Path, fields: Vec<Arc<Type>>) -> SerializedFileWriter<File> {
+    let schema = Type::group_type_builder("schema")
+        .with_fields(fields)
+        .build()
+        .unwrap();
+    let file = File::create(path).unwrap();
+    SerializedFileWriter::new(file, Arc::new(schema), Default::default()).unwrap()
+}
+
+#[test]
+fn test_read_logical_types() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("logical_types_{}.parquet", std::process::id()));
+    let time = |unit| LogicalType::Time {
+        is_adjusted_to_u_t_c: false,
+        unit,
+    };
+    let mut writer = write_file(&path, vec![
+        logical_type_field(
+            "time_ms",
+            PhysicalType::INT32,
+            time(TimeUnit::MILLIS(Default::default())),
+        ),
+        logical_type_field(
+            "time_us",
+            PhysicalType::INT64,
+            time(TimeUnit::MICROS(Default::default())),
+        ),
+        logical_type_field(
+            "time_ns",
+            PhysicalType::INT64,
+            time(TimeUnit::NANOS(Default::default())),
+        ),
+        logical_type_field("json", PhysicalType::BYTE_ARRAY, LogicalType::Json),
+        logical_type_field("enum", PhysicalType::BYTE_ARRAY, LogicalType::Enum),
+    ]);
+    // 12:34:56.789 and 23:59:59.999
+    let millis = [45296789, 86399999];
+    let micros = millis.map(|v| v as i64 * 1000);
+    let nanos = micros.map(|v| v * 1000);
+    let jsons = [r#"{"a":1}"#, "[1,null,\"b\"]"];
+    let enums = ["RED", "GREEN"];
+
+    let mut row_group = writer.next_row_group().unwrap();
+    let mut column = row_group.next_column().unwrap().unwrap();
+    column
+        .typed::<Int32Type>()
+        .write_batch(&millis, None, None)
+        .unwrap();
+    column.close().unwrap();
+    for values in [micros, nanos] {
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<Int64Type>()
+            .write_batch(&values, None, None)
+            .unwrap();
+        column.close().unwrap();
+    }
+    for values in [jsons, enums] {
+        let values = values.map(ByteArray::from);
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<ByteArrayType>()
+            .write_batch(&values, None, None)
+            .unwrap();
+        column.close().unwrap();
+    }
+    row_group.close().unwrap();
+    writer.close().unwrap();
+
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(&path)?)?;
+    let arrow_schema = infer_schema_with_extension(builder.metadata().file_metadata())?;
+    let table_schema = TableSchema::try_from(&arrow_schema)?;
+    let expected_types = [
+        TableDataType::Number(NumberDataType::Int64),
+        TableDataType::Number(NumberDataType::Int64),
+        TableDataType::Number(NumberDataType::Int64),
+        TableDataType::Variant,
+        TableDataType::String,
+    ];
+    for (i, ty) in expected_types.iter().enumerate() {
+        assert_eq!(table_schema.field(i).data_type(), ty);
+    }
+
+    let batch = builder.build()?.next().unwrap()?;
+    let schema = DataSchema::try_from(&arrow_schema)?;
+    let (block, _) = DataBlock::from_record_batch(&schema, &batch)?;
+    assert_eq!(block.num_rows(), 2);
+
+    // the time of day is read as the microseconds since midnight
+    for i in 0..3 {
+        let column = block.get_by_offset(i).to_column(block.num_rows());
+        for (row, v) in micros.iter().enumerate() {
+            assert_eq!(
+                column.index(row),
+                Some(ScalarRef::Number(NumberScalar::Int64(*v)))
+            );
+        }
+    }
+    let column = block.get_by_offset(3).to_column(block.num_rows());
+    for (row, v) in jsons.iter().enumerate() {
+        let Some(ScalarRef::Variant(value)) = column.index(row) else {
+            unreachable!()
+        };
+        assert_eq!(jsonb::to_string(value), *v);
+    }
+    let column = block.get_by_offset(4).to_column(block.num_rows());
+    for (row, v) in enums.iter().enumerate() {
+        assert_eq!(column.index(row), Some(ScalarRef::String(*v)));
+    }
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+fn test_read_unsupported_logical_type() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("float16_{}.parquet", std::process::id()));
+    let interval = Type::primitive_type_builder("interval", PhysicalType::FIXED_LEN_BYTE_ARRAY)
+        .with_repetition(Repetition::REQUIRED)
+        .with_converted_type(ConvertedType::INTERVAL)
+        .with_length(12)
+        .build()
+        .unwrap();
+    let writer = write_file(&path, vec![
+        logical_type_field(
+            "f",
+            PhysicalType::FIXED_LEN_BYTE_ARRAY,
+            LogicalType::Float16,
+        ),
+        Arc::new(interval),
+        logical_type_field("s", PhysicalType::BYTE_ARRAY, LogicalType::String),
+    ]);
+    writer.close().unwrap();
+
+    // the schema of the file is still inferred, the other columns can be read
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(&path)?)?;
+    let arrow_schema = infer_schema_with_extension(builder.metadata().file_metadata())?;
+    assert_eq!(arrow_schema.fields().len(), 3);
+    let interval = TableField::try_from(arrow_schema.field(1))?;
+    assert_eq!(interval.data_type(), &TableDataType::Binary);
+    let s = TableField::try_from(arrow_schema.field(2))?;
+    assert_eq!(s.data_type(), &TableDataType::String);
+
+    // the error is raised when the unsupported column is converted
+    let err = TableField::try_from(arrow_schema.field(0)).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UNIMPLEMENTED);
+    assert!(err.message().contains("'f'"));
+    assert!(err.message().contains("Float16"));
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+fn read_json_column(name: &str, jsons: &[&str]) -> Result<Column> {
+    let path = std::env::temp_dir().join(format!("{name}_{}.parquet", std::process::id()));
+    let mut writer = write_file(&path, vec![logical_type_field(
+        "json",
+        PhysicalType::BYTE_ARRAY,
+        LogicalType::Json,
+    )]);
+    let values = jsons
+        .iter()
+        .map(|v| ByteArray::from(*v))
+        .collect::<Vec<_>>();
+    let mut row_group = writer.next_row_group().unwrap();
+    let mut column = row_group.next_column().unwrap().unwrap();
+    column
+        .typed::<ByteArrayType>()
+        .write_batch(&values, None, None)
+        .unwrap();
+    column.close().unwrap();
+    row_group.close().unwrap();
+    writer.close().unwrap();
+
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(&path)?)?;
+    let arrow_schema = infer_schema_with_extension(builder.metadata().file_metadata())?;
+    let batch = builder.build()?.next().unwrap()?;
+    std::fs::remove_file(&path)?;
+    let schema = DataSchema::try_from(&arrow_schema)?;
+    let (block, _) = DataBlock::from_record_batch(&schema, &batch)?;
+    Ok(block.get_by_offset(0).to_column(block.num_rows()))
+}
+
+#[test]
+fn test_read_json_logical_type() -> Result<()> {
+    // the JSON text is parsed into Variant, its formatting is not kept
+    let column = read_json_column("json", &[r#"{ "a" : 1 }"#, "[1, 2]"])?;
+    let values = (0..column.len())
+        .map(|row| match column.index(row) {
+            Some(ScalarRef::Variant(value)) => jsonb::to_string(value),
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(values, vec![r#"{"a":1}"#, "[1,2]"]);
+
+    // an invalid JSON value fails the whole column
+    let err = read_json_column("invalid_json", &["[1]", "{not json"]).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::BAD_BYTES);
+    assert!(err.message().contains("{not json"));
+    Ok(())
+}
@@ -2877,6 +2877,15 @@ pub struct CacheConfig {
     )]
     pub table_data_deserialized_memory_ratio: u64,
 
+    /// Max size of the pinned columns in the in memory table column object cache, which are
+    /// never evicted. By default it is 0 (pinning disabled)
+    #[clap(
+        long = "cache-table-data-deserialized-pinned-bytes",
+        value_name = "VALUE",
+        default_value = "0"
+    )]
+    pub table_data_deserialized_pinned_bytes: u64,
+
     // ----- the following options/args are all deprecated               ----
     /// Max number of cached table segment
     #[clap(long = "cache-table-meta-segment-count", value_name = "VALUE")]
@@ -3104,6 +3113,7 @@ mod cache_config_converters {
                 data_cache_key_reload_policy: value.data_cache_key_reload_policy.try_into()?,
                 table_data_deserialized_data_bytes: value.table_data_deserialized_data_bytes,
                 table_data_deserialized_memory_ratio: value.table_data_deserialized_memory_ratio,
+                table_data_deserialized_pinned_bytes: value.table_data_deserialized_pinned_bytes,
             })
         }
     }
@@ -3131,6 +3141,7 @@ mod cache_config_converters {
                 disk_cache_config: value.disk_cache_config.into(),
                 table_data_deserialized_data_bytes: value.table_data_deserialized_data_bytes,
                 table_data_deserialized_memory_ratio: value.table_data_deserialized_memory_ratio,
+                table_data_deserialized_pinned_bytes: value.table_data_deserialized_pinned_bytes,
                 table_meta_segment_count: None,
             }
         }
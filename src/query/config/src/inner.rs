@@ -622,6 +622,10 @@ pub struct CacheConfig {
     /// Only if query nodes have plenty of un-utilized memory, the working set can be fitted into,
     /// and the access pattern will benefit from caching, consider enabled this cache.
     pub table_data_deserialized_memory_ratio: u64,
+
+    /// Max size of the pinned columns in the in memory table column object cache, which are
+    /// never evicted. By default it is 0 (pinning disabled)
+    pub table_data_deserialized_pinned_bytes: u64,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -716,6 +720,7 @@ impl Default for CacheConfig {
             data_cache_key_reload_policy: Default::default(),
             table_data_deserialized_data_bytes: 0,
             table_data_deserialized_memory_ratio: 0,
+            table_data_deserialized_pinned_bytes: 0,
         }
     }
 }
@@ -21,6 +21,7 @@ use arrow_schema::DataType as ArrowDataType;
 use arrow_schema::Field;
 use arrow_schema::Schema;
 use databend_common_column::binary::BinaryColumn;
+use databend_common_column::binary::BinaryColumnBuilder;
 use databend_common_column::binview::StringColumn;
 use databend_common_column::bitmap::Bitmap;
 use databend_common_column::buffer::Buffer;
@@ -31,9 +32,11 @@ use databend_common_exception::Result;
 use super::ARROW_EXT_TYPE_BITMAP;
 use super::ARROW_EXT_TYPE_EMPTY_ARRAY;
 use super::ARROW_EXT_TYPE_EMPTY_MAP;
+use super::ARROW_EXT_TYPE_ENUM;
 use super::ARROW_EXT_TYPE_GEOGRAPHY;
 use super::ARROW_EXT_TYPE_GEOMETRY;
 use super::ARROW_EXT_TYPE_INTERVAL;
+use super::ARROW_EXT_TYPE_UUID;
 use super::ARROW_EXT_TYPE_VARIANT;
 use super::EXTENSION_KEY;
 use crate::types::ArrayColumn;
@@ -76,6 +79,8 @@ impl TryFrom<&Field> for TableField {
             ARROW_EXT_TYPE_GEOMETRY => TableDataType::Geometry,
             ARROW_EXT_TYPE_GEOGRAPHY => TableDataType::Geography,
             ARROW_EXT_TYPE_INTERVAL => TableDataType::Interval,
+            ARROW_EXT_TYPE_UUID => TableDataType::String,
+            ARROW_EXT_TYPE_ENUM => TableDataType::String,
             _ => match arrow_f.data_type() {
                 ArrowDataType::Null => TableDataType::Null,
                 ArrowDataType::Boolean => TableDataType::Boolean,
@@ -111,6 +116,10 @@ impl TryFrom<&Field> for TableField {
                 ArrowDataType::Timestamp(_, _) => TableDataType::Timestamp,
                 ArrowDataType::Date32 => TableDataType::Date,
                 ArrowDataType::Date64 => TableDataType::Date,
+                // there is no time type, the time of day is read as the microseconds since midnight
+                ArrowDataType::Time32(_) | ArrowDataType::Time64(_) => {
+                    TableDataType::Number(NumberDataType::Int64)
+                }
                 ArrowDataType::List(field) => {
                     let inner_type = TableField::try_from(field.as_ref())?;
                     TableDataType::Array(Box::new(inner_type.data_type))
@@ -150,6 +159,13 @@ impl TryFrom<&Field> for TableField {
                         fields_type,
                     }
                 }
+                // the half floats of the external parquet files, e.g. FLOAT16
+                ArrowDataType::Float16 => {
+                    return Err(ErrorCode::Unimplemented(format!(
+                        "Unsupported column '{}' of type Float16",
+                        arrow_f.name()
+                    )));
+                }
                 arrow_type => {
                     return Err(ErrorCode::Internal(format!(
                         "Unsupported Arrow type: {:?}",
@@ -241,6 +257,16 @@ impl Column {
             DataType::EmptyArray => Column::EmptyArray { len: array.len() },
             DataType::EmptyMap => Column::EmptyMap { len: array.len() },
             DataType::Number(_ty) => {
+                let array = match array.data_type() {
+                    ArrowDataType::Time32(_) | ArrowDataType::Time64(_) => {
+                        let array = arrow_cast::cast(
+                            array.as_ref(),
+                            &ArrowDataType::Time64(arrow_schema::TimeUnit::Microsecond),
+                        )?;
+                        arrow_cast::cast(array.as_ref(), &ArrowDataType::Int64)?
+                    }
+                    _ => array,
+                };
                 let col = NumberColumn::try_from_arrow_data(array.to_data())?;
                 Column::Number(col)
             }
@@ -336,7 +362,7 @@ impl Column {
 
             DataType::Binary => Column::Binary(try_to_binary_column(array)?),
             DataType::Bitmap => Column::Bitmap(try_to_binary_column(array)?),
-            DataType::Variant => Column::Variant(try_to_binary_column(array)?),
+            DataType::Variant => Column::Variant(try_to_variant_column(array)?),
             DataType::Geometry => Column::Geometry(try_to_binary_column(array)?),
             DataType::Geography => Column::Geography(GeographyColumn(try_to_binary_column(array)?)),
             DataType::Generic(_) => unreachable!("Generic type is not supported"),
@@ -361,14 +387,74 @@ fn try_to_binary_column(array: ArrayRef) -> Result<BinaryColumn> {
     Ok(BinaryColumn::new(values.into(), offsets.into()))
 }
 
+// The JSON columns of the external parquet files are strings, which are parsed into JSONB.
+fn try_to_variant_column(array: ArrayRef) -> Result<BinaryColumn> {
+    if !matches!(
+        array.data_type(),
+        ArrowDataType::Utf8 | ArrowDataType::LargeUtf8 | ArrowDataType::Utf8View
+    ) {
+        return try_to_binary_column(array);
+    }
+    let array = arrow_cast::cast(array.as_ref(), &ArrowDataType::LargeUtf8)?;
+    let array = array
+        .as_any()
+        .downcast_ref::<arrow_array::LargeStringArray>()
+        .ok_or_else(|| {
+            ErrorCode::Internal(format!(
+                "Cannot downcast to LargeStringArray from array: {:?}",
+                array
+            ))
+        })?;
+    let mut builder = BinaryColumnBuilder::with_capacity(array.len(), array.value_data().len());
+    for i in 0..array.len() {
+        // the NULL values are left empty, they are masked by the validity
+        if array.is_valid(i) {
+            let value = jsonb::parse_value(array.value(i).as_bytes()).map_err(|err| {
+                ErrorCode::BadBytes(format!("Invalid JSON value {:?}: {err}", array.value(i)))
+            })?;
+            value.write_to_vec(&mut builder.data);
+        }
+        builder.commit_row();
+    }
+    Ok(builder.build())
+}
+
 // Convert from `ArrayData` into BinaryColumn ignores the validity
 fn try_to_string_column(array: ArrayRef) -> Result<StringColumn> {
-    let array = if !matches!(array.data_type(), ArrowDataType::Utf8View) {
-        arrow_cast::cast(array.as_ref(), &ArrowDataType::Utf8View)?
-    } else {
-        array
+    let array = match array.data_type() {
+        ArrowDataType::Utf8View => array,
+        // UUID is the only string stored as fixed size binary
+        ArrowDataType::FixedSizeBinary(16) => {
+            let array = array
+                .as_any()
+                .downcast_ref::<arrow_array::FixedSizeBinaryArray>()
+                .ok_or_else(|| {
+                    ErrorCode::Internal(format!(
+                        "Cannot downcast to FixedSizeBinaryArray from array: {:?}",
+                        array
+                    ))
+                })?;
+            let array = array
+                .iter()
+                .map(|value| value.map(format_uuid))
+                .collect::<arrow_array::StringViewArray>();
+            Arc::new(array)
+        }
+        _ => arrow_cast::cast(array.as_ref(), &ArrowDataType::Utf8View)?,
     };
 
     let data = array.to_data();
     Ok(data.into())
 }
+
+// Format the 16 bytes as `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx`.
+fn format_uuid(bytes: &[u8]) -> String {
+    let mut uuid = String::with_capacity(36);
+    for (i, byte) in bytes.iter().enumerate() {
+        if matches!(i, 4 | 6 | 8 | 10) {
+            uuid.push('-');
+        }
+        uuid.push_str(&format!("{:02x}", byte));
+    }
+    uuid
+}
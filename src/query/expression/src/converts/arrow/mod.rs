@@ -23,3 +23,7 @@ pub const ARROW_EXT_TYPE_BITMAP: &str = "Bitmap";
 pub const ARROW_EXT_TYPE_GEOMETRY: &str = "Geometry";
 pub const ARROW_EXT_TYPE_GEOGRAPHY: &str = "Geography";
 pub const ARROW_EXT_TYPE_INTERVAL: &str = "Interval";
+// UUID of the external parquet files, it is read as the canonical string form.
+pub const ARROW_EXT_TYPE_UUID: &str = "Uuid";
+// ENUM of the external parquet files, whose values are stored as binary.
+pub const ARROW_EXT_TYPE_ENUM: &str = "Enum";
@@ -26,8 +26,10 @@ pub use port::connect;
 pub use port::InputPort;
 pub use port::OutputPort;
 pub use port_trigger::DirectedEdge;
+pub use port_trigger::ScheduleRecord;
 pub use port_trigger::UpdateList;
 pub use port_trigger::UpdateTrigger;
+pub use port_trigger::UpdateTriggerHandle;
 pub use processor::Event;
 pub use processor::EventCause;
 pub use processor::Processor;
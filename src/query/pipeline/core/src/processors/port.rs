@@ -24,6 +24,7 @@ use databend_common_exception::Result;
 use databend_common_expression::DataBlock;
 
 use crate::processors::UpdateTrigger;
+use crate::processors::UpdateTriggerHandle;
 use crate::unsafe_cell_wrap::UnSafeCellWrap;
 
 const HAS_DATA: usize = 0b1;
@@ -127,6 +128,8 @@ impl SharedStatus {
 pub struct InputPort {
     shared: UnSafeCellWrap<Arc<SharedStatus>>,
     update_trigger: UnSafeCellWrap<*mut UpdateTrigger>,
+    // keeps the trigger alive after the update list is cleared
+    trigger_handle: UnSafeCellWrap<Option<UpdateTriggerHandle>>,
 }
 
 impl InputPort {
@@ -134,6 +137,7 @@ impl InputPort {
         Arc::new(InputPort {
             shared: UnSafeCellWrap::create(SharedStatus::create()),
             update_trigger: UnSafeCellWrap::create(std::ptr::null_mut()),
+            trigger_handle: UnSafeCellWrap::create(None),
         })
     }
 
@@ -212,8 +216,9 @@ impl InputPort {
     /// # Safety
     ///
     /// Method is thread unsafe and require thread safe call
-    pub unsafe fn set_trigger(&self, update_trigger: *mut UpdateTrigger) {
-        self.update_trigger.set_value(update_trigger)
+    pub unsafe fn set_trigger(&self, update_trigger: UpdateTriggerHandle) {
+        self.update_trigger.set_value(update_trigger.as_ptr());
+        self.trigger_handle.set_value(Some(update_trigger));
     }
 }
 
@@ -221,6 +226,8 @@ pub struct OutputPort {
     record_profile: UnSafeCellWrap<bool>,
     shared: UnSafeCellWrap<Arc<SharedStatus>>,
     update_trigger: UnSafeCellWrap<*mut UpdateTrigger>,
+    // keeps the trigger alive after the update list is cleared
+    trigger_handle: UnSafeCellWrap<Option<UpdateTriggerHandle>>,
 }
 
 impl OutputPort {
@@ -229,6 +236,7 @@ impl OutputPort {
             record_profile: UnSafeCellWrap::create(false),
             shared: UnSafeCellWrap::create(SharedStatus::create()),
             update_trigger: UnSafeCellWrap::create(std::ptr::null_mut()),
+            trigger_handle: UnSafeCellWrap::create(None),
         })
     }
 
@@ -297,8 +305,9 @@ impl OutputPort {
     /// # Safety
     ///
     /// Method is thread unsafe and require thread safe call
-    pub unsafe fn set_trigger(&self, update_trigger: *mut UpdateTrigger) {
-        self.update_trigger.set_value(update_trigger)
+    pub unsafe fn set_trigger(&self, update_trigger: UpdateTriggerHandle) {
+        self.update_trigger.set_value(update_trigger.as_ptr());
+        self.trigger_handle.set_value(Some(update_trigger));
     }
 
     /// # Safety
@@ -13,9 +13,12 @@
 // limitations under the License.
 
 use std::cell::UnsafeCell;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::sync::Arc;
+use std::time::Instant;
 
 use databend_common_exception::ErrorCode;
 use databend_common_exception::Result;
@@ -33,6 +36,7 @@ unsafe impl Send for UpdateList {}
 
 unsafe impl Sync for UpdateList {}
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum DirectedEdge {
     Source(EdgeIndex),
     Target(EdgeIndex),
@@ -68,9 +72,57 @@ impl DirectedEdge {
     }
 }
 
+/// An edge drained by `UpdateList::trigger` to be scheduled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduleRecord {
+    pub time: Instant,
+    pub edge: DirectedEdge,
+}
+
+impl ScheduleRecord {
+    /// Resolve the node which updated the edge and the node the edge is scheduled to.
+    pub fn resolve<N: Debug, E: Debug>(
+        &self,
+        graph: &StableGraph<N, E>,
+    ) -> Result<(NodeIndex, NodeIndex)> {
+        let target = self.edge.get_target(graph)?;
+        Ok((self.edge.get_source(graph), target))
+    }
+}
+
+/// A ring buffer of the most recent scheduling decisions, for post-mortem debugging.
+struct ScheduleTrace {
+    capacity: usize,
+    records: VecDeque<ScheduleRecord>,
+}
+
+impl ScheduleTrace {
+    fn push(&mut self, record: ScheduleRecord) {
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+}
+
+/// A trigger shared by the `UpdateList` which created it and the port it is set to.
+///
+/// The port keeps the trigger alive after `UpdateList::clear` detached it from the list,
+/// so the pointer used by the port never dangles.
+#[derive(Clone)]
+pub struct UpdateTriggerHandle(Arc<UnsafeCell<UpdateTrigger>>);
+
+impl UpdateTriggerHandle {
+    pub fn as_ptr(&self) -> *mut UpdateTrigger {
+        self.0.get()
+    }
+}
+
 pub struct UpdateListMutable {
-    updated_edges: Vec<DirectedEdge>,
-    updated_triggers: Vec<Arc<UnsafeCell<UpdateTrigger>>>,
+    updated_edges: Vec<(DirectedEdge, usize)>,
+    updated_triggers: Vec<UpdateTriggerHandle>,
+    sink_first: bool,
+    trace: Option<ScheduleTrace>,
 }
 
 impl UpdateList {
@@ -79,6 +131,8 @@ impl UpdateList {
             inner: UnsafeCell::new(UpdateListMutable {
                 updated_edges: vec![],
                 updated_triggers: vec![],
+                sink_first: false,
+                trace: None,
             }),
         })
     }
@@ -92,26 +146,147 @@ impl UpdateList {
         let inner = &mut *self.inner.get();
 
         for trigger in &inner.updated_triggers {
-            UpdateTrigger::trigger_version(trigger.get());
+            UpdateTrigger::trigger_version(trigger.as_ptr());
+        }
+
+        if inner.sink_first {
+            // the reordering leaves the edges pending in the queue behind, schedule them once
+            if !queue.is_empty() && !inner.updated_edges.is_empty() {
+                let queued_edges = queue.iter().copied().collect::<HashSet<_>>();
+                inner
+                    .updated_edges
+                    .retain(|(edge, _)| !queued_edges.contains(edge));
+            }
+            // edges closer to the sinks are in the front of the queue
+            inner
+                .updated_edges
+                .sort_by_key(|(_, sink_distance)| *sink_distance);
+        }
+
+        match &mut inner.trace {
+            Some(trace) => {
+                let time = Instant::now();
+                while let Some((edge, _)) = inner.updated_edges.pop() {
+                    trace.push(ScheduleRecord { time, edge });
+                    queue.push_front(edge);
+                }
+            }
+            None => {
+                while let Some((edge, _)) = inner.updated_edges.pop() {
+                    queue.push_front(edge);
+                }
+            }
+        }
+    }
+
+    /// Record the edges drained by `trigger` into a ring buffer keeping the latest `capacity` ones,
+    /// a zero capacity disables the trace. The buffer grows with the records up to `capacity`,
+    /// nothing is allocated upfront. The trace is updated in the same critical section as
+    /// `trigger`, without any extra synchronization.
+    ///
+    /// # Safety
+    ///
+    /// Must be thread safe call. In other words, it needs to be called in single thread or in mutex guard.
+    pub unsafe fn enable_trace(&self, capacity: usize) {
+        let inner = &mut *self.inner.get();
+        inner.trace = (capacity > 0).then(|| ScheduleTrace {
+            capacity,
+            records: VecDeque::new(),
+        });
+    }
+
+    /// The recorded scheduling decisions, from the oldest to the latest.
+    ///
+    /// # Safety
+    ///
+    /// Must be thread safe call. In other words, it needs to be called in single thread or in mutex guard.
+    pub unsafe fn dump_trace(&self) -> Vec<ScheduleRecord> {
+        let inner = &*self.inner.get();
+        match &inner.trace {
+            Some(trace) => trace.records.iter().copied().collect(),
+            None => vec![],
+        }
+    }
+
+    /// Schedule the edges closer to the sinks first, to drain the output faster.
+    ///
+    /// An updated edge which is still pending in the queue is not pushed again by `trigger`.
+    ///
+    /// # Safety
+    ///
+    /// Must be thread safe call. In other words, it needs to be called in single thread or in mutex guard.
+    pub unsafe fn enable_sink_first(&self, sink_distances: &HashMap<EdgeIndex, usize>) {
+        let inner = &mut *self.inner.get();
+
+        for trigger in &inner.updated_triggers {
+            let trigger = &mut *trigger.as_ptr();
+            if let Some(sink_distance) = sink_distances.get(&trigger.index) {
+                trigger.sink_distance = *sink_distance;
+            }
+        }
+
+        inner.sink_first = true;
+    }
+
+    /// Reset all triggers and drop pending edges, used when the pipeline is re-initialized.
+    ///
+    /// # Safety
+    ///
+    /// Must be thread safe call. In other words, it needs to be called in single thread or in mutex guard.
+    pub unsafe fn reset(&self) {
+        let inner = &mut *self.inner.get();
+
+        for trigger in &inner.updated_triggers {
+            UpdateTrigger::reset(trigger.as_ptr());
+        }
+
+        inner.updated_edges.clear();
+    }
+
+    /// Drop all triggers, pending edges and traced records, used when the pipeline is torn down
+    /// to be rebuilt.
+    ///
+    /// The triggers are detached before they are dropped, the ports still holding them keep them
+    /// alive, and their updates are ignored until the ports are set with new triggers.
+    ///
+    /// # Safety
+    ///
+    /// Must be thread safe call. In other words, it needs to be called in single thread or in mutex guard.
+    pub unsafe fn clear(&self) {
+        let inner = &mut *self.inner.get();
+        for trigger in &inner.updated_triggers {
+            UpdateTrigger::detach(trigger.as_ptr());
         }
 
-        while let Some(index) = inner.updated_edges.pop() {
-            queue.push_front(index);
+        inner.updated_edges.clear();
+        inner.updated_triggers.clear();
+        inner.sink_first = false;
+        if let Some(trace) = &mut inner.trace {
+            trace.records.clear();
         }
     }
 
+    /// Returns true if the list has neither triggers nor pending edges.
+    ///
+    /// # Safety
+    ///
+    /// Must be thread safe call. In other words, it needs to be called in single thread or in mutex guard.
+    pub unsafe fn is_empty(&self) -> bool {
+        let inner = &*self.inner.get();
+        inner.updated_edges.is_empty() && inner.updated_triggers.is_empty()
+    }
+
     /// Create schedule trigger for the port
     ///
     /// # Safety
     ///
     /// Must be thread safe call. In other words, it needs to be called in single thread or in mutex guard.
-    pub unsafe fn create_trigger(self: &Arc<Self>, edge_index: EdgeIndex) -> *mut UpdateTrigger {
+    pub unsafe fn create_trigger(self: &Arc<Self>, edge_index: EdgeIndex) -> UpdateTriggerHandle {
         let inner = &mut *self.inner.get();
         let update_trigger = UpdateTrigger::create(edge_index, self.inner.get());
-        inner
-            .updated_triggers
-            .push(Arc::new(UnsafeCell::new(update_trigger)));
-        inner.updated_triggers.last().unwrap().get()
+        let handle = UpdateTriggerHandle(Arc::new(UnsafeCell::new(update_trigger)));
+        inner.updated_triggers.push(handle.clone());
+        handle
     }
 }
 
@@ -120,6 +295,8 @@ pub struct UpdateTrigger {
     update_list: *mut UpdateListMutable,
     version: usize,
     prev_version: usize,
+    // distance from the edge to the nearest sink
+    sink_distance: usize,
 }
 
 unsafe impl Send for UpdateTrigger {}
@@ -131,6 +308,7 @@ impl UpdateTrigger {
             update_list,
             version: 0,
             prev_version: 0,
+            sink_distance: 0,
         }
     }
 
@@ -143,6 +321,25 @@ impl UpdateTrigger {
         (*self_).prev_version = (*self_).version;
     }
 
+    /// Detach from the update list, the later updates are ignored.
+    ///
+    /// # Safety
+    ///
+    /// *mut UpdateTrigger must be a safe pointer
+    pub unsafe fn detach(self_: *mut UpdateTrigger) {
+        (*self_).update_list = std::ptr::null_mut();
+    }
+
+    /// Reset the versions, so that the next update will be triggered.
+    ///
+    /// # Safety
+    ///
+    /// *mut UpdateTrigger must be a safe pointer
+    pub unsafe fn reset(self_: *mut UpdateTrigger) {
+        (*self_).version = 0;
+        (*self_).prev_version = 0;
+    }
+
     /// Trigger node input edge. Executor will schedule this edge.
     ///
     /// # Safety
@@ -152,10 +349,12 @@ impl UpdateTrigger {
     pub unsafe fn update_input(self_: &*mut UpdateTrigger) {
         if !self_.is_null() {
             let self_ = &mut **self_;
-            if self_.version == self_.prev_version {
+            if self_.version == self_.prev_version && !self_.update_list.is_null() {
                 self_.version += 1;
                 let inner = &mut *self_.update_list;
-                inner.updated_edges.push(DirectedEdge::Target(self_.index));
+                inner
+                    .updated_edges
+                    .push((DirectedEdge::Target(self_.index), self_.sink_distance));
             }
         }
     }
@@ -169,10 +368,12 @@ impl UpdateTrigger {
     pub unsafe fn update_output(self_: &*mut UpdateTrigger) {
         if !self_.is_null() {
             let self_ = &mut **self_;
-            if self_.version == self_.prev_version {
+            if self_.version == self_.prev_version && !self_.update_list.is_null() {
                 self_.version += 1;
                 let inner = &mut *self_.update_list;
-                inner.updated_edges.push(DirectedEdge::Source(self_.index));
+                inner
+                    .updated_edges
+                    .push((DirectedEdge::Source(self_.index), self_.sink_distance));
             }
         }
     }
@@ -14,5 +14,7 @@
 
 mod duplicate;
 mod port_test;
+mod port_trigger;
 mod resize;
+mod schedule_trace;
 mod shuffle;
@@ -0,0 +1,238 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use databend_common_pipeline_core::processors::DirectedEdge;
+use databend_common_pipeline_core::processors::InputPort;
+use databend_common_pipeline_core::processors::ScheduleRecord;
+use databend_common_pipeline_core::processors::UpdateList;
+use databend_common_pipeline_core::processors::UpdateTrigger;
+use petgraph::prelude::EdgeIndex;
+use petgraph::prelude::StableGraph;
+
+#[test]
+fn test_update_trigger_dedup() {
+    unsafe {
+        let mut queue = VecDeque::new();
+        let update_list = UpdateList::create();
+        let trigger = update_list.create_trigger(EdgeIndex::new(0)).as_ptr();
+
+        UpdateTrigger::update_input(&trigger);
+        UpdateTrigger::update_output(&trigger);
+        update_list.trigger(&mut queue);
+        assert_eq!(queue.len(), 1);
+        assert!(matches!(queue[0], DirectedEdge::Target(_)));
+
+        // next scheduling cycle
+        queue.clear();
+        UpdateTrigger::update_output(&trigger);
+        update_list.trigger(&mut queue);
+        assert_eq!(queue.len(), 1);
+        assert!(matches!(queue[0], DirectedEdge::Source(_)));
+    }
+}
+
+#[test]
+fn test_update_trigger_reset() {
+    unsafe {
+        let mut queue = VecDeque::new();
+        let update_list = UpdateList::create();
+        let trigger = update_list.create_trigger(EdgeIndex::new(0)).as_ptr();
+
+        // leave a stale version without entering the next scheduling cycle
+        UpdateTrigger::update_input(&trigger);
+        UpdateTrigger::update_input(&trigger);
+        update_list.reset();
+        update_list.trigger(&mut queue);
+        assert!(queue.is_empty());
+
+        // the first update after reset fires
+        UpdateTrigger::update_input(&trigger);
+        update_list.trigger(&mut queue);
+        assert_eq!(queue.len(), 1);
+        assert!(matches!(queue[0], DirectedEdge::Target(_)));
+    }
+}
+
+#[test]
+fn test_update_list_clear() {
+    unsafe {
+        let mut queue = VecDeque::new();
+        let update_list = UpdateList::create();
+        update_list.enable_trace(4);
+        let input = InputPort::create();
+        input.set_trigger(update_list.create_trigger(EdgeIndex::new(0)));
+        let trigger = update_list.create_trigger(EdgeIndex::new(1));
+
+        UpdateTrigger::update_output(&trigger.as_ptr());
+        update_list.trigger(&mut queue);
+        assert_eq!(update_list.dump_trace().len(), 1);
+
+        // leave a pending edge
+        queue.clear();
+        UpdateTrigger::update_output(&trigger.as_ptr());
+        assert!(!update_list.is_empty());
+
+        update_list.clear();
+        assert!(update_list.is_empty());
+        assert!(update_list.dump_trace().is_empty());
+        update_list.trigger(&mut queue);
+        assert!(queue.is_empty());
+
+        // the cleared triggers are kept alive by their holders, but no longer update the list
+        input.set_need_data();
+        UpdateTrigger::reset(trigger.as_ptr());
+        UpdateTrigger::update_output(&trigger.as_ptr());
+        assert!(update_list.is_empty());
+        update_list.trigger(&mut queue);
+        assert!(queue.is_empty());
+
+        // the list is usable with fresh triggers
+        let trigger = update_list.create_trigger(EdgeIndex::new(2)).as_ptr();
+        assert!(!update_list.is_empty());
+        UpdateTrigger::update_output(&trigger);
+        update_list.trigger(&mut queue);
+        assert_eq!(queue.len(), 1);
+        assert!(matches!(queue[0], DirectedEdge::Source(index) if index == EdgeIndex::new(2)));
+    }
+}
+
+#[test]
+fn test_update_trigger_sink_first() {
+    // source -(edge 0)-> transform -(edge 1)-> sink
+    let source_edge = EdgeIndex::new(0);
+    let sink_edge = EdgeIndex::new(1);
+    let sink_distances = HashMap::from([(source_edge, 1), (sink_edge, 0)]);
+
+    for sink_first in [false, true] {
+        unsafe {
+            let mut queue = VecDeque::new();
+            let update_list = UpdateList::create();
+            let input_trigger = update_list.create_trigger(source_edge).as_ptr();
+            let output_trigger = update_list.create_trigger(sink_edge).as_ptr();
+            if sink_first {
+                update_list.enable_sink_first(&sink_distances);
+            }
+
+            UpdateTrigger::update_input(&input_trigger);
+            UpdateTrigger::update_output(&output_trigger);
+            update_list.trigger(&mut queue);
+            assert_eq!(queue.len(), 2);
+
+            let expected = if sink_first { sink_edge } else { source_edge };
+            match queue.pop_front().unwrap() {
+                DirectedEdge::Source(index) | DirectedEdge::Target(index) => {
+                    assert_eq!(index, expected)
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_update_trigger_sink_first_pending() {
+    let sink_distances = HashMap::from([(EdgeIndex::new(0), 1), (EdgeIndex::new(1), 0)]);
+
+    for sink_first in [false, true] {
+        unsafe {
+            let mut queue = VecDeque::new();
+            let update_list = UpdateList::create();
+            let input_trigger = update_list.create_trigger(EdgeIndex::new(0)).as_ptr();
+            let output_trigger = update_list.create_trigger(EdgeIndex::new(1)).as_ptr();
+            if sink_first {
+                update_list.enable_sink_first(&sink_distances);
+            }
+
+            UpdateTrigger::update_input(&input_trigger);
+            update_list.trigger(&mut queue);
+            assert_eq!(queue.len(), 1);
+
+            // the input edge is updated again in the next cycle while it is still pending
+            UpdateTrigger::update_input(&input_trigger);
+            UpdateTrigger::update_output(&output_trigger);
+            update_list.trigger(&mut queue);
+
+            let expected = match sink_first {
+                true => vec![
+                    DirectedEdge::Source(EdgeIndex::new(1)),
+                    DirectedEdge::Target(EdgeIndex::new(0)),
+                ],
+                false => vec![
+                    DirectedEdge::Target(EdgeIndex::new(0)),
+                    DirectedEdge::Source(EdgeIndex::new(1)),
+                    DirectedEdge::Target(EdgeIndex::new(0)),
+                ],
+            };
+            assert_eq!(queue.into_iter().collect::<Vec<_>>(), expected);
+        }
+    }
+}
+
+#[test]
+fn test_update_list_trace() {
+    let mut graph = StableGraph::<(), ()>::new();
+    let nodes = [graph.add_node(()), graph.add_node(()), graph.add_node(())];
+    let e0 = graph.add_edge(nodes[0], nodes[1], ());
+    let e1 = graph.add_edge(nodes[1], nodes[2], ());
+
+    unsafe {
+        let mut queue = VecDeque::new();
+        let update_list = UpdateList::create();
+        let t0 = update_list.create_trigger(e0).as_ptr();
+        let t1 = update_list.create_trigger(e1).as_ptr();
+
+        // nothing is recorded before the trace is enabled
+        UpdateTrigger::update_input(&t0);
+        update_list.trigger(&mut queue);
+        assert!(update_list.dump_trace().is_empty());
+
+        update_list.enable_trace(3);
+        // the edges are drained from the latest updated one
+        UpdateTrigger::update_output(&t0);
+        UpdateTrigger::update_input(&t1);
+        update_list.trigger(&mut queue);
+        UpdateTrigger::update_input(&t0);
+        update_list.trigger(&mut queue);
+        UpdateTrigger::update_output(&t1);
+        update_list.trigger(&mut queue);
+
+        // the oldest decision `Target(e1)` is evicted
+        let trace = update_list.dump_trace();
+        let edges = trace.iter().map(|record| record.edge).collect::<Vec<_>>();
+        assert_eq!(edges, vec![
+            DirectedEdge::Source(e0),
+            DirectedEdge::Target(e0),
+            DirectedEdge::Source(e1),
+        ]);
+        assert!(trace.windows(2).all(|w| w[0].time <= w[1].time));
+
+        let resolved = trace
+            .iter()
+            .map(|record: &ScheduleRecord| record.resolve(&graph).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(resolved, vec![
+            (nodes[0], nodes[1]),
+            (nodes[1], nodes[0]),
+            (nodes[1], nodes[2]),
+        ]);
+
+        // a zero capacity disables the trace
+        update_list.enable_trace(0);
+        UpdateTrigger::update_input(&t1);
+        update_list.trigger(&mut queue);
+        assert!(update_list.dump_trace().is_empty());
+    }
+}
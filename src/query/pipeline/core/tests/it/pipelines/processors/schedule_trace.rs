@@ -0,0 +1,267 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Golden schedule traces of the update triggers.
+//!
+//! A source -> transform -> sink chain is driven the same way the executor does: pop an edge
+//! from the queue, call the event of the node it points to, then enter the next scheduling
+//! cycle of the node's `UpdateList`. Any change in the order of the scheduled edges shows up
+//! as a diff of `tests/it/testdata/schedule_trace.txt`.
+//!
+//! If the change is intended, regenerate the trace with
+//! `env REGENERATE_GOLDENFILES=1 cargo test -p databend-common-pipeline-core schedule_trace`
+//! and review it with `git diff`.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::io::Write;
+use std::sync::Arc;
+
+use databend_common_expression::DataBlock;
+use databend_common_pipeline_core::processors::connect;
+use databend_common_pipeline_core::processors::DirectedEdge;
+use databend_common_pipeline_core::processors::InputPort;
+use databend_common_pipeline_core::processors::OutputPort;
+use databend_common_pipeline_core::processors::UpdateList;
+use goldenfile::Mint;
+use petgraph::prelude::EdgeIndex;
+use petgraph::prelude::NodeIndex;
+use petgraph::prelude::StableGraph;
+
+const MAX_STEPS: usize = 100;
+
+enum MockProcessor {
+    Source {
+        output: Arc<OutputPort>,
+        remaining: usize,
+    },
+    Transform {
+        input: Arc<InputPort>,
+        output: Arc<OutputPort>,
+    },
+    Sink {
+        input: Arc<InputPort>,
+    },
+}
+
+impl MockProcessor {
+    fn name(&self) -> &'static str {
+        match self {
+            MockProcessor::Source { .. } => "source",
+            MockProcessor::Transform { .. } => "transform",
+            MockProcessor::Sink { .. } => "sink",
+        }
+    }
+
+    // Returns the name of the action and whether the processor is finished.
+    fn event(&mut self) -> (&'static str, bool) {
+        match self {
+            MockProcessor::Source { output, remaining } => {
+                if !output.can_push() {
+                    return ("wait", false);
+                }
+
+                if *remaining == 0 {
+                    output.finish();
+                    return ("finish", true);
+                }
+
+                output.push_data(Ok(DataBlock::empty()));
+                *remaining -= 1;
+                ("push", false)
+            }
+            MockProcessor::Transform { input, output } => {
+                if output.is_finished() {
+                    input.finish();
+                    return ("finish", true);
+                }
+
+                if !output.can_push() {
+                    input.set_not_need_data();
+                    return ("wait", false);
+                }
+
+                if input.has_data() {
+                    let data = input.pull_data().unwrap();
+                    output.push_data(data);
+                    return ("transform", false);
+                }
+
+                if input.is_finished() {
+                    output.finish();
+                    return ("finish", true);
+                }
+
+                input.set_need_data();
+                ("need_data", false)
+            }
+            MockProcessor::Sink { input } => {
+                let action = match input.has_data() {
+                    true => {
+                        input.pull_data().unwrap().unwrap();
+                        "consume"
+                    }
+                    false => "need_data",
+                };
+
+                if input.is_finished() {
+                    return ("finish", true);
+                }
+
+                input.set_need_data();
+                (action, false)
+            }
+        }
+    }
+}
+
+struct MockNode {
+    processor: MockProcessor,
+    update_list: Arc<UpdateList>,
+    finished: bool,
+}
+
+impl Debug for MockNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.processor.name())
+    }
+}
+
+fn edge_name(edge: &DirectedEdge) -> String {
+    match edge {
+        DirectedEdge::Source(index) => format!("Source({})", index.index()),
+        DirectedEdge::Target(index) => format!("Target({})", index.index()),
+    }
+}
+
+fn create_graph(blocks: usize) -> StableGraph<MockNode, ()> {
+    let source_output = OutputPort::create();
+    let transform_input = InputPort::create();
+    let transform_output = OutputPort::create();
+    let sink_input = InputPort::create();
+
+    let mut graph = StableGraph::new();
+    let mut add_node = |processor| {
+        graph.add_node(MockNode {
+            processor,
+            update_list: UpdateList::create(),
+            finished: false,
+        })
+    };
+
+    let source = add_node(MockProcessor::Source {
+        output: source_output.clone(),
+        remaining: blocks,
+    });
+    let transform = add_node(MockProcessor::Transform {
+        input: transform_input.clone(),
+        output: transform_output.clone(),
+    });
+    let sink = add_node(MockProcessor::Sink {
+        input: sink_input.clone(),
+    });
+
+    let ports = [
+        (source, transform, &transform_input, &source_output),
+        (transform, sink, &sink_input, &transform_output),
+    ];
+
+    for (from, to, input, output) in ports {
+        let edge_index = graph.add_edge(from, to, ());
+
+        // Same as the executor: the input trigger notifies the node owning the input port,
+        // the output trigger notifies the node owning the output port.
+        unsafe {
+            connect(input, output);
+            input.set_trigger(graph[to].update_list.create_trigger(edge_index));
+            output.set_trigger(graph[from].update_list.create_trigger(edge_index));
+        }
+    }
+
+    graph
+}
+
+fn schedule(
+    graph: &mut StableGraph<MockNode, ()>,
+    node_index: NodeIndex,
+    queue: &mut VecDeque<DirectedEdge>,
+) -> String {
+    let node = &mut graph[node_index];
+    let action = match node.finished {
+        true => "finished",
+        false => {
+            let (action, finished) = node.processor.event();
+            node.finished = finished;
+            action
+        }
+    };
+
+    unsafe { node.update_list.trigger(queue) };
+
+    let queue = queue.iter().map(edge_name).collect::<Vec<_>>();
+    format!(
+        "{}: {}, queue: [{}]",
+        node.processor.name(),
+        action,
+        queue.join(", ")
+    )
+}
+
+fn write_trace(file: &mut impl Write, sink_first: bool) {
+    let mut graph = create_graph(2);
+
+    if sink_first {
+        let sink_distances = HashMap::from([(EdgeIndex::new(0), 1), (EdgeIndex::new(1), 0)]);
+        for node_index in graph.node_indices() {
+            unsafe {
+                graph[node_index]
+                    .update_list
+                    .enable_sink_first(&sink_distances)
+            };
+        }
+    }
+
+    writeln!(file, "---------- sink_first: {} ----------", sink_first).unwrap();
+
+    let mut queue = VecDeque::new();
+    let node_indices = graph.node_indices().collect::<Vec<_>>();
+    for node_index in node_indices {
+        let step = schedule(&mut graph, node_index, &mut queue);
+        writeln!(file, "init -> {}", step).unwrap();
+    }
+
+    let mut steps = 0;
+    while let Some(edge) = queue.pop_front() {
+        steps += 1;
+        assert!(steps <= MAX_STEPS, "the schedule does not converge");
+
+        let node_index = edge.get_target(&graph).unwrap();
+        let step = schedule(&mut graph, node_index, &mut queue);
+        writeln!(file, "{:>2}: {} -> {}", steps, edge_name(&edge), step).unwrap();
+    }
+
+    assert!(graph.node_indices().all(|index| graph[index].finished));
+    writeln!(file).unwrap();
+}
+
+#[test]
+fn test_schedule_trace() {
+    let mut mint = Mint::new("tests/it/testdata");
+    let file = &mut mint.new_goldenfile("schedule_trace.txt").unwrap();
+
+    write_trace(file, false);
+    write_trace(file, true);
+}
@@ -0,0 +1,290 @@
+// Copyright 2021 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[macro_use]
+extern crate criterion;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow_array::ArrayRef;
+use bytes::Bytes;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use criterion::Throughput;
+use databend_common_catalog::plan::Projection;
+use databend_common_expression::types::NumberDataType;
+use databend_common_expression::Column;
+use databend_common_expression::ColumnId;
+use databend_common_expression::DataBlock;
+use databend_common_expression::RandomOptions;
+use databend_common_expression::TableDataType;
+use databend_common_expression::TableField;
+use databend_common_expression::TableSchemaRef;
+use databend_common_expression::TableSchemaRefExt;
+use databend_common_storages_fuse::io::read::DataItem;
+use databend_common_storages_fuse::io::serialize_block;
+use databend_common_storages_fuse::io::BlockReader;
+use databend_common_storages_fuse::io::WriteSettings;
+use databend_common_storages_fuse::FuseStorageFormat;
+use databend_query::test_kits::TestFixture;
+use databend_storages_common_cache::SizedColumnArray;
+use databend_storages_common_table_meta::meta::BlockMeta;
+use databend_storages_common_table_meta::meta::ColumnMeta;
+use databend_storages_common_table_meta::meta::Compression;
+use opendal::Operator;
+
+/// Benchmark `BlockReader::deserialize_parquet_chunks`, through `BlockReader::deserialize_chunks`.
+///
+/// - cold: all column chunks are raw (compressed) bytes, they are decompressed and decoded
+///   into arrow arrays, then converted to columns.
+/// - warm: all column chunks hit the table data array cache, only the conversion from the
+///   cached arrow arrays to columns is measured.
+///
+/// The block is built and serialized in memory, no external storage is required.
+/// Throughput is reported against the size of the serialized block.
+fn bench_deserialize_parquet_chunks(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let fixture = runtime.block_on(TestFixture::setup()).unwrap();
+
+    let mut group = c.benchmark_group("deserialize_parquet_chunks");
+    group.sample_size(20);
+
+    for num_columns in [4, 16, 64] {
+        for num_rows in [8192, 65536] {
+            let input = BenchInput::create(&runtime, &fixture, num_columns, num_rows);
+            let id = format!("{num_columns}_cols/{num_rows}_rows");
+            group.throughput(Throughput::Bytes(input.size as u64));
+
+            group.bench_with_input(BenchmarkId::new("cold", &id), &input, |b, input| {
+                b.iter(|| criterion::black_box(input.deserialize(input.raw_column_chunks())))
+            });
+
+            let cached = input.cached_arrays();
+            group.bench_with_input(BenchmarkId::new("warm", &id), &input, |b, input| {
+                b.iter(|| criterion::black_box(input.deserialize(cached_column_chunks(&cached))))
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Benchmark the decode of many small blocks of the same schema.
+///
+/// - per_block: each block is decoded by `BlockReader::deserialize_chunks`, which builds
+///   the decode schemas for each block.
+/// - shared: the blocks are decoded by `BlockReader::deserialize_many`, which builds
+///   the decode schemas once and shares them by the blocks.
+///
+/// The blocks are small, so the setup takes a large part of the decode of a block.
+fn bench_deserialize_many(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let fixture = runtime.block_on(TestFixture::setup()).unwrap();
+
+    let mut group = c.benchmark_group("deserialize_many");
+    group.sample_size(20);
+
+    let num_blocks = 64;
+    for num_columns in [16, 64] {
+        let input = BenchInput::create(&runtime, &fixture, num_columns, 128);
+        let id = format!("{num_columns}_cols/{num_blocks}_blocks");
+        group.throughput(Throughput::Elements(num_blocks as u64));
+
+        group.bench_with_input(BenchmarkId::new("per_block", &id), &input, |b, input| {
+            b.iter(|| {
+                for _ in 0..num_blocks {
+                    criterion::black_box(input.deserialize(input.raw_column_chunks()));
+                }
+            })
+        });
+
+        let metas = vec![input.block_meta(); num_blocks];
+        group.bench_with_input(BenchmarkId::new("shared", &id), &input, |b, input| {
+            b.iter(|| {
+                let chunks_per_block = (0..num_blocks).map(|_| input.raw_column_chunks()).collect();
+                criterion::black_box(
+                    input
+                        .block_reader
+                        .deserialize_many(&metas, chunks_per_block)
+                        .unwrap(),
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+struct BenchInput {
+    block_reader: Arc<BlockReader>,
+    schema: TableSchemaRef,
+    num_rows: usize,
+    size: usize,
+    column_chunks: HashMap<ColumnId, Bytes>,
+    column_metas: HashMap<ColumnId, ColumnMeta>,
+    compression: Compression,
+}
+
+impl BenchInput {
+    fn create(
+        runtime: &tokio::runtime::Runtime,
+        fixture: &TestFixture,
+        num_columns: usize,
+        num_rows: usize,
+    ) -> Self {
+        let schema = build_schema(num_columns);
+        let options = RandomOptions {
+            seed: Some(0),
+            min_string_len: 8,
+            max_string_len: 32,
+            max_array_len: 3,
+        };
+        let columns = schema
+            .fields()
+            .iter()
+            .map(|f| Column::random(&f.data_type().into(), num_rows, Some(options.clone())))
+            .collect::<Vec<_>>();
+        let block = DataBlock::new_from_columns(columns);
+
+        let write_settings = WriteSettings::default();
+        let mut buf = Vec::new();
+        let column_metas = serialize_block(&write_settings, &schema, block, &mut buf).unwrap();
+        let data = Bytes::from(buf);
+        let column_chunks = column_metas
+            .iter()
+            .map(|(column_id, meta)| {
+                let (offset, len) = meta.offset_length();
+                let range = offset as usize..(offset + len) as usize;
+                (*column_id, data.slice(range))
+            })
+            .collect();
+
+        // the chunks are given to the reader, the operator is never used to read them.
+        let ctx = runtime.block_on(fixture.new_query_ctx()).unwrap();
+        let operator = Operator::new(opendal::services::Memory::default())
+            .unwrap()
+            .finish();
+        let projection = Projection::Columns((0..num_columns).collect());
+        let block_reader = BlockReader::create(
+            ctx,
+            operator,
+            schema.clone(),
+            projection,
+            false,
+            false,
+            false,
+        )
+        .unwrap();
+
+        Self {
+            block_reader,
+            schema,
+            num_rows,
+            size: data.len(),
+            column_chunks,
+            column_metas,
+            compression: write_settings.table_compression.into(),
+        }
+    }
+
+    fn deserialize(&self, column_chunks: HashMap<ColumnId, DataItem>) -> DataBlock {
+        self.block_reader
+            .deserialize_chunks(
+                "bench",
+                self.num_rows,
+                &self.compression,
+                &self.column_metas,
+                column_chunks,
+                &FuseStorageFormat::Parquet,
+            )
+            .unwrap()
+    }
+
+    fn raw_column_chunks(&self) -> HashMap<ColumnId, DataItem> {
+        self.column_chunks
+            .iter()
+            .map(|(column_id, data)| (*column_id, DataItem::RawData(data.clone())))
+            .collect()
+    }
+
+    fn block_meta(&self) -> BlockMeta {
+        BlockMeta::new(
+            self.num_rows as u64,
+            self.size as u64,
+            self.size as u64,
+            HashMap::new(),
+            self.column_metas.clone(),
+            None,
+            ("bench".to_string(), 0),
+            None,
+            0,
+            None,
+            self.compression,
+            None,
+        )
+    }
+
+    // Simulate the array cache being populated by a previous (cold) read.
+    fn cached_arrays(&self) -> HashMap<ColumnId, Arc<SizedColumnArray>> {
+        let block = self.deserialize(self.raw_column_chunks());
+        self.schema
+            .fields()
+            .iter()
+            .zip(block.columns())
+            .map(|(f, entry)| {
+                let array: ArrayRef = entry.to_column(self.num_rows).into_arrow_rs();
+                let len = self
+                    .column_metas
+                    .get(&f.column_id)
+                    .unwrap()
+                    .offset_length()
+                    .1;
+                (f.column_id, Arc::new((array, len as usize)))
+            })
+            .collect()
+    }
+}
+
+fn cached_column_chunks(
+    cached: &HashMap<ColumnId, Arc<SizedColumnArray>>,
+) -> HashMap<ColumnId, DataItem> {
+    cached
+        .iter()
+        .map(|(column_id, array)| (*column_id, DataItem::ColumnArray(array)))
+        .collect()
+}
+
+// A representative mix of fixed-size, variable-size and nullable leaf columns.
+fn build_schema(num_columns: usize) -> TableSchemaRef {
+    let types = [
+        TableDataType::Number(NumberDataType::Int64),
+        TableDataType::Number(NumberDataType::Float64),
+        TableDataType::String,
+        TableDataType::Nullable(Box::new(TableDataType::Number(NumberDataType::Int32))),
+        TableDataType::Timestamp,
+        TableDataType::Nullable(Box::new(TableDataType::String)),
+        TableDataType::Boolean,
+        TableDataType::Date,
+    ];
+    let fields = (0..num_columns)
+        .map(|i| TableField::new(&format!("c{i}"), types[i % types.len()].clone()))
+        .collect::<Vec<_>>();
+    TableSchemaRefExt::create(fields)
+}
+
+criterion_group!(
+    benches,
+    bench_deserialize_parquet_chunks,
+    bench_deserialize_many
+);
+criterion_main!(benches);
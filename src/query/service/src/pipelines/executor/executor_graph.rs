@@ -41,6 +41,7 @@ use databend_common_pipeline_core::Pipeline;
 use databend_common_pipeline_core::PlanProfile;
 use fastrace::prelude::*;
 use log::debug;
+use log::info;
 use log::trace;
 use log::warn;
 use parking_lot::Condvar;
@@ -67,8 +68,9 @@ use crate::pipelines::processors::Event;
 use crate::pipelines::processors::InputPort;
 use crate::pipelines::processors::OutputPort;
 use crate::pipelines::processors::ProcessorPtr;
+use crate::pipelines::processors::ScheduleRecord;
 use crate::pipelines::processors::UpdateList;
-use crate::pipelines::processors::UpdateTrigger;
+use crate::pipelines::processors::UpdateTriggerHandle;
 
 enum State {
     Idle,
@@ -159,7 +161,19 @@ impl Node {
         self.updated_list.trigger(queue)
     }
 
-    pub unsafe fn create_trigger(&self, index: EdgeIndex) -> *mut UpdateTrigger {
+    pub unsafe fn enable_sink_first(&self, edge_distances: &HashMap<EdgeIndex, usize>) {
+        self.updated_list.enable_sink_first(edge_distances)
+    }
+
+    pub unsafe fn enable_trace(&self, capacity: usize) {
+        self.updated_list.enable_trace(capacity)
+    }
+
+    pub unsafe fn reset_triggers(&self) {
+        self.updated_list.reset()
+    }
+
+    pub unsafe fn create_trigger(&self, index: EdgeIndex) -> UpdateTriggerHandle {
         self.updated_list.create_trigger(index)
     }
 }
@@ -334,6 +348,44 @@ impl ExecutingGraph {
         }
     }
 
+    /// Compute the distance from each edge to the nearest sink,
+    /// and let the triggers schedule the edges closer to the sinks first.
+    ///
+    /// # Safety
+    ///
+    /// Method is thread unsafe and require thread safe call
+    pub unsafe fn enable_sink_first_scheduling(locker: &StateLockGuard) {
+        let graph = &locker.graph;
+        let mut node_distances = HashMap::with_capacity(graph.node_count());
+        let mut queue = VecDeque::new();
+        for sink_index in graph.externals(Direction::Outgoing) {
+            node_distances.insert(sink_index, 0);
+            queue.push_back(sink_index);
+        }
+
+        while let Some(node_index) = queue.pop_front() {
+            let distance = node_distances[&node_index] + 1;
+            for prev_index in graph.neighbors_directed(node_index, Direction::Incoming) {
+                if let Entry::Vacant(v) = node_distances.entry(prev_index) {
+                    v.insert(distance);
+                    queue.push_back(prev_index);
+                }
+            }
+        }
+
+        // the distance of an edge is the distance of its target node
+        let mut edge_distances = HashMap::with_capacity(graph.edge_count());
+        for edge_index in graph.edge_indices() {
+            let (_, target) = graph.edge_endpoints(edge_index).unwrap();
+            let distance = node_distances.get(&target).copied().unwrap_or(usize::MAX);
+            edge_distances.insert(edge_index, distance);
+        }
+
+        for node in graph.node_weights() {
+            node.enable_sink_first(&edge_distances);
+        }
+    }
+
     /// # Safety
     ///
     /// Method is thread unsafe and require thread safe call
@@ -342,6 +394,11 @@ impl ExecutingGraph {
         capacity: usize,
         graph: &Arc<RunningGraph>,
     ) -> Result<ScheduleQueue> {
+        // Stale trigger versions from a previous run may suppress the first updates.
+        for node in locker.graph.node_weights() {
+            node.reset_triggers();
+        }
+
         let mut schedule_queue = ScheduleQueue::with_capacity(capacity);
         for sink_index in locker.graph.externals(Direction::Outgoing) {
             ExecutingGraph::schedule_queue(locker, sink_index, &mut schedule_queue, graph)?;
@@ -713,6 +770,81 @@ impl RunningGraph {
         Ok(Arc::new(RunningGraph(graph_state)))
     }
 
+    /// # Safety
+    ///
+    /// Method is thread unsafe and require thread safe call
+    pub unsafe fn enable_sink_first_scheduling(&self) {
+        ExecutingGraph::enable_sink_first_scheduling(&self.0)
+    }
+
+    /// Record the latest `capacity` edges scheduled by each node, see `dump_schedule_trace`.
+    ///
+    /// # Safety
+    ///
+    /// Method is thread unsafe and require thread safe call
+    pub unsafe fn enable_schedule_trace(&self, capacity: usize) {
+        for node in self.0.graph.node_weights() {
+            node.enable_trace(capacity);
+        }
+    }
+
+    /// The recorded scheduling decisions of all the nodes with the resolved source and target
+    /// nodes of the edges, ordered by time.
+    pub fn dump_schedule_trace(&self) -> Result<Vec<(ScheduleRecord, NodeIndex, NodeIndex)>> {
+        let graph = &self.0.graph;
+        let mut records = vec![];
+        for node_index in graph.node_indices() {
+            let node = &graph[node_index];
+            // the trace is updated under the state lock of the node when it is triggered
+            let trace = {
+                let _guard = node.state.lock().unwrap_or_else(PoisonError::into_inner);
+                unsafe { node.updated_list.dump_trace() }
+            };
+            for record in trace {
+                let (source, target) = record.resolve(graph)?;
+                records.push((record, source, target));
+            }
+        }
+        records.sort_by_key(|(record, _, _)| record.time);
+        Ok(records)
+    }
+
+    /// The recorded scheduling decisions, one `+<elapsed> <source> -> <target>` line per edge,
+    /// with the elapsed time since the first decision. `None` if nothing is recorded.
+    pub fn format_schedule_trace(&self) -> Result<Option<String>> {
+        let records = self.dump_schedule_trace()?;
+        let Some((first, _, _)) = records.first() else {
+            return Ok(None);
+        };
+        let start = first.time;
+        let lines = records
+            .iter()
+            .map(|(record, source, target)| {
+                format!(
+                    "+{:?} {} -> {}",
+                    record.time.duration_since(start),
+                    source.index(),
+                    target.index()
+                )
+            })
+            .collect::<Vec<_>>();
+        Ok(Some(lines.join("\n")))
+    }
+
+    /// Log the recorded scheduling decisions, for the post-mortem debugging of the aborted
+    /// or timed out queries. Nothing is logged if the trace is not enabled.
+    pub fn log_schedule_trace(&self) {
+        match self.format_schedule_trace() {
+            Ok(Some(trace)) => info!(
+                "Schedule trace of the aborted query {:?}:\n{}",
+                self.get_query_id(),
+                trace
+            ),
+            Ok(None) => {}
+            Err(cause) => warn!("Dump schedule trace error: {:?}", cause),
+        }
+    }
+
     /// # Safety
     ///
     /// Method is thread unsafe and require thread safe call
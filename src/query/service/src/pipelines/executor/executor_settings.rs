@@ -23,6 +23,9 @@ pub struct ExecutorSettings {
     pub query_id: Arc<String>,
     pub max_threads: u64,
     pub enable_queries_executor: bool,
+    pub enable_sink_first_scheduling: bool,
+    /// The number of the latest scheduled edges recorded by each node, 0 disables the trace.
+    pub schedule_trace_capacity: usize,
     pub max_execute_time_in_seconds: Duration,
     pub executor_node_id: String,
 }
@@ -36,6 +39,8 @@ impl ExecutorSettings {
 
         Ok(ExecutorSettings {
             enable_queries_executor: settings.get_enable_experimental_queries_executor()?,
+            enable_sink_first_scheduling: settings.get_enable_sink_first_scheduling()?,
+            schedule_trace_capacity: settings.get_executor_schedule_trace_capacity()? as usize,
             query_id: Arc::new(query_id),
             max_execute_time_in_seconds: Duration::from_secs(max_execute_time_in_seconds),
             max_threads,
@@ -79,6 +79,13 @@ impl PipelineExecutor {
                 Some(finish_condvar.clone()),
             )?;
 
+            if settings.enable_sink_first_scheduling {
+                unsafe { graph.enable_sink_first_scheduling() };
+            }
+            if settings.schedule_trace_capacity > 0 {
+                unsafe { graph.enable_schedule_trace(settings.schedule_trace_capacity) };
+            }
+
             Ok(PipelineExecutor::QueriesPipelineExecutor(QueryWrapper {
                 graph,
                 settings,
@@ -134,6 +141,13 @@ impl PipelineExecutor {
                 Some(finish_condvar.clone()),
             )?;
 
+            if settings.enable_sink_first_scheduling {
+                unsafe { graph.enable_sink_first_scheduling() };
+            }
+            if settings.schedule_trace_capacity > 0 {
+                unsafe { graph.enable_schedule_trace(settings.schedule_trace_capacity) };
+            }
+
             Ok(PipelineExecutor::QueriesPipelineExecutor(QueryWrapper {
                 graph,
                 settings,
@@ -203,6 +217,9 @@ impl PipelineExecutor {
                         finished_chain.apply(info)
                     }
                     Some(cause) => {
+                        if cause.code() == ErrorCode::ABORTED_QUERY {
+                            query_wrapper.graph.log_schedule_trace();
+                        }
                         let mut finished_chain = query_wrapper.on_finished_chain.lock();
                         let profiling = self.fetch_profiling(true);
                         let info = ExecutionInfo::create(Err(cause.clone()), profiling);
@@ -173,6 +173,13 @@ impl QueryPipelineExecutor {
         settings: ExecutorSettings,
         lock_guards: Vec<Arc<LockGuard>>,
     ) -> Result<Arc<QueryPipelineExecutor>> {
+        if settings.enable_sink_first_scheduling {
+            unsafe { graph.enable_sink_first_scheduling() };
+        }
+        if settings.schedule_trace_capacity > 0 {
+            unsafe { graph.enable_schedule_trace(settings.schedule_trace_capacity) };
+        }
+
         let workers_condvar = WorkersCondvar::create(threads_num);
         let global_tasks_queue = QueryExecutorTasksQueue::create(threads_num);
 
@@ -245,6 +252,11 @@ impl QueryPipelineExecutor {
                     let may_error = error.clone();
                     drop(finished_error_guard);
 
+                    // the query is killed or exceeds the maximum execution time
+                    if may_error.code() == ErrorCode::ABORTED_QUERY {
+                        self.graph.log_schedule_trace();
+                    }
+
                     let profiling = self.fetch_plans_profile(true);
                     self.on_finished(ExecutionInfo::create(Err(may_error.clone()), profiling))?;
                     return Err(may_error);
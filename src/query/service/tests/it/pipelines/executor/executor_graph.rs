@@ -218,6 +218,50 @@ async fn test_simple_schedule_queue() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_format_schedule_trace() -> Result<()> {
+    let fixture = TestFixture::setup().await?;
+    let ctx = fixture.new_query_ctx().await?;
+
+    for trace_capacity in [0, 16] {
+        let pipeline = create_simple_pipeline(ctx.clone())?;
+        if trace_capacity > 0 {
+            unsafe { pipeline.enable_schedule_trace(trace_capacity) };
+        }
+        let init_queue = unsafe { pipeline.clone().init_schedule_queue(0)? };
+        unsafe {
+            let _ = init_queue.sync_queue.front().unwrap().processor.process();
+        }
+        for index in [2, 1, 0, 1, 2] {
+            let scheduled = unsafe { pipeline.clone().schedule_queue(NodeIndex::new(index))? };
+            if let Some(task) = scheduled.sync_queue.front() {
+                unsafe {
+                    let _ = task.processor.process();
+                }
+            }
+        }
+
+        let trace = pipeline.format_schedule_trace()?;
+        if trace_capacity == 0 {
+            // nothing is recorded if the trace is not enabled
+            assert_eq!(trace, None);
+            continue;
+        }
+        let trace = trace.unwrap();
+        assert!(trace.starts_with("+0ns "), "{trace}");
+        for line in trace.lines() {
+            // the edges are scheduled between the adjacent nodes of 0 -> 1 -> 2
+            let (_, edge) = line.split_once(' ').unwrap();
+            let (source, target) = edge.split_once(" -> ").unwrap();
+            let source = source.parse::<usize>().unwrap();
+            let target = target.parse::<usize>().unwrap();
+            assert_eq!(source.abs_diff(target), 1, "{trace}");
+        }
+    }
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_parallel_schedule_queue() -> Result<()> {
     let fixture = TestFixture::setup().await?;
@@ -560,6 +604,8 @@ async fn create_executor_with_simple_pipeline(
         query_id: Arc::new("".to_string()),
         max_execute_time_in_seconds: Default::default(),
         enable_queries_executor: false,
+        enable_sink_first_scheduling: false,
+        schedule_trace_capacity: 0,
         max_threads: 8,
         executor_node_id: "".to_string(),
     };
@@ -55,6 +55,8 @@ fn create_pipeline(
         query_id: Arc::new("".to_string()),
         max_execute_time_in_seconds: Default::default(),
         enable_queries_executor: false,
+        enable_sink_first_scheduling: false,
+        schedule_trace_capacity: 0,
         max_threads: 8,
         executor_node_id: "".to_string(),
     };
@@ -12,14 +12,48 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow_array::ArrayRef;
+use arrow_array::Int32Array;
+use arrow_array::StringArray;
+use arrow_schema::Schema;
+use databend_common_base::runtime::metrics::MetricValue;
+use databend_common_base::runtime::metrics::ScopedRegistry;
+use databend_common_base::runtime::ThreadTracker;
+use databend_common_catalog::plan::Projection;
+use databend_common_catalog::table::Table;
 use databend_common_config::InnerConfig;
+use databend_common_exception::ErrorCode;
 use databend_common_exception::Result;
+use databend_common_expression::types::NumberScalar;
 use databend_common_expression::DataBlock;
+use databend_common_expression::Scalar;
+use databend_common_storages_fuse::io::read::DataItem;
+use databend_common_storages_fuse::io::BlockReader;
+use databend_common_storages_fuse::io::ContiguousColumn;
+use databend_common_storages_fuse::io::MetaReaders;
+use databend_common_storages_fuse::FuseTable;
+use databend_query::sessions::TableContext;
 use databend_query::storages::fuse::io::TableMetaLocationGenerator;
 use databend_query::test_kits::TestFixture;
+use databend_storages_common_cache::CacheAccessor;
+use databend_storages_common_cache::CacheManager;
+use databend_storages_common_cache::LoadParams;
+use databend_storages_common_cache::TableDataCacheKey;
+use databend_storages_common_io::ReadSettings;
+use databend_storages_common_table_meta::meta::BlockMeta;
 use databend_storages_common_table_meta::meta::TableSnapshot;
 use databend_storages_common_table_meta::meta::Versioned;
 use futures_util::TryStreamExt;
+use parquet::arrow::ArrowWriter;
+use parquet::file::properties::WriterProperties;
+use parquet::format::PageHeader;
+use parquet::thrift::TSerializable;
+use thrift::protocol::TCompactInputProtocol;
+use thrift::protocol::TCompactOutputProtocol;
 use uuid::Uuid;
 
 #[test]
@@ -81,3 +115,1303 @@ async fn test_array_cache_of_nested_column_iusse_14502() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_array_cache_read_only_policy() -> Result<()> {
+    let fixture = with_array_cache().await?;
+
+    fixture.create_default_database().await?;
+    let db = fixture.default_db_name();
+
+    fixture
+        .execute_command(&format!(
+            "create table {db}.t1(a int, b int) storage_format = Parquet"
+        ))
+        .await?;
+    fixture
+        .execute_command(&format!("insert into {db}.t1 values(1, 2), (3, 4)"))
+        .await?;
+
+    let cache = CacheManager::instance()
+        .get_table_data_array_cache()
+        .unwrap();
+    let settings = fixture.default_session().get_settings();
+    let query = |sql: String| {
+        let fixture = &fixture;
+        async move {
+            fixture
+                .execute_query(&sql)
+                .await?
+                .try_collect::<Vec<DataBlock>>()
+                .await
+        }
+    };
+
+    // cold cache, read only: nothing is written
+    settings.set_setting(
+        "table_data_cache_policy".to_string(),
+        "read_only".to_string(),
+    )?;
+    let expected = query(format!("select a from {db}.t1 order by a")).await?;
+    assert_eq!(cache.len(), 0);
+
+    // read write: the column `a` is cached
+    settings.set_setting(
+        "table_data_cache_policy".to_string(),
+        "read_write".to_string(),
+    )?;
+    query(format!("select a from {db}.t1 order by a")).await?;
+    let num_cached = cache.len();
+    assert!(num_cached > 0);
+
+    // warm cache, read only: the cached column is served, the column `b` is not cached
+    settings.set_setting(
+        "table_data_cache_policy".to_string(),
+        "read_only".to_string(),
+    )?;
+    let actual = query(format!("select a from {db}.t1 order by a")).await?;
+    assert_eq!(format!("{:?}", actual), format!("{:?}", expected));
+    query(format!("select b from {db}.t1 order by b")).await?;
+    assert_eq!(cache.len(), num_cached);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_array_cache_concurrent_population() -> Result<()> {
+    let fixture = with_array_cache().await?;
+
+    fixture.create_default_database().await?;
+    let db = fixture.default_db_name();
+
+    for table in ["t1", "t2"] {
+        fixture
+            .execute_command(&format!(
+                "create table {db}.{table}(a int, b string) storage_format = Parquet"
+            ))
+            .await?;
+        fixture
+            .execute_command(&format!(
+                "insert into {db}.{table} values(1, 'a'), (2, 'b'), (3, 'c')"
+            ))
+            .await?;
+    }
+
+    let cache = CacheManager::instance()
+        .get_table_data_array_cache()
+        .unwrap();
+    let query = |sql: String| {
+        let fixture = &fixture;
+        async move {
+            fixture
+                .execute_query(&sql)
+                .await?
+                .try_collect::<Vec<DataBlock>>()
+                .await
+        }
+    };
+
+    // the same block is read by a single reader
+    let (len, bytes) = (cache.len(), cache.bytes_size());
+    query(format!("select a, b from {db}.t1")).await?;
+    let (single_len, single_bytes) = (cache.len() - len, cache.bytes_size() - bytes);
+    assert_eq!(single_len, 2);
+
+    // the same block is read by two concurrent readers
+    let (len, bytes) = (cache.len(), cache.bytes_size());
+    let sql = format!("select a, b from {db}.t2");
+    let (r1, r2) = futures::join!(query(sql.clone()), query(sql));
+    assert_eq!(format!("{:?}", r1?), format!("{:?}", r2?));
+
+    // one entry per column, and each of them is accounted once
+    assert_eq!(cache.len() - len, single_len);
+    assert_eq!(cache.bytes_size() - bytes, single_bytes);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_array_cache_max_entry_bytes() -> Result<()> {
+    let fixture = with_array_cache().await?;
+
+    fixture.create_default_database().await?;
+    let db = fixture.default_db_name();
+
+    // the chunk of `a` is a few kilobytes, the chunk of the random strings `b` is much larger
+    for table in ["t1", "t2"] {
+        fixture
+            .execute_command(&format!(
+                "create table {db}.{table}(a int, b string) storage_format = Parquet"
+            ))
+            .await?;
+        fixture
+            .execute_command(&format!(
+                "insert into {db}.{table} select number, uuid() from numbers(2000)"
+            ))
+            .await?;
+    }
+
+    let cache = CacheManager::instance()
+        .get_table_data_array_cache()
+        .unwrap();
+    let settings = fixture.default_session().get_settings();
+    let query = |sql: String| {
+        let fixture = &fixture;
+        async move {
+            fixture
+                .execute_query(&sql)
+                .await?
+                .try_collect::<Vec<DataBlock>>()
+                .await
+        }
+    };
+
+    // only the small array of `a` is cached
+    settings.set_setting(
+        "table_data_array_cache_max_entry_bytes".to_string(),
+        "16384".to_string(),
+    )?;
+    let len = cache.len();
+    query(format!("select a, b from {db}.t1")).await?;
+    assert_eq!(cache.len() - len, 1);
+
+    // unlimited, both arrays are cached
+    settings.set_setting(
+        "table_data_array_cache_max_entry_bytes".to_string(),
+        "0".to_string(),
+    )?;
+    let len = cache.len();
+    query(format!("select a, b from {db}.t2")).await?;
+    assert_eq!(cache.len() - len, 2);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_read_single_column() -> Result<()> {
+    let fixture = TestFixture::setup().await?;
+    fixture.create_default_database().await?;
+    let db = fixture.default_db_name();
+
+    for storage_format in ["parquet", "native"] {
+        fixture
+            .execute_command(&format!(
+                "create or replace table {db}.t(a int, b string) storage_format = '{storage_format}'"
+            ))
+            .await?;
+        fixture
+            .execute_command(&format!("insert into {db}.t values(1, 'a'), (2, null)"))
+            .await?;
+        // the blocks written before get the default value of `c`
+        fixture
+            .execute_command(&format!(
+                "alter table {db}.t add column c string default 'x'"
+            ))
+            .await?;
+        fixture
+            .execute_command(&format!("insert into {db}.t values(3, 'c', 'y')"))
+            .await?;
+        fixture
+            .execute_command(&format!(
+                "insert into {db}.t values(4, 'd', null), (5, 'e', 'z')"
+            ))
+            .await?;
+
+        let ctx = fixture.new_query_ctx().await?;
+        let table = ctx.get_table(&ctx.get_current_catalog(), &db, "t").await?;
+        let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+        let metas = load_block_metas(fuse_table).await?;
+        assert_eq!(metas.len(), 3);
+
+        let block_reader = fuse_table.create_block_reader(
+            ctx.clone(),
+            Projection::Columns(vec![0, 1, 2]),
+            false,
+            false,
+            false,
+        )?;
+        let table_ctx: Arc<dyn TableContext> = ctx.clone();
+        let read_settings = ReadSettings::from_ctx(&table_ctx)?;
+        let format = fuse_table.get_storage_format();
+
+        let mut blocks = vec![];
+        for meta in &metas {
+            let block = block_reader
+                .read_by_meta(&read_settings, meta, &format)
+                .await?;
+            blocks.push(block);
+        }
+
+        // the columns are in the order of the blocks, however many reads are in flight
+        for max_concurrent_io in [0, 1, 2] {
+            let read_settings = ReadSettings {
+                max_concurrent_io,
+                ..read_settings
+            };
+            for (i, field) in table.schema().fields().iter().enumerate() {
+                let columns = block_reader
+                    .read_single_column(&read_settings, field.column_id, &metas, &format)
+                    .await?;
+                assert_eq!(columns.len(), blocks.len());
+                for (column, block) in columns.iter().zip(blocks.iter()) {
+                    let entry = block.get_by_offset(i);
+                    let expected = entry
+                        .value
+                        .convert_to_full_column(&entry.data_type, block.num_rows());
+                    assert_eq!(column, &expected, "{storage_format}: {}", field.name());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_read_column_validity() -> Result<()> {
+    let fixture = TestFixture::setup().await?;
+    fixture.create_default_database().await?;
+    let db = fixture.default_db_name();
+
+    for storage_format in ["parquet", "native"] {
+        fixture
+            .execute_command(&format!(
+                "create or replace table {db}.t(a int not null, b string) storage_format = '{storage_format}'"
+            ))
+            .await?;
+        fixture
+            .execute_command(&format!(
+                "insert into {db}.t values(1, 'a'), (2, null), (3, null)"
+            ))
+            .await?;
+        // the blocks written before are all null in `c`
+        fixture
+            .execute_command(&format!("alter table {db}.t add column c int"))
+            .await?;
+        fixture
+            .execute_command(&format!(
+                "insert into {db}.t values(4, null, 1), (5, 'e', null)"
+            ))
+            .await?;
+
+        let ctx = fixture.new_query_ctx().await?;
+        let table = ctx.get_table(&ctx.get_current_catalog(), &db, "t").await?;
+        let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+        let metas = load_block_metas(fuse_table).await?;
+        assert_eq!(metas.len(), 2);
+
+        let block_reader = fuse_table.create_block_reader(
+            ctx.clone(),
+            Projection::Columns(vec![0, 1, 2]),
+            false,
+            false,
+            false,
+        )?;
+        let table_ctx: Arc<dyn TableContext> = ctx.clone();
+        let read_settings = ReadSettings::from_ctx(&table_ctx)?;
+        let format = fuse_table.get_storage_format();
+
+        let mut total_null_counts = vec![0; 3];
+        for meta in &metas {
+            let block = block_reader
+                .read_by_meta(&read_settings, meta, &format)
+                .await?;
+            for (i, field) in table.schema().fields().iter().enumerate() {
+                let (validity, null_count) = block_reader
+                    .read_column_validity(&read_settings, field.column_id, meta, &format)
+                    .await?;
+                let entry = block.get_by_offset(i);
+                let column = entry
+                    .value
+                    .convert_to_full_column(&entry.data_type, block.num_rows());
+                let expected = match column.validity() {
+                    (_, Some(validity)) => validity.null_count(),
+                    (true, None) => block.num_rows(),
+                    (false, None) => 0,
+                };
+                assert_eq!(validity.len(), block.num_rows());
+                assert_eq!(null_count, validity.null_count());
+                assert_eq!(null_count, expected, "{storage_format}: {}", field.name());
+                total_null_counts[i] += null_count;
+            }
+        }
+        assert_eq!(total_null_counts, vec![0, 3, 4], "{storage_format}");
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_read_column_validity_parquet_levels() -> Result<()> {
+    let fixture = TestFixture::setup().await?;
+    fixture.create_default_database().await?;
+    let db = fixture.default_db_name();
+
+    // only the definition levels of the chunks are decoded
+    fixture
+        .execute_command(&format!(
+            "create table {db}.t(a int, b string, c int) storage_format = 'parquet'"
+        ))
+        .await?;
+    fixture
+        .execute_command(&format!(
+            "insert into {db}.t values(1, 'a', null), (null, null, null), (3, null, null)"
+        ))
+        .await?;
+
+    let ctx = fixture.new_query_ctx().await?;
+    let table = ctx.get_table(&ctx.get_current_catalog(), &db, "t").await?;
+    let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+    let meta = load_block_metas(fuse_table).await?.remove(0);
+
+    let block_reader = fuse_table.create_block_reader(
+        ctx.clone(),
+        Projection::Columns(vec![0, 1, 2]),
+        false,
+        false,
+        false,
+    )?;
+    let table_ctx: Arc<dyn TableContext> = ctx.clone();
+    let read_settings = ReadSettings::from_ctx(&table_ctx)?;
+    let format = fuse_table.get_storage_format();
+
+    let schema = table.schema();
+    for (name, expected) in [
+        ("a", vec![true, false, true]),
+        ("b", vec![true, false, false]),
+        // all null, but the chunk is still written
+        ("c", vec![false, false, false]),
+    ] {
+        let column_id = schema.field_with_name(name)?.column_id;
+        assert!(meta.col_metas.contains_key(&column_id));
+        let (validity, null_count) = block_reader
+            .read_column_validity(&read_settings, column_id, &meta, &format)
+            .await?;
+        assert_eq!(validity.iter().collect::<Vec<_>>(), expected, "{name}");
+        assert_eq!(
+            null_count,
+            expected.iter().filter(|v| !**v).count(),
+            "{name}"
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_staged_deserialize() -> Result<()> {
+    let fixture = TestFixture::setup().await?;
+    fixture.create_default_database().await?;
+    let db = fixture.default_db_name();
+
+    fixture
+        .execute_command(&format!(
+            "create table {db}.t(a int, b string, c tuple(c1 int, c2 string), d array(int)) storage_format = 'parquet'"
+        ))
+        .await?;
+    fixture
+        .execute_command(&format!(
+            "insert into {db}.t values(1, 'a', (1, 'x'), [1, 2]), (2, null, (2, null), [])"
+        ))
+        .await?;
+    // the block written before gets the default value of `e`
+    fixture
+        .execute_command(&format!("alter table {db}.t add column e int default 7"))
+        .await?;
+
+    let ctx = fixture.new_query_ctx().await?;
+    let table = ctx.get_table(&ctx.get_current_catalog(), &db, "t").await?;
+    let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+    let meta = load_block_metas(fuse_table).await?.remove(0);
+
+    let block_reader = fuse_table.create_block_reader(
+        ctx.clone(),
+        Projection::Columns(vec![0, 1, 2, 3, 4]),
+        false,
+        false,
+        false,
+    )?;
+    let table_ctx: Arc<dyn TableContext> = ctx.clone();
+    let read_settings = ReadSettings::from_ctx(&table_ctx)?;
+    let format = fuse_table.get_storage_format();
+    let expected = block_reader
+        .read_by_meta(&read_settings, &meta, &format)
+        .await?;
+
+    for max_fields in 1..=5 {
+        let data = block_reader
+            .read_columns_data_by_merge_io(&read_settings, &meta.location.0, &meta.col_metas, &None)
+            .await?;
+        let column_chunks = data.columns_chunks()?;
+
+        let mut state = block_reader.create_staged_deserialize_state(meta.row_count as usize)?;
+        let mut columns = vec![];
+        let mut stages = 0;
+        while !state.is_finished() {
+            let entries = block_reader.deserialize_parquet_stage(
+                &mut state,
+                &meta.col_metas,
+                &column_chunks,
+                &meta.compression,
+                max_fields,
+            )?;
+            assert!(entries.len() <= max_fields);
+            columns.extend(entries);
+            stages += 1;
+        }
+        assert_eq!(stages, 5_usize.div_ceil(max_fields));
+
+        let block = block_reader.finish_staged_deserialize(state, columns)?;
+        assert_eq!(block.num_rows(), expected.num_rows());
+        assert_eq!(block.num_columns(), expected.num_columns());
+        for i in 0..expected.num_columns() {
+            let expected = expected.get_by_offset(i);
+            let actual = block.get_by_offset(i);
+            assert_eq!(actual.data_type, expected.data_type);
+            assert_eq!(
+                actual
+                    .value
+                    .convert_to_full_column(&actual.data_type, block.num_rows()),
+                expected
+                    .value
+                    .convert_to_full_column(&expected.data_type, block.num_rows())
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_deserialize_with_timings() -> Result<()> {
+    let fixture = TestFixture::setup().await?;
+    fixture.create_default_database().await?;
+    let db = fixture.default_db_name();
+
+    fixture
+        .execute_command(&format!(
+            "create table {db}.t(a int, b string, c tuple(c1 int, c2 string)) storage_format = 'parquet'"
+        ))
+        .await?;
+    fixture
+        .execute_command(&format!(
+            "insert into {db}.t values(1, 'a', (1, 'x')), (2, null, (2, null))"
+        ))
+        .await?;
+    // no chunk of `d` in the block, it is filled with the default value
+    fixture
+        .execute_command(&format!("alter table {db}.t add column d int default 7"))
+        .await?;
+
+    let ctx = fixture.new_query_ctx().await?;
+    let table = ctx.get_table(&ctx.get_current_catalog(), &db, "t").await?;
+    let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+    let meta = load_block_metas(fuse_table).await?.remove(0);
+
+    let block_reader = fuse_table.create_block_reader(
+        ctx.clone(),
+        Projection::Columns(vec![0, 1, 2, 3]),
+        false,
+        false,
+        false,
+    )?;
+    let table_ctx: Arc<dyn TableContext> = ctx.clone();
+    let read_settings = ReadSettings::from_ctx(&table_ctx)?;
+    let format = fuse_table.get_storage_format();
+    let expected = block_reader
+        .read_by_meta(&read_settings, &meta, &format)
+        .await?;
+
+    let data = block_reader
+        .read_columns_data_by_merge_io(&read_settings, &meta.location.0, &meta.col_metas, &None)
+        .await?;
+    let column_chunks = data.columns_chunks()?;
+    let (block, timings) = block_reader.deserialize_parquet_chunks_with_timings(
+        meta.row_count as usize,
+        &meta.col_metas,
+        &column_chunks,
+        &meta.compression,
+        &meta.location.0,
+    )?;
+    assert_eq!(format!("{:?}", block), format!("{:?}", expected));
+
+    // one entry for each of `a`, `b` and `c`, but not `d`
+    let schema = table.schema();
+    let mut column_ids = timings.keys().cloned().collect::<Vec<_>>();
+    column_ids.sort();
+    assert_eq!(column_ids, vec![
+        schema.field_with_name("a")?.column_id,
+        schema.field_with_name("b")?.column_id,
+        schema.field_with_name("c")?.column_id,
+    ]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_read_raw_columns() -> Result<()> {
+    let fixture = TestFixture::setup().await?;
+    fixture.create_default_database().await?;
+    let db = fixture.default_db_name();
+
+    for storage_format in ["parquet", "native"] {
+        fixture
+            .execute_command(&format!(
+                "create or replace table {db}.t(a int, b string, c tuple(c1 int, c2 string), d array(int)) storage_format = '{storage_format}'"
+            ))
+            .await?;
+        fixture
+            .execute_command(&format!(
+                "insert into {db}.t values(1, 'a', (1, 'x'), [1, 2]), (2, null, (2, null), [])"
+            ))
+            .await?;
+
+        let ctx = fixture.new_query_ctx().await?;
+        let table = ctx.get_table(&ctx.get_current_catalog(), &db, "t").await?;
+        let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+        let meta = load_block_metas(fuse_table).await?.remove(0);
+
+        let block_reader = fuse_table.create_block_reader(
+            ctx.clone(),
+            Projection::Columns(vec![0, 1, 2, 3]),
+            false,
+            false,
+            false,
+        )?;
+        let table_ctx: Arc<dyn TableContext> = ctx.clone();
+        let read_settings = ReadSettings::from_ctx(&table_ctx)?;
+        let format = fuse_table.get_storage_format();
+        let expected = block_reader
+            .read_by_meta(&read_settings, &meta, &format)
+            .await?;
+
+        // the raw bytes of all the leaf columns are returned, in the compression of the block
+        let raw = block_reader.read_raw_columns(&read_settings, &meta).await?;
+        assert_eq!(raw.compression, meta.compression);
+        let mut column_ids = raw.columns.keys().cloned().collect::<Vec<_>>();
+        column_ids.sort();
+        let mut expected_ids = meta.col_metas.keys().cloned().collect::<Vec<_>>();
+        expected_ids.sort();
+        assert_eq!(column_ids, expected_ids, "{storage_format}");
+        for (column_id, data) in &raw.columns {
+            let (_, len) = meta.col_metas[column_id].offset_length();
+            assert_eq!(data.len() as u64, len, "{storage_format}");
+        }
+
+        // decoding the raw bytes reproduces the block
+        let column_chunks = raw
+            .columns
+            .into_iter()
+            .map(|(column_id, data)| (column_id, DataItem::RawData(data)))
+            .collect();
+        let block = block_reader.deserialize_chunks(
+            &meta.location.0,
+            meta.row_count as usize,
+            &raw.compression,
+            &meta.col_metas,
+            column_chunks,
+            &format,
+        )?;
+        assert_eq!(
+            format!("{:?}", block),
+            format!("{:?}", expected),
+            "{storage_format}"
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_array_cache_mismatched_array() -> Result<()> {
+    let fixture = with_array_cache().await?;
+
+    fixture.create_default_database().await?;
+    let db = fixture.default_db_name();
+
+    fixture
+        .execute_command(&format!(
+            "create table {db}.t(a int, b string) storage_format = 'parquet'"
+        ))
+        .await?;
+    fixture
+        .execute_command(&format!(
+            "insert into {db}.t values(1, 'a'), (2, 'b'), (3, null)"
+        ))
+        .await?;
+
+    let ctx = fixture.new_query_ctx().await?;
+    let table = ctx.get_table(&ctx.get_current_catalog(), &db, "t").await?;
+    let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+    let meta = load_block_metas(fuse_table).await?.remove(0);
+
+    let block_reader = fuse_table.create_block_reader(
+        ctx.clone(),
+        Projection::Columns(vec![0, 1]),
+        false,
+        false,
+        false,
+    )?;
+    let table_ctx: Arc<dyn TableContext> = ctx.clone();
+    let read_settings = ReadSettings::from_ctx(&table_ctx)?;
+    let format = fuse_table.get_storage_format();
+    let expected = block_reader
+        .read_by_meta(&read_settings, &meta, &format)
+        .await?;
+
+    let cache = CacheManager::instance()
+        .get_table_data_array_cache()
+        .unwrap();
+    let column_id = table.schema().field(0).column_id;
+    let (offset, len) = meta.col_metas[&column_id].offset_length();
+    let key = TableDataCacheKey::new(&meta.location.0, column_id, offset, len);
+
+    // the array of another type, e.g. cached before the type of the column is changed,
+    // and the array of another length
+    let stale_arrays: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(vec!["x", "y", "z"])),
+        Arc::new(Int32Array::from(vec![7])),
+    ];
+    for array in stale_arrays {
+        cache.insert(key.as_ref().to_owned(), (array, 0));
+        let block = block_reader
+            .read_by_meta(&read_settings, &meta, &format)
+            .await?;
+        // the column is decoded from the raw bytes, instead of the stale array
+        assert_eq!(block.num_rows(), expected.num_rows());
+        for i in 0..expected.num_columns() {
+            let expected = expected.get_by_offset(i);
+            let actual = block.get_by_offset(i);
+            assert_eq!(actual.data_type, expected.data_type);
+            assert_eq!(
+                actual
+                    .value
+                    .convert_to_full_column(&actual.data_type, block.num_rows()),
+                expected
+                    .value
+                    .convert_to_full_column(&expected.data_type, block.num_rows())
+            );
+        }
+        // and the stale array is evicted
+        assert!(!cache.contains_key(key.as_ref()));
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_deserialize_many() -> Result<()> {
+    let fixture = TestFixture::setup().await?;
+    fixture.create_default_database().await?;
+    let db = fixture.default_db_name();
+
+    fixture
+        .execute_command(&format!(
+            "create table {db}.t(a int, b string, c tuple(c1 int, c2 string)) storage_format = 'parquet'"
+        ))
+        .await?;
+    fixture
+        .execute_command(&format!(
+            "insert into {db}.t values(1, 'a', (1, 'x')), (2, null, (2, null))"
+        ))
+        .await?;
+    // the block written before gets the default value of `d`
+    fixture
+        .execute_command(&format!("alter table {db}.t add column d int default 7"))
+        .await?;
+    for i in 3..5 {
+        fixture
+            .execute_command(&format!(
+                "insert into {db}.t values({i}, 'b', ({i}, 'y'), {i})"
+            ))
+            .await?;
+    }
+
+    let ctx = fixture.new_query_ctx().await?;
+    let table = ctx.get_table(&ctx.get_current_catalog(), &db, "t").await?;
+    let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+    let metas = load_block_metas(fuse_table).await?;
+    assert_eq!(metas.len(), 3);
+
+    let block_reader = fuse_table.create_block_reader(
+        ctx.clone(),
+        Projection::Columns(vec![0, 1, 2, 3]),
+        false,
+        false,
+        false,
+    )?;
+    let table_ctx: Arc<dyn TableContext> = ctx.clone();
+    let read_settings = ReadSettings::from_ctx(&table_ctx)?;
+    let format = fuse_table.get_storage_format();
+
+    let mut expected = vec![];
+    let mut read_results = vec![];
+    for meta in &metas {
+        expected.push(
+            block_reader
+                .read_by_meta(&read_settings, meta, &format)
+                .await?,
+        );
+        read_results.push(
+            block_reader
+                .read_columns_data_by_merge_io(
+                    &read_settings,
+                    &meta.location.0,
+                    &meta.col_metas,
+                    &None,
+                )
+                .await?,
+        );
+    }
+    let chunks_per_block = read_results
+        .iter()
+        .map(|res| res.columns_chunks())
+        .collect::<Result<Vec<_>>>()?;
+    let blocks = block_reader.deserialize_many(&metas, chunks_per_block)?;
+
+    // the same as the blocks decoded one by one
+    assert_eq!(blocks.len(), expected.len());
+    for (block, expected) in blocks.iter().zip(expected.iter()) {
+        assert_eq!(block.num_rows(), expected.num_rows());
+        assert_eq!(block.num_columns(), expected.num_columns());
+        for i in 0..expected.num_columns() {
+            let expected = expected.get_by_offset(i);
+            let actual = block.get_by_offset(i);
+            assert_eq!(actual.data_type, expected.data_type);
+            assert_eq!(
+                actual
+                    .value
+                    .convert_to_full_column(&actual.data_type, block.num_rows()),
+                expected
+                    .value
+                    .convert_to_full_column(&expected.data_type, block.num_rows())
+            );
+        }
+    }
+
+    // the chunks must be given for each block
+    let chunks_per_block = vec![read_results[0].columns_chunks()?];
+    assert!(block_reader
+        .deserialize_many(&metas, chunks_per_block)
+        .is_err());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_deserialize_contiguous() -> Result<()> {
+    let fixture = TestFixture::setup().await?;
+    fixture.create_default_database().await?;
+    let db = fixture.default_db_name();
+
+    fixture
+        .execute_command(&format!(
+            "create table {db}.t(a int not null, b string null) storage_format = 'parquet'"
+        ))
+        .await?;
+    fixture
+        .execute_command(&format!(
+            "insert into {db}.t values(1, 'x'), (-2, null), (3, 'databend')"
+        ))
+        .await?;
+
+    let ctx = fixture.new_query_ctx().await?;
+    let table = ctx.get_table(&ctx.get_current_catalog(), &db, "t").await?;
+    let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+    let metas = load_block_metas(fuse_table).await?;
+    let meta = &metas[0];
+
+    let block_reader = fuse_table.create_block_reader(
+        ctx.clone(),
+        Projection::Columns(vec![0, 1]),
+        false,
+        false,
+        false,
+    )?;
+    let table_ctx: Arc<dyn TableContext> = ctx.clone();
+    let read_settings = ReadSettings::from_ctx(&table_ctx)?;
+    let raw = block_reader
+        .read_columns_data_by_merge_io(&read_settings, &meta.location.0, &meta.col_metas, &None)
+        .await?;
+    let (block, columns) = block_reader.deserialize_parquet_chunks_contiguous(
+        meta.row_count as usize,
+        &meta.col_metas,
+        raw.columns_chunks()?,
+        &meta.compression,
+        &meta.location.0,
+    )?;
+    assert_eq!(block.num_rows(), 3);
+    assert_eq!(columns.len(), block.num_columns());
+
+    // the compacted buffers hold the same values as the block
+    let ints = (0..3)
+        .map(|row| {
+            columns[0]
+                .value_at(row)
+                .map(|v| i32::from_ne_bytes(v.try_into().unwrap()))
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(ints, vec![Some(1), Some(-2), Some(3)]);
+    let strings = (0..3)
+        .map(|row| columns[1].value_at(row))
+        .collect::<Vec<_>>();
+    assert_eq!(strings, vec![
+        Some(b"x".as_slice()),
+        None,
+        Some(b"databend".as_slice())
+    ]);
+    for (i, column) in columns.iter().enumerate() {
+        let entry = block.get_by_offset(i);
+        let expected = entry
+            .value
+            .convert_to_full_column(&entry.data_type, block.num_rows());
+        assert_eq!(column, &ContiguousColumn::try_from_column(&expected)?);
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_read_nested_column_stats() -> Result<()> {
+    let fixture = TestFixture::setup().await?;
+    fixture.create_default_database().await?;
+    let db = fixture.default_db_name();
+
+    fixture
+        .execute_command(&format!(
+            "create table {db}.t(a int, c tuple(c1 int, c2 tuple(c3 string, c4 int))) storage_format = 'parquet'"
+        ))
+        .await?;
+    fixture
+        .execute_command(&format!(
+            "insert into {db}.t values(1, (2, ('x', 20))), (3, (1, ('y', 10)))"
+        ))
+        .await?;
+
+    let ctx = fixture.new_query_ctx().await?;
+    let table = ctx.get_table(&ctx.get_current_catalog(), &db, "t").await?;
+    let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+    let block_meta = load_block_metas(fuse_table).await?.remove(0);
+
+    let schema = table.schema();
+    let a_id = schema.leaf_columns_of(&"a".to_string())[0];
+    let leaf_ids = schema.leaf_columns_of(&"c".to_string());
+    assert_eq!(leaf_ids.len(), 3);
+    let int = |v| Scalar::Number(NumberScalar::Int32(v));
+    let string = |v: &str| Scalar::String(v.to_string());
+    let expected = [
+        (a_id, int(1), int(3)),
+        (leaf_ids[0], int(1), int(2)),
+        (leaf_ids[1], string("x"), string("y")),
+        (leaf_ids[2], int(10), int(20)),
+    ];
+
+    // each leaf of the nested column has its own statistics
+    let block_reader = fuse_table.create_block_reader(
+        ctx.clone(),
+        Projection::Columns(vec![0, 1]),
+        false,
+        false,
+        false,
+    )?;
+    let stats = block_reader.read_column_stats(&block_meta);
+    assert_eq!(stats.len(), expected.len());
+    for (column_id, min, max) in &expected {
+        let column_stats = stats.get(column_id).unwrap();
+        assert_eq!(column_stats.min(), min, "min of column {column_id}");
+        assert_eq!(column_stats.max(), max, "max of column {column_id}");
+        assert_eq!(column_stats.null_count, 0);
+    }
+
+    // only the leaves of the projected inner field `c.c2`
+    let block_reader = fuse_table.create_block_reader(
+        ctx.clone(),
+        Projection::InnerColumns(BTreeMap::from([(0, vec![1, 1])])),
+        false,
+        false,
+        false,
+    )?;
+    let stats = block_reader.read_column_stats(&block_meta);
+    let mut column_ids = stats.keys().copied().collect::<Vec<_>>();
+    column_ids.sort();
+    assert_eq!(column_ids, leaf_ids[1..].to_vec());
+    for (column_id, min, max) in &expected[2..] {
+        let column_stats = stats.get(column_id).unwrap();
+        assert_eq!(column_stats.min(), min);
+        assert_eq!(column_stats.max(), max);
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_deserialized_bytes_of_nested_column() -> Result<()> {
+    let fixture = TestFixture::setup().await?;
+    fixture.create_default_database().await?;
+    let db = fixture.default_db_name();
+
+    for storage_format in ["parquet", "native"] {
+        fixture
+            .execute_command(&format!(
+                "create or replace table {db}.t(a int not null, c tuple(c1 int not null, c2 int not null) not null) storage_format = '{storage_format}'"
+            ))
+            .await?;
+        fixture
+            .execute_command(&format!(
+                "insert into {db}.t values(1, (2, 3)), (4, (5, 6)), (7, (8, 9))"
+            ))
+            .await?;
+
+        let ctx = fixture.new_query_ctx().await?;
+        let table = ctx.get_table(&ctx.get_current_catalog(), &db, "t").await?;
+        let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+        let meta = load_block_metas(fuse_table).await?.remove(0);
+        let format = fuse_table.get_storage_format();
+        let block_reader = fuse_table.create_block_reader(
+            ctx.clone(),
+            Projection::Columns(vec![0, 1]),
+            false,
+            false,
+            false,
+        )?;
+        let table_ctx: Arc<dyn TableContext> = ctx.clone();
+        let read_settings = ReadSettings::from_ctx(&table_ctx)?;
+        let data = block_reader
+            .read_columns_data_by_merge_io(&read_settings, &meta.location.0, &meta.col_metas, &None)
+            .await?;
+
+        // the deserialized bytes are only tracked by the registry of this thread
+        let registry = ScopedRegistry::create(None);
+        {
+            let mut payload = ThreadTracker::new_tracking_payload();
+            payload.metrics = Some(registry.clone());
+            let _guard = ThreadTracker::tracking(payload);
+            let block = block_reader.deserialize_chunks_with_meta(&meta, &format, data)?;
+            assert_eq!(block.num_rows(), 3);
+        }
+        let bytes = registry
+            .dump_sample()?
+            .into_iter()
+            .find(|sample| sample.name == "fuse_remote_io_deserialized_bytes_total")
+            .map(|sample| sample.value);
+
+        // the decoded values of `a`, `c.c1` and `c.c2`, not the size of the chunks
+        assert_eq!(
+            bytes,
+            Some(MetricValue::Counter((3 * 4 * 3) as f64)),
+            "{storage_format}"
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_strict_projection_missing_column() -> Result<()> {
+    let fixture = TestFixture::setup().await?;
+    fixture.create_default_database().await?;
+    let db = fixture.default_db_name();
+
+    for storage_format in ["parquet", "native"] {
+        fixture
+            .execute_command(&format!(
+                "create or replace table {db}.t(a int, b int) storage_format = '{storage_format}'"
+            ))
+            .await?;
+        fixture
+            .execute_command(&format!("insert into {db}.t values(1, 2), (3, 4)"))
+            .await?;
+        // the block written before has no column `c`
+        fixture
+            .execute_command(&format!("alter table {db}.t add column c int default 7"))
+            .await?;
+
+        let ctx = fixture.new_query_ctx().await?;
+        let table = ctx.get_table(&ctx.get_current_catalog(), &db, "t").await?;
+        let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+        let block_meta = load_block_metas(fuse_table).await?.remove(0);
+
+        let block_reader = fuse_table.create_block_reader(
+            ctx.clone(),
+            Projection::Columns(vec![0, 1, 2]),
+            false,
+            false,
+            false,
+        )?;
+        let mut strict_reader = block_reader.as_ref().clone();
+        strict_reader.set_strict_projection(true);
+        let table_ctx: Arc<dyn TableContext> = ctx.clone();
+        let read_settings = ReadSettings::from_ctx(&table_ctx)?;
+        let format = fuse_table.get_storage_format();
+
+        // the added column is filled with the default value in strict mode
+        let block = strict_reader
+            .read_by_meta(&read_settings, &block_meta, &format)
+            .await?;
+        assert_eq!(block.num_rows(), 2, "{storage_format}");
+        let c = block.get_by_offset(2);
+        assert_eq!(
+            c.value.index(0).unwrap().to_owned(),
+            Scalar::Number(NumberScalar::Int32(7)),
+            "{storage_format}"
+        );
+
+        // `a` is missing in the block, but it is not an added column
+        let a_id = table.schema().leaf_columns_of(&"a".to_string())[0];
+        let mut missing_meta = block_meta.clone();
+        missing_meta.col_metas.remove(&a_id);
+        let err = strict_reader
+            .read_by_meta(&read_settings, &missing_meta, &format)
+            .await
+            .unwrap_err();
+        assert!(
+            err.message()
+                .contains(&format!("column id {a_id} of the projection is missing")),
+            "{storage_format}: {err}"
+        );
+
+        // same for the staged decode
+        if storage_format == "parquet" {
+            let data = strict_reader
+                .read_columns_data_by_merge_io(
+                    &read_settings,
+                    &missing_meta.location.0,
+                    &missing_meta.col_metas,
+                    &None,
+                )
+                .await?;
+            let column_chunks = data.columns_chunks()?;
+            let mut state =
+                strict_reader.create_staged_deserialize_state(missing_meta.row_count as usize)?;
+            let err = strict_reader
+                .deserialize_parquet_stage(
+                    &mut state,
+                    &missing_meta.col_metas,
+                    &column_chunks,
+                    &missing_meta.compression,
+                    3,
+                )
+                .unwrap_err();
+            assert!(
+                err.message()
+                    .contains(&format!("column id {a_id} of the projection is missing")),
+                "{err}"
+            );
+        }
+
+        // it is filled with the default value if not strict
+        let block = block_reader
+            .read_by_meta(&read_settings, &missing_meta, &format)
+            .await?;
+        assert_eq!(block.num_rows(), 2, "{storage_format}");
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_read_dictionary_strings() -> Result<()> {
+    let fixture = TestFixture::setup().await?;
+    fixture.create_default_database().await?;
+    let db = fixture.default_db_name();
+
+    fixture
+        .execute_command(&format!(
+            "create table {db}.t(s string not null) storage_format = 'parquet' compression = 'none'"
+        ))
+        .await?;
+    fixture
+        .execute_command(&format!("insert into {db}.t values('a'), ('b'), ('a')"))
+        .await?;
+
+    let ctx = fixture.new_query_ctx().await?;
+    let table = ctx.get_table(&ctx.get_current_catalog(), &db, "t").await?;
+    let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+    let meta = load_block_metas(fuse_table).await?.remove(0);
+    let format = fuse_table.get_storage_format();
+    let block_reader = fuse_table.create_block_reader(
+        ctx.clone(),
+        Projection::Columns(vec![0]),
+        false,
+        false,
+        false,
+    )?;
+    let mut dictionary_reader = block_reader.as_ref().clone();
+    dictionary_reader.set_dictionary_strings(true);
+    let table_ctx: Arc<dyn TableContext> = ctx.clone();
+    let read_settings = ReadSettings::from_ctx(&table_ctx)?;
+
+    // fuse writes plain pages, which are decoded as usual
+    let expected = block_reader
+        .read_by_meta(&read_settings, &meta, &format)
+        .await?;
+    let block = dictionary_reader
+        .read_by_meta(&read_settings, &meta, &format)
+        .await?;
+    assert_eq!(block.get_by_offset(0), expected.get_by_offset(0));
+
+    // the chunk of the same values written with a dictionary page
+    let schema = table.schema();
+    let props = WriterProperties::builder()
+        .set_dictionary_enabled(true)
+        .build();
+    let mut buf = vec![];
+    let arrow_schema = Arc::new(Schema::from(schema.as_ref()));
+    let mut writer = ArrowWriter::try_new(&mut buf, arrow_schema, Some(props))?;
+    writer.write(&expected.to_record_batch(&schema)?)?;
+    let file_meta = writer.close()?;
+    let chunk_meta = file_meta.row_groups[0].columns[0]
+        .meta_data
+        .as_ref()
+        .unwrap();
+    let offset = chunk_meta.dictionary_page_offset.unwrap() as usize;
+    let chunk = buf[offset..offset + chunk_meta.total_compressed_size as usize].to_vec();
+
+    let column_id = schema.field_with_name("s")?.column_id;
+    let column_chunks = HashMap::from([(column_id, DataItem::RawData(chunk.into()))]);
+    let block = dictionary_reader.deserialize_chunks(
+        &meta.location.0,
+        meta.row_count as usize,
+        &meta.compression,
+        &meta.col_metas,
+        column_chunks,
+        &format,
+    )?;
+    assert_eq!(block.get_by_offset(0), expected.get_by_offset(0));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_read_parquet_page_checksum() -> Result<()> {
+    let fixture = TestFixture::setup().await?;
+    fixture.create_default_database().await?;
+    let db = fixture.default_db_name();
+
+    // the pages are plain and uncompressed, a flipped byte of a value decodes to another value
+    fixture
+        .execute_command(&format!(
+            "create table {db}.t(a int not null) storage_format = 'parquet' compression = 'none'"
+        ))
+        .await?;
+    fixture
+        .execute_command(&format!("insert into {db}.t values(1), (2), (3)"))
+        .await?;
+
+    let ctx = fixture.new_query_ctx().await?;
+    let table = ctx.get_table(&ctx.get_current_catalog(), &db, "t").await?;
+    let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+    let meta = load_block_metas(fuse_table).await?.remove(0);
+    let column_id = table.schema().field_with_name("a")?.column_id;
+    let format = fuse_table.get_storage_format();
+
+    let mut block_readers = vec![];
+    for enabled in ["1", "0"] {
+        ctx.get_settings().set_setting(
+            "enable_parquet_page_checksum".to_string(),
+            enabled.to_string(),
+        )?;
+        let block_reader = fuse_table.create_block_reader(
+            ctx.clone(),
+            Projection::Columns(vec![0]),
+            false,
+            false,
+            false,
+        )?;
+        block_readers.push(block_reader);
+    }
+    let checked_reader = block_readers[0].as_ref();
+    let unchecked_reader = block_readers[1].as_ref();
+
+    let table_ctx: Arc<dyn TableContext> = ctx.clone();
+    let read_settings = ReadSettings::from_ctx(&table_ctx)?;
+    let raw = checked_reader
+        .read_raw_columns(&read_settings, &meta)
+        .await?;
+    let deserialize = |block_reader: &BlockReader, chunk: Vec<u8>| {
+        let column_chunks = HashMap::from([(column_id, DataItem::RawData(chunk.into()))]);
+        block_reader.deserialize_chunks(
+            &meta.location.0,
+            meta.row_count as usize,
+            &raw.compression,
+            &meta.col_metas,
+            column_chunks,
+            &format,
+        )
+    };
+
+    // fuse writes no page checksum, its blocks are read as they are
+    let chunk = raw.columns[&column_id].to_vec();
+    let mut corrupted = chunk.clone();
+    *corrupted.last_mut().unwrap() ^= 0xff;
+    assert_eq!(deserialize(checked_reader, corrupted)?.num_rows(), 3);
+
+    // the pages written with a checksum are validated
+    let chunk = with_page_checksums(&chunk);
+    assert_eq!(deserialize(checked_reader, chunk.clone())?.num_rows(), 3);
+
+    let mut corrupted = chunk;
+    *corrupted.last_mut().unwrap() ^= 0xff;
+    let err = deserialize(checked_reader, corrupted.clone()).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::CORRUPT_PARQUET);
+    assert!(err.message().contains("page index: 0"));
+
+    // not validated by default, the corrupted value is read
+    let block = deserialize(unchecked_reader, corrupted)?;
+    let a = block.get_by_offset(0);
+    assert_ne!(
+        a.value.index(2).unwrap().to_owned(),
+        Scalar::Number(NumberScalar::Int32(3))
+    );
+
+    Ok(())
+}
+
+async fn with_array_cache() -> Result<TestFixture> {
+    let mut config = InnerConfig::default();
+    config.query.cluster_id = String::from("test-cluster-id");
+    // memory cache is not enabled by default, let's enable it
+    config.cache.table_data_deserialized_data_bytes = 1024 * 1024 * 10;
+    TestFixture::setup_with_config(&config).await
+}
+
+// the metas of all the blocks of the table, in the order of the segments of its snapshot
+async fn load_block_metas(fuse_table: &FuseTable) -> Result<Vec<BlockMeta>> {
+    let snapshot = fuse_table.read_table_snapshot().await?.unwrap();
+    let segment_reader =
+        MetaReaders::segment_info_reader(fuse_table.get_operator(), fuse_table.schema());
+    let mut metas = vec![];
+    for (location, ver) in &snapshot.segments {
+        let segment_info = segment_reader
+            .read(&LoadParams {
+                location: location.to_string(),
+                len_hint: None,
+                ver: *ver,
+                put_cache: false,
+            })
+            .await?;
+        for block_meta in segment_info.block_metas()? {
+            metas.push(block_meta.as_ref().clone());
+        }
+    }
+    Ok(metas)
+}
+
+// Rewrite the page headers of a parquet column chunk with the CRC32 checksums of the pages.
+fn with_page_checksums(chunk: &[u8]) -> Vec<u8> {
+    let mut remaining = chunk;
+    let mut buf = Vec::with_capacity(chunk.len());
+    while !remaining.is_empty() {
+        let mut header = {
+            let mut prot = TCompactInputProtocol::new(&mut remaining);
+            PageHeader::read_from_in_protocol(&mut prot).unwrap()
+        };
+        let (page, rest) = remaining.split_at(header.compressed_page_size as usize);
+        header.crc = Some(crc32fast::hash(page) as i32);
+        let mut prot = TCompactOutputProtocol::new(&mut buf);
+        header.write_to_out_protocol(&mut prot).unwrap();
+        buf.extend_from_slice(page);
+        remaining = rest;
+    }
+    buf
+}
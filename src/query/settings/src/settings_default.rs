@@ -208,6 +208,48 @@ impl DefaultSettings {
                     scope: SettingScope::Both,
                     range: Some(SettingRange::Numeric(0..=u64::MAX)),
                 }),
+                ("storage_io_max_concurrent_reads", DefaultSettingValue {
+                    value: UserSettingValue::UInt64(0),
+                    desc: "Sets the maximum number of concurrent range reads of a block from storage, 0 means unlimited.",
+                    mode: SettingMode::Both,
+                    scope: SettingScope::Both,
+                    range: Some(SettingRange::Numeric(0..=u64::MAX)),
+                }),
+                ("enable_parquet_page_checksum", DefaultSettingValue {
+                    value: UserSettingValue::UInt64(0),
+                    desc: "Enables validating the checksum of parquet pages when reading fuse table data, only the pages written with a checksum are validated, fuse itself writes none.",
+                    mode: SettingMode::Both,
+                    scope: SettingScope::Both,
+                    range: Some(SettingRange::Numeric(0..=1)),
+                }),
+                ("table_data_cache_policy", DefaultSettingValue {
+                    value: UserSettingValue::String("read_write".to_string()),
+                    desc: "Sets how queries use the table data caches: read_write, read_only (serve hits without populating the caches) or bypass.",
+                    mode: SettingMode::Both,
+                    scope: SettingScope::Both,
+                    range: Some(SettingRange::String(vec!["read_write".into(), "read_only".into(), "bypass".into()])),
+                }),
+                ("table_data_array_cache_max_entry_bytes", DefaultSettingValue {
+                    value: UserSettingValue::UInt64(0),
+                    desc: "Sets the maximum size in bytes of a column array to put into the table data array cache, 0 means unlimited.",
+                    mode: SettingMode::Both,
+                    scope: SettingScope::Both,
+                    range: Some(SettingRange::Numeric(0..=u64::MAX)),
+                }),
+                ("enable_sink_first_scheduling", DefaultSettingValue {
+                    value: UserSettingValue::UInt64(0),
+                    desc: "Enables scheduling the pipeline edges closer to the sinks first, to reduce the output latency.",
+                    mode: SettingMode::Both,
+                    scope: SettingScope::Both,
+                    range: Some(SettingRange::Numeric(0..=1)),
+                }),
+                ("executor_schedule_trace_capacity", DefaultSettingValue {
+                    value: UserSettingValue::UInt64(0),
+                    desc: "Sets the number of the latest scheduled edges recorded by each processor for debugging, 0 disables the trace.",
+                    mode: SettingMode::Both,
+                    scope: SettingScope::Both,
+                    range: Some(SettingRange::Numeric(0..=65536)),
+                }),
                 ("flight_client_timeout", DefaultSettingValue {
                     value: UserSettingValue::UInt64(60),
                     desc: "Sets the maximum time in seconds that a flight client request can be processed.",
@@ -245,11 +245,35 @@ impl Settings {
         self.try_get_u64("storage_io_max_page_bytes_for_read")
     }
 
+    pub fn get_storage_io_max_concurrent_reads(&self) -> Result<u64> {
+        self.try_get_u64("storage_io_max_concurrent_reads")
+    }
+
+    pub fn get_enable_parquet_page_checksum(&self) -> Result<bool> {
+        Ok(self.try_get_u64("enable_parquet_page_checksum")? != 0)
+    }
+
+    pub fn get_table_data_cache_policy(&self) -> Result<String> {
+        self.try_get_string("table_data_cache_policy")
+    }
+
+    pub fn get_table_data_array_cache_max_entry_bytes(&self) -> Result<u64> {
+        self.try_get_u64("table_data_array_cache_max_entry_bytes")
+    }
+
     // Get max_execute_time_in_seconds.
     pub fn get_max_execute_time_in_seconds(&self) -> Result<u64> {
         self.try_get_u64("max_execute_time_in_seconds")
     }
 
+    pub fn get_enable_sink_first_scheduling(&self) -> Result<bool> {
+        Ok(self.try_get_u64("enable_sink_first_scheduling")? != 0)
+    }
+
+    pub fn get_executor_schedule_trace_capacity(&self) -> Result<u64> {
+        self.try_get_u64("executor_schedule_trace_capacity")
+    }
+
     // Get flight client timeout.
     pub fn get_flight_client_timeout(&self) -> Result<u64> {
         self.try_get_u64("flight_client_timeout")
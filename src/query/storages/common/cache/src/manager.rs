@@ -135,7 +135,12 @@ impl CacheManager {
 
         // Cache of deserialized table data
         let in_memory_table_data_cache =
-            Self::new_bytes_cache_slot(MEMORY_CACHE_TABLE_DATA, memory_cache_capacity);
+            Self::new_bytes_cache(MEMORY_CACHE_TABLE_DATA, memory_cache_capacity).map(|cache| {
+                cache.with_pinned_bytes_capacity(
+                    config.table_data_deserialized_pinned_bytes as usize,
+                )
+            });
+        let in_memory_table_data_cache = CacheSlot::new(in_memory_table_data_cache);
 
         // setup in-memory table meta cache
         if !config.enable_table_meta_cache {
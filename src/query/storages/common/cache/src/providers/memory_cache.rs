@@ -12,10 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use databend_common_cache::Cache;
 use databend_common_cache::LruCache;
+use databend_common_cache::MemSized;
 use parking_lot::RwLock;
 
 use crate::caches::CacheValue;
@@ -25,6 +27,31 @@ pub struct InMemoryLruCache<V: Into<CacheValue<V>>> {
     unit: Unit,
     name: String,
     inner: Arc<RwLock<LruCache<String, CacheValue<V>>>>,
+    // None if pinning is disabled, so that the cache operations do not take its lock.
+    pinned: Option<Arc<RwLock<PinnedEntries<V>>>>,
+}
+
+// The pinned entries are never evicted, they are bounded by a separate bytes capacity.
+struct PinnedEntries<V> {
+    bytes_capacity: usize,
+    bytes_size: usize,
+    entries: HashMap<String, CacheValue<V>>,
+}
+
+impl<V> PinnedEntries<V> {
+    fn new(bytes_capacity: usize) -> Self {
+        Self {
+            bytes_capacity,
+            bytes_size: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    fn pop(&mut self, k: &str) -> Option<CacheValue<V>> {
+        let value = self.entries.remove(k)?;
+        self.bytes_size -= value.mem_bytes();
+        Some(value)
+    }
 }
 
 impl<V: Into<CacheValue<V>>> InMemoryLruCache<V> {
@@ -37,6 +64,74 @@ impl<V: Into<CacheValue<V>>> InMemoryLruCache<V> {
         let mut cache = self.inner.write();
         cache.set_items_capacity(capacity);
     }
+
+    /// Inserts the value only if the key is not cached, and returns the cached value.
+    ///
+    /// The check and the insertion are done under the same lock, so concurrent readers
+    /// populating the same key will not replace the value put by each other.
+    pub fn insert_if_absent(&self, k: String, v: V) -> Arc<V> {
+        let pinned = self.pinned.as_ref().map(|pinned| pinned.read());
+        if let Some(cache_value) = pinned.as_ref().and_then(|pinned| pinned.entries.get(&k)) {
+            return cache_value.get_inner();
+        }
+        let mut guard = self.inner.write();
+        if let Some(cache_value) = guard.peek(&k) {
+            return cache_value.get_inner();
+        }
+        let cache_value = v.into();
+        let res = cache_value.get_inner();
+        guard.insert(k, cache_value);
+        res
+    }
+
+    /// Sets the capacity of the pinned entries, 0 disables pinning.
+    pub fn with_pinned_bytes_capacity(mut self, capacity: usize) -> Self {
+        self.pinned = match capacity {
+            0 => None,
+            _ => Some(Arc::new(RwLock::new(PinnedEntries::new(capacity)))),
+        };
+        self
+    }
+
+    /// Same as `insert_if_absent`, but the value is pinned so that it is never evicted.
+    ///
+    /// If the pinned entries have no room for the value, it is inserted as an ordinary
+    /// entry. A value cached as an ordinary entry is pinned if there is room for it.
+    pub fn insert_pinned_if_absent(&self, k: String, v: V) -> Arc<V> {
+        let Some(pinned) = &self.pinned else {
+            return self.insert_if_absent(k, v);
+        };
+        let mut pinned = pinned.write();
+        if let Some(cache_value) = pinned.entries.get(&k) {
+            return cache_value.get_inner();
+        }
+        let mut guard = self.inner.write();
+        let cache_value = match guard.pop(&k) {
+            Some(cache_value) => cache_value,
+            None => v.into(),
+        };
+        let res = cache_value.get_inner();
+        let mem_bytes = cache_value.mem_bytes();
+        if pinned.bytes_size + mem_bytes <= pinned.bytes_capacity {
+            pinned.bytes_size += mem_bytes;
+            pinned.entries.insert(k, cache_value);
+        } else {
+            guard.insert(k, cache_value);
+        }
+        res
+    }
+
+    pub fn is_pinned(&self, k: &str) -> bool {
+        self.pinned
+            .as_ref()
+            .is_some_and(|pinned| pinned.read().entries.contains_key(k))
+    }
+
+    pub fn pinned_bytes_size(&self) -> usize {
+        self.pinned
+            .as_ref()
+            .map_or(0, |pinned| pinned.read().bytes_size)
+    }
 }
 
 impl<V: Into<CacheValue<V>>> Clone for InMemoryLruCache<V> {
@@ -45,6 +140,7 @@ impl<V: Into<CacheValue<V>>> Clone for InMemoryLruCache<V> {
             unit: self.unit,
             name: self.name.clone(),
             inner: self.inner.clone(),
+            pinned: self.pinned.clone(),
         }
     }
 }
@@ -55,6 +151,7 @@ impl<V: Into<CacheValue<V>>> InMemoryLruCache<V> {
             name,
             unit: Unit::Count,
             inner: Arc::new(RwLock::new(LruCache::with_items_capacity(items_capacity))),
+            pinned: None,
         }
     }
 
@@ -63,6 +160,7 @@ impl<V: Into<CacheValue<V>>> InMemoryLruCache<V> {
             unit: Unit::Bytes,
             name,
             inner: Arc::new(RwLock::new(LruCache::with_bytes_capacity(bytes_capacity))),
+            pinned: None,
         }
     }
 
@@ -93,12 +191,19 @@ mod impls {
 
         fn get<Q: AsRef<str>>(&self, k: Q) -> Option<Arc<V>> {
             metrics_inc_cache_access_count(1, self.name());
-            let v = {
+            let pinned = self.pinned.as_ref().and_then(|pinned| {
+                pinned
+                    .read()
+                    .entries
+                    .get(k.as_ref())
+                    .map(|cache_value| cache_value.get_inner())
+            });
+            let v = pinned.or_else(|| {
                 let mut guard = self.inner.write();
                 guard
                     .get(k.as_ref())
                     .map(|cache_value: &CacheValue<V>| cache_value.get_inner())
-            };
+            });
             if v.is_none() {
                 metrics_inc_cache_miss_count(1, &self.name);
             } else {
@@ -119,19 +224,37 @@ mod impls {
         fn insert(&self, k: String, v: V) -> Arc<V> {
             let cache_value = v.into();
             let res = cache_value.get_inner();
+            let mut pinned_guard = self.pinned.as_ref().map(|pinned| pinned.write());
+            if let Some(pinned) = pinned_guard.as_deref_mut() {
+                if pinned.pop(&k).is_some() {
+                    // keep the new value pinned if there is room for it, otherwise it is
+                    // unpinned and inserted as an ordinary entry
+                    let mem_bytes = cache_value.mem_bytes();
+                    if pinned.bytes_size + mem_bytes <= pinned.bytes_capacity {
+                        pinned.bytes_size += mem_bytes;
+                        pinned.entries.insert(k, cache_value);
+                        return res;
+                    }
+                }
+            }
             let mut guard = self.inner.write();
             guard.insert(k, cache_value);
             res
         }
 
         fn evict(&self, k: &str) -> bool {
+            let mut pinned = self.pinned.as_ref().map(|pinned| pinned.write());
             let mut guard = self.inner.write();
-            guard.pop(k).is_some()
+            let unpinned = pinned
+                .as_mut()
+                .is_some_and(|pinned| pinned.pop(k).is_some());
+            guard.pop(k).is_some() || unpinned
         }
 
         fn contains_key(&self, k: &str) -> bool {
+            let pinned = self.pinned.as_ref().map(|pinned| pinned.read());
             let guard = self.inner.read();
-            guard.contains(k)
+            pinned.is_some_and(|pinned| pinned.entries.contains_key(k)) || guard.contains(k)
         }
 
         fn items_capacity(&self) -> u64 {
@@ -145,13 +268,15 @@ mod impls {
         }
 
         fn len(&self) -> usize {
+            let pinned = self.pinned.as_ref().map(|pinned| pinned.read());
             let guard = self.inner.read();
-            guard.len()
+            pinned.map_or(0, |pinned| pinned.entries.len()) + guard.len()
         }
 
         fn bytes_size(&self) -> u64 {
+            let pinned = self.pinned.as_ref().map(|pinned| pinned.read());
             let guard = self.inner.read();
-            guard.bytes_size()
+            pinned.map_or(0, |pinned| pinned.bytes_size) as u64 + guard.bytes_size()
         }
 
         fn name(&self) -> &str {
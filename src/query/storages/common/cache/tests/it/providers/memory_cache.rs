@@ -0,0 +1,143 @@
+// Copyright 2023 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::sync::Barrier;
+
+use arrow::array::ArrayRef;
+use arrow::array::Int32Array;
+use databend_storages_common_cache::CacheAccessor;
+use databend_storages_common_cache::InMemoryLruCache;
+use databend_storages_common_cache::SizedColumnArray;
+
+fn sized_array(size: usize) -> SizedColumnArray {
+    let array: ArrayRef = Arc::new(Int32Array::from(vec![1, 2, 3]));
+    (array, size)
+}
+
+#[test]
+fn test_insert_if_absent_concurrently() {
+    let cache = InMemoryLruCache::with_bytes_capacity("test".to_string(), 100);
+    let num_threads = 8;
+    let barrier = Barrier::new(num_threads);
+
+    // all the threads race to populate the same key
+    let values = std::thread::scope(|s| {
+        let handles = (0..num_threads)
+            .map(|i| {
+                let cache = &cache;
+                let barrier = &barrier;
+                s.spawn(move || {
+                    barrier.wait();
+                    cache.insert_if_absent("key".to_string(), sized_array(10 + i))
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    // every caller gets the value put by the first one
+    let cached = cache.get("key").unwrap();
+    for value in &values {
+        assert!(Arc::ptr_eq(value, &cached));
+    }
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.bytes_size(), cached.1 as u64);
+}
+
+#[test]
+fn test_pinned_entries_survive_eviction() {
+    let cache = InMemoryLruCache::with_bytes_capacity("test".to_string(), 100)
+        .with_pinned_bytes_capacity(50);
+
+    cache.insert_pinned_if_absent("pinned_1".to_string(), sized_array(20));
+    cache.insert_pinned_if_absent("pinned_2".to_string(), sized_array(20));
+    // no room in the pinned entries, inserted as an ordinary entry
+    cache.insert_pinned_if_absent("pinned_3".to_string(), sized_array(20));
+    assert!(cache.is_pinned("pinned_1"));
+    assert!(cache.is_pinned("pinned_2"));
+    assert!(!cache.is_pinned("pinned_3"));
+    assert_eq!(cache.pinned_bytes_size(), 40);
+
+    // the ordinary entries are evicted to make room for the new ones
+    for i in 0..10 {
+        cache.insert(format!("unpinned_{i}"), sized_array(30));
+    }
+    assert!(cache.get("pinned_1").is_some());
+    assert!(cache.get("pinned_2").is_some());
+    assert!(cache.get("pinned_3").is_none());
+    assert!(cache.get("unpinned_0").is_none());
+    assert!(cache.get("unpinned_9").is_some());
+    assert_eq!(cache.len(), 2 + 3);
+    assert_eq!(cache.bytes_size(), 40 + 90);
+
+    // inserting a pinned key again keeps the cached value
+    cache.insert_if_absent("pinned_1".to_string(), sized_array(10));
+    assert_eq!(cache.pinned_bytes_size(), 40);
+    assert_eq!(cache.get("pinned_1").unwrap().1, 20);
+
+    // evicted explicitly
+    assert!(cache.evict("pinned_1"));
+    assert!(!cache.contains_key("pinned_1"));
+    assert_eq!(cache.pinned_bytes_size(), 20);
+}
+
+#[test]
+fn test_replace_pinned_entry() {
+    let cache = InMemoryLruCache::with_bytes_capacity("test".to_string(), 100)
+        .with_pinned_bytes_capacity(50);
+
+    cache.insert_pinned_if_absent("pinned_1".to_string(), sized_array(20));
+    cache.insert_pinned_if_absent("pinned_2".to_string(), sized_array(20));
+
+    // the new value fits in the pinned entries, kept pinned
+    cache.insert("pinned_1".to_string(), sized_array(30));
+    assert!(cache.is_pinned("pinned_1"));
+    assert_eq!(cache.pinned_bytes_size(), 50);
+    assert_eq!(cache.get("pinned_1").unwrap().1, 30);
+
+    // no room for the new value, unpinned and inserted as an ordinary entry
+    cache.insert("pinned_2".to_string(), sized_array(40));
+    assert!(!cache.is_pinned("pinned_2"));
+    assert_eq!(cache.pinned_bytes_size(), 30);
+    assert_eq!(cache.get("pinned_2").unwrap().1, 40);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.bytes_size(), 30 + 40);
+
+    // evicted as an ordinary entry
+    cache.insert("unpinned".to_string(), sized_array(80));
+    assert!(cache.get("pinned_2").is_none());
+    assert!(cache.get("pinned_1").is_some());
+}
+
+#[test]
+fn test_pinning_disabled() {
+    let cache = InMemoryLruCache::with_bytes_capacity("test".to_string(), 100)
+        .with_pinned_bytes_capacity(0);
+
+    // inserted as ordinary entries, and evicted as such
+    cache.insert_pinned_if_absent("key_1".to_string(), sized_array(60));
+    assert!(!cache.is_pinned("key_1"));
+    assert_eq!(cache.pinned_bytes_size(), 0);
+    assert_eq!(cache.get("key_1").unwrap().1, 60);
+
+    cache.insert_pinned_if_absent("key_2".to_string(), sized_array(60));
+    assert!(cache.get("key_1").is_none());
+    assert!(cache.get("key_2").is_some());
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.bytes_size(), 60);
+}
@@ -20,4 +20,5 @@ mod read_settings;
 pub use files::Files;
 pub use merge_io_reader::MergeIOReader;
 pub use merge_io_result::MergeIOReadResult;
+pub use read_settings::CachePolicy;
 pub use read_settings::ReadSettings;
@@ -13,9 +13,11 @@
 // limitations under the License.
 
 use std::collections::HashMap;
+use std::future::Future;
 use std::ops::Range;
 use std::time::Instant;
 
+use databend_common_base::base::tokio::sync::Semaphore;
 use databend_common_base::rangemap::RangeMerger;
 use databend_common_base::runtime::UnlimitedFuture;
 use databend_common_exception::ErrorCode;
@@ -85,7 +87,9 @@ impl MergeIOReader {
         }
 
         let start = Instant::now();
-        let owner_memory = OwnerMemory::create(try_join_all(read_handlers).await?);
+        let owner_memory = OwnerMemory::create(
+            Self::try_join_bounded(read_handlers, read_settings.max_concurrent_io).await?,
+        );
 
         // Perf.
         {
@@ -103,10 +107,8 @@ impl MergeIOReader {
             )))?;
 
             // Fetch the raw data for the raw range.
-            let start = (column_range.start - merged_range.start) as usize;
-            let end = (column_range.end - merged_range.start) as usize;
+            let range = Self::range_in_merged(&column_range, &merged_range)?;
             let column_id = *raw_idx as ColumnId;
-            let range = start..end;
             columns_chunk_offsets.insert(column_id, (merged_range_idx, range));
         }
 
@@ -116,6 +118,55 @@ impl MergeIOReader {
         Ok(read_res)
     }
 
+    /// The range of the column chunk relative to the start of the merged range.
+    ///
+    /// The offsets are 64-bit, column chunks and files larger than 2GB (or 4GB)
+    /// must not be truncated.
+    pub(crate) fn range_in_merged(
+        column_range: &Range<u64>,
+        merged_range: &Range<u64>,
+    ) -> Result<Range<usize>> {
+        if column_range.start > column_range.end
+            || column_range.start < merged_range.start
+            || column_range.end > merged_range.end
+        {
+            return Err(ErrorCode::Internal(format!(
+                "Column range {:?} is out of the merged range {:?}",
+                column_range, merged_range
+            )));
+        }
+        let to_usize = |offset: u64| {
+            usize::try_from(offset - merged_range.start).map_err(|_| {
+                ErrorCode::Internal(format!(
+                    "Column range {:?} exceeds the addressable memory",
+                    column_range
+                ))
+            })
+        };
+        Ok(to_usize(column_range.start)?..to_usize(column_range.end)?)
+    }
+
+    /// Runs the reads with at most `max_concurrent_io` of them in flight, 0 means unlimited.
+    ///
+    /// A read takes a permit only when it starts and releases it when it finishes, and
+    /// never waits for another read while holding it, so any limit (including 1) makes progress.
+    async fn try_join_bounded<T, F>(reads: Vec<F>, max_concurrent_io: usize) -> Result<Vec<T>>
+    where F: Future<Output = Result<T>> {
+        if max_concurrent_io == 0 || reads.len() <= max_concurrent_io {
+            return try_join_all(reads).await;
+        }
+
+        let semaphore = Semaphore::new(max_concurrent_io);
+        let semaphore = &semaphore;
+        try_join_all(reads.into_iter().map(|read| async move {
+            let _permit = semaphore.acquire().await.map_err(|e| {
+                ErrorCode::Internal(format!("Failed to acquire the io permit: {}", e))
+            })?;
+            read.await
+        }))
+        .await
+    }
+
     #[inline]
     #[async_backtrace::framed]
     async fn read_range(
@@ -175,9 +226,7 @@ impl MergeIOReader {
             )))?;
 
             // Fetch the raw data for the raw range.
-            let start = (column_range.start - merged_range.start) as usize;
-            let end = (column_range.end - merged_range.start) as usize;
-            let range = start..end;
+            let range = Self::range_in_merged(&column_range, &merged_range)?;
             columns_chunk_offsets.insert(column_id, (merged_range_idx, range));
         }
 
@@ -199,3 +248,77 @@ impl MergeIOReader {
         Ok((index, chunk.to_vec()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    use databend_common_base::base::tokio;
+    use databend_common_exception::Result;
+
+    use super::MergeIOReader;
+
+    // A mock store which records the peak number of in-flight reads.
+    #[derive(Default)]
+    struct MockStore {
+        in_flight: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    impl MockStore {
+        async fn read(&self, index: usize) -> Result<(usize, Vec<u8>)> {
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok((index, vec![index as u8]))
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_max_concurrent_io() -> Result<()> {
+        let num_reads = 16;
+        for max_concurrent_io in [0, 1, 3, num_reads, num_reads + 1] {
+            let store = MockStore::default();
+            let reads = (0..num_reads).map(|i| store.read(i)).collect::<Vec<_>>();
+            let res = MergeIOReader::try_join_bounded(reads, max_concurrent_io).await?;
+
+            // all the reads are done, in order
+            let indices = res.iter().map(|(i, _)| *i).collect::<Vec<_>>();
+            assert_eq!(indices, (0..num_reads).collect::<Vec<_>>());
+
+            let peak = store.peak.load(Ordering::SeqCst);
+            if max_concurrent_io == 0 {
+                assert_eq!(peak, num_reads);
+            } else {
+                assert!(peak <= max_concurrent_io);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_range_in_merged_large_offsets() {
+        let i32_max = i32::MAX as u64;
+        let u32_max = u32::MAX as u64;
+
+        // column chunk starting beyond 2GB
+        let merged_range = i32_max..i32_max + 1024;
+        let range = MergeIOReader::range_in_merged(&(i32_max + 10..i32_max + 20), &merged_range);
+        assert_eq!(range.unwrap(), 10..20);
+
+        // column chunk larger than 4GB
+        let merged_range = 100..u32_max + 200;
+        let range = MergeIOReader::range_in_merged(&(100..u32_max + 200), &merged_range);
+        assert_eq!(range.unwrap(), 0..(u32_max + 100) as usize);
+
+        // column range out of the merged range
+        let merged_range = u32_max..u32_max + 10;
+        assert!(
+            MergeIOReader::range_in_merged(&(u32_max - 1..u32_max + 5), &merged_range).is_err()
+        );
+        assert!(MergeIOReader::range_in_merged(&(u32_max..u32_max + 11), &merged_range).is_err());
+    }
+}
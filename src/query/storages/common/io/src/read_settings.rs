@@ -12,15 +12,61 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::str::FromStr;
 use std::sync::Arc;
 
 use databend_common_catalog::table_context::TableContext;
+use databend_common_exception::ErrorCode;
 use databend_common_exception::Result;
 
+/// How a query uses the table data caches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// Serve hits and populate the caches on misses.
+    ReadWrite,
+    /// Serve hits, but never populate the caches, e.g. for one-off large scans
+    /// which would evict the hot data.
+    ReadOnly,
+    /// Neither read from nor write to the caches.
+    Bypass,
+}
+
+impl CachePolicy {
+    pub fn can_read(&self) -> bool {
+        !matches!(self, CachePolicy::Bypass)
+    }
+
+    pub fn can_write(&self) -> bool {
+        matches!(self, CachePolicy::ReadWrite)
+    }
+}
+
+impl FromStr for CachePolicy {
+    type Err = ErrorCode;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "read_write" => Ok(CachePolicy::ReadWrite),
+            "read_only" => Ok(CachePolicy::ReadOnly),
+            "bypass" => Ok(CachePolicy::Bypass),
+            _ => Err(ErrorCode::InvalidConfig(format!(
+                "invalid CachePolicy: {:?}",
+                s
+            ))),
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct ReadSettings {
     pub storage_io_min_bytes_for_seek: u64,
     pub storage_io_max_page_bytes_for_read: u64,
+    /// The maximum number of concurrent range reads of a block, 0 means unlimited.
+    pub max_concurrent_io: usize,
+    pub enable_parquet_page_checksum: bool,
+    pub cache_policy: CachePolicy,
+    /// The maximum size of a column array to put into the array cache, 0 means unlimited.
+    pub max_cacheable_array_bytes: usize,
 }
 
 impl ReadSettings {
@@ -32,6 +78,19 @@ impl ReadSettings {
             storage_io_max_page_bytes_for_read: ctx
                 .get_settings()
                 .get_storage_io_max_page_bytes_for_read()?,
+            max_concurrent_io: ctx.get_settings().get_storage_io_max_concurrent_reads()? as usize,
+            enable_parquet_page_checksum: ctx.get_settings().get_enable_parquet_page_checksum()?,
+            cache_policy: ctx.get_settings().get_table_data_cache_policy()?.parse()?,
+            max_cacheable_array_bytes: ctx
+                .get_settings()
+                .get_table_data_array_cache_max_entry_bytes()?
+                as usize,
         })
     }
+
+    /// Whether a column array of `size` bytes can be put into the array cache,
+    /// a huge array would evict many small hot entries.
+    pub fn can_cache_array(&self, size: usize) -> bool {
+        self.max_cacheable_array_bytes == 0 || size <= self.max_cacheable_array_bytes
+    }
 }
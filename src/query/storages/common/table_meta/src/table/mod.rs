@@ -20,6 +20,9 @@ mod table_prefix;
 
 pub use dynamic_table_keys::*;
 pub use stream_keys::*;
+pub use table_compression::to_parquet_compression;
+pub use table_compression::CompressionFallback;
+pub use table_compression::CompressionFallbackFn;
 pub use table_compression::TableCompression;
 pub use table_keys::*;
 pub use table_prefix::*;
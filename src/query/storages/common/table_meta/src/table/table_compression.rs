@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
 use databend_common_exception::ErrorCode;
 use databend_common_native;
 use parquet::basic::Compression as ParquetCompression;
@@ -97,3 +99,140 @@ impl From<meta::Compression> for ParquetCompression {
         }
     }
 }
+
+/// Map a codec that has no parquet counterpart to a codec parquet can decode, `None` if it can't.
+pub type CompressionFallbackFn =
+    Arc<dyn Fn(meta::Compression) -> Option<ParquetCompression> + Send + Sync>;
+
+/// How to read the blocks whose codec can't be mapped to a parquet codec, like the deprecated
+/// meta Lz4. This is for the experimental and external codecs, the reads are strict by default.
+#[derive(Clone, Default)]
+pub enum CompressionFallback {
+    /// Fail to read the blocks.
+    #[default]
+    Strict,
+    /// Read the chunks as uncompressed.
+    Uncompressed,
+    /// Map the codec with the function, fail if it returns `None`.
+    Custom(CompressionFallbackFn),
+}
+
+/// Convert the meta Compression of the blocks to read to parquet Compression,
+/// the codecs without a parquet counterpart are handled by the fallback.
+pub fn to_parquet_compression(
+    compression: meta::Compression,
+    fallback: &CompressionFallback,
+) -> Result<ParquetCompression, ErrorCode> {
+    let mapped = match compression {
+        meta::Compression::Lz4 => match fallback {
+            CompressionFallback::Strict => None,
+            CompressionFallback::Uncompressed => Some(ParquetCompression::UNCOMPRESSED),
+            CompressionFallback::Custom(func) => func(compression),
+        },
+        other => Some(ParquetCompression::from(other)),
+    };
+    mapped.ok_or_else(|| {
+        ErrorCode::UnknownFormat(format!("unsupported block compression: {:?}", compression))
+    })
+}
+
+/// Convert from parquet Compression, the inverse of `From<meta::Compression>`.
+impl TryFrom<ParquetCompression> for meta::Compression {
+    type Error = ErrorCode;
+
+    fn try_from(value: ParquetCompression) -> Result<Self, Self::Error> {
+        match value {
+            ParquetCompression::LZ4_RAW => Ok(meta::Compression::Lz4Raw),
+            ParquetCompression::SNAPPY => Ok(meta::Compression::Snappy),
+            ParquetCompression::ZSTD(_) => Ok(meta::Compression::Zstd),
+            ParquetCompression::UNCOMPRESSED => Ok(meta::Compression::None),
+            ParquetCompression::GZIP(_) => Ok(meta::Compression::Gzip),
+            // The deprecated meta Lz4 is never converted to parquet, the legacy parquet LZ4
+            // (hadoop framed) is not written by databend either.
+            other => Err(ErrorCode::UnknownFormat(format!(
+                "unsupported parquet compression: {}",
+                other
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use parquet::basic::BrotliLevel;
+    use parquet::basic::Compression as ParquetCompression;
+
+    use super::to_parquet_compression;
+    use super::CompressionFallback;
+    use crate::meta::Compression;
+
+    #[test]
+    fn test_parquet_compression_round_trip() {
+        for compression in [
+            Compression::Lz4Raw,
+            Compression::Snappy,
+            Compression::Zstd,
+            Compression::Gzip,
+            Compression::None,
+        ] {
+            let parquet_compression = ParquetCompression::from(compression);
+            assert_eq!(
+                Compression::try_from(parquet_compression).unwrap(),
+                compression
+            );
+        }
+
+        for parquet_compression in [
+            ParquetCompression::LZ4,
+            ParquetCompression::LZO,
+            ParquetCompression::BROTLI(BrotliLevel::default()),
+        ] {
+            assert!(Compression::try_from(parquet_compression).is_err());
+        }
+    }
+
+    #[test]
+    fn test_compression_fallback() {
+        // strict by default
+        let strict = CompressionFallback::default();
+        assert!(to_parquet_compression(Compression::Lz4, &strict).is_err());
+        assert_eq!(
+            to_parquet_compression(Compression::Zstd, &strict).unwrap(),
+            ParquetCompression::from(Compression::Zstd)
+        );
+
+        let uncompressed = CompressionFallback::Uncompressed;
+        assert_eq!(
+            to_parquet_compression(Compression::Lz4, &uncompressed).unwrap(),
+            ParquetCompression::UNCOMPRESSED
+        );
+
+        // the registered fallback is only invoked for the unsupported codec
+        let calls = Arc::new(AtomicUsize::new(0));
+        let custom = CompressionFallback::Custom({
+            let calls = calls.clone();
+            Arc::new(move |compression| {
+                calls.fetch_add(1, Ordering::Relaxed);
+                assert_eq!(compression, Compression::Lz4);
+                Some(ParquetCompression::LZ4)
+            })
+        });
+        assert_eq!(
+            to_parquet_compression(Compression::Lz4, &custom).unwrap(),
+            ParquetCompression::LZ4
+        );
+        assert_eq!(
+            to_parquet_compression(Compression::Snappy, &custom).unwrap(),
+            ParquetCompression::SNAPPY
+        );
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        // the fallback can decline the codec
+        let declined = CompressionFallback::Custom(Arc::new(|_| None));
+        assert!(to_parquet_compression(Compression::Lz4, &declined).is_err());
+    }
+}
@@ -24,11 +24,15 @@ pub use read::BlockReadResult;
 pub use read::BlockReader;
 pub use read::BloomBlockFilterReader;
 pub use read::CompactSegmentInfoReader;
+pub use read::ContiguousBuffer;
+pub use read::ContiguousColumn;
 pub use read::InvertedIndexReader;
 pub use read::MetaReaders;
 pub use read::NativeReaderExt;
 pub use read::NativeSourceData;
+pub use read::RawBlockColumns;
 pub use read::SnapshotHistoryReader;
+pub use read::StagedDeserializeState;
 pub use read::TableSnapshotReader;
 pub use read::VirtualBlockReadResult;
 pub use read::VirtualColumnReader;
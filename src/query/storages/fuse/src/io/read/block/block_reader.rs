@@ -13,6 +13,8 @@
 // limitations under the License.
 
 use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use arrow_schema::Field;
@@ -22,6 +24,7 @@ use databend_common_catalog::plan::Projection;
 use databend_common_catalog::table_context::TableContext;
 use databend_common_exception::ErrorCode;
 use databend_common_exception::Result;
+use databend_common_expression::is_internal_column_id;
 use databend_common_expression::types::DataType;
 use databend_common_expression::ColumnId;
 use databend_common_expression::DataField;
@@ -33,6 +36,10 @@ use databend_common_native::read::NativeColumnsReader;
 use databend_common_sql::field_default_value;
 use databend_common_storage::ColumnNode;
 use databend_common_storage::ColumnNodes;
+use databend_storages_common_io::ReadSettings;
+use databend_storages_common_table_meta::meta::BlockMeta;
+use databend_storages_common_table_meta::meta::ColumnMeta;
+use databend_storages_common_table_meta::meta::ColumnStatistics;
 use opendal::Operator;
 
 use crate::BlockReadResult;
@@ -52,6 +59,14 @@ pub struct BlockReader {
     // used for mutation to update stream columns.
     pub update_stream_columns: bool,
     pub put_cache: bool,
+    // the arrays of these columns are pinned in the array cache when populated.
+    pub pinned_columns: HashSet<ColumnId>,
+    // error on the projected columns which are unexpectedly missing in the block.
+    pub strict_projection: bool,
+    // decode the dictionary encoded string chunks of parquet blocks as dictionary arrays.
+    pub dictionary_strings: bool,
+    // the settings to deserialize the blocks, read once instead of for each block.
+    pub(crate) read_settings: ReadSettings,
 
     pub original_schema: TableSchemaRef,
     pub native_columns_reader: NativeColumnsReader,
@@ -139,6 +154,7 @@ impl BlockReader {
             .collect();
 
         let project_indices = Self::build_projection_indices(&project_column_nodes);
+        let read_settings = ReadSettings::from_ctx(&ctx)?;
 
         Ok(Arc::new(BlockReader {
             ctx,
@@ -152,11 +168,67 @@ impl BlockReader {
             query_internal_columns,
             update_stream_columns,
             put_cache,
+            pinned_columns: HashSet::new(),
+            strict_projection: false,
+            dictionary_strings: false,
+            read_settings,
             original_schema: schema,
             native_columns_reader,
         }))
     }
 
+    /// Pin the arrays of the columns in the array cache when they are populated,
+    /// so the hot columns survive the eviction, within the pinned bytes capacity of the cache.
+    pub fn set_pinned_columns(&mut self, column_ids: impl IntoIterator<Item = ColumnId>) {
+        self.pinned_columns = column_ids.into_iter().collect();
+    }
+
+    /// The statistics of the projected columns in the block, keyed by the leaf column ids.
+    ///
+    /// A nested column has no statistics of its own, the statistics are kept for each of
+    /// its leaves, and only for the leaves of the projected inner fields. The leaves of an
+    /// unsupported type, or added after the block was written, have no statistics.
+    pub fn read_column_stats(&self, meta: &BlockMeta) -> HashMap<ColumnId, ColumnStatistics> {
+        let mut stats = HashMap::new();
+        for column_node in &self.project_column_nodes {
+            for column_id in &column_node.leaf_column_ids {
+                if let Some(column_stats) = meta.col_stats.get(column_id) {
+                    stats.insert(*column_id, column_stats.clone());
+                }
+            }
+        }
+        stats
+    }
+
+    /// Error on a projected column missing in the block, unless it was added after the block
+    /// was written, instead of filling it with the default value, to catch the projection bugs.
+    pub fn set_strict_projection(&mut self, strict: bool) {
+        self.strict_projection = strict;
+    }
+
+    /// Decode the top level string columns of parquet blocks as dictionary arrays if their
+    /// chunks are dictionary encoded, instead of materializing the strings.
+    pub fn set_dictionary_strings(&mut self, enabled: bool) {
+        self.dictionary_strings = enabled;
+    }
+
+    // Check the projected column which has no meta in the block, it is filled with the
+    // default value, unless the projection is strict and it isn't added after the block.
+    pub(crate) fn check_missing_column(
+        &self,
+        column_id: ColumnId,
+        column_metas: &HashMap<ColumnId, ColumnMeta>,
+    ) -> Result<()> {
+        if self.strict_projection && !is_added_column(column_id, column_metas) {
+            return Err(ErrorCode::StorageOther(format!(
+                "column id {} of the projection is missing in the block, \
+                which has columns added after it",
+                column_id
+            )));
+        }
+        Ok(())
+    }
+
     pub fn support_blocking_api(&self) -> bool {
         self.operator.info().native_capability().blocking
     }
@@ -235,3 +307,14 @@ impl BlockReader {
         );
     }
 }
+
+// The column ids only grow, a column missing in the block is added after the block
+// was written if its id is greater than the ids of all the columns in the block.
+// The internal columns, like the stream columns, may be absent in any block.
+fn is_added_column(column_id: ColumnId, column_metas: &HashMap<ColumnId, ColumnMeta>) -> bool {
+    is_internal_column_id(column_id)
+        || column_metas
+            .keys()
+            .filter(|id| !is_internal_column_id(**id))
+            .all(|id| *id < column_id)
+}
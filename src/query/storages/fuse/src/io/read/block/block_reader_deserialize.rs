@@ -15,6 +15,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use arrow::array::ArrayData;
 use arrow_array::ArrayRef;
 use databend_common_catalog::plan::PartInfoPtr;
 use databend_common_exception::Result;
@@ -34,8 +35,34 @@ use crate::FuseStorageFormat;
 
 pub enum DeserializedArray<'a> {
     Cached(&'a Arc<SizedColumnArray>),
+    /// The size of a deserialized array is the size of its buffers, see `array_buffer_size`,
+    /// which is both the size put into the array cache and the size of the deserialized bytes.
     Deserialized((ColumnId, ArrayRef, usize)),
-    NoNeedToCache(ArrayRef),
+    /// Arrays of nested fields are not cached, the size is only used for memory accounting.
+    NoNeedToCache((ArrayRef, usize)),
+}
+
+impl DeserializedArray<'_> {
+    pub fn size(&self) -> usize {
+        match self {
+            DeserializedArray::Cached(sized_column) => sized_column.1,
+            DeserializedArray::Deserialized((_, _, size)) => *size,
+            DeserializedArray::NoNeedToCache((_, size)) => *size,
+        }
+    }
+}
+
+/// Returns the total length of the buffers of the array, including the null buffers
+/// and the buffers of the child arrays.
+pub fn array_buffer_size(data: &ArrayData) -> usize {
+    let buffers = data.buffers().iter().map(|b| b.len()).sum::<usize>();
+    let nulls = data.nulls().map_or(0, |n| n.buffer().len());
+    let children = data
+        .child_data()
+        .iter()
+        .map(array_buffer_size)
+        .sum::<usize>();
+    buffers + nulls + children
 }
 
 pub struct FieldDeserializationContext<'a> {
@@ -130,3 +157,56 @@ impl BlockReader {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::buffer::NullBuffer;
+    use arrow::buffer::OffsetBuffer;
+    use arrow_array::Array;
+    use arrow_array::ArrayRef;
+    use arrow_array::Int32Array;
+    use arrow_array::Int64Array;
+    use arrow_array::ListArray;
+    use arrow_array::StringArray;
+    use arrow_array::StructArray;
+    use arrow_schema::DataType;
+    use arrow_schema::Field;
+
+    use super::array_buffer_size;
+    use super::DeserializedArray;
+
+    #[test]
+    fn test_nested_array_size() {
+        // [[1, 2], NULL, [3]]
+        let list = ListArray::new(
+            Arc::new(Field::new("item", DataType::Int32, false)),
+            OffsetBuffer::new(vec![0, 2, 2, 3].into()),
+            Arc::new(Int32Array::from(vec![1, 2, 3])),
+            Some(NullBuffer::from(vec![true, false, true])),
+        );
+        let array: ArrayRef = Arc::new(list);
+        let size = array_buffer_size(&array.to_data());
+        // offsets + values + null bitmap
+        assert_eq!(size, 4 * 4 + 3 * 4 + 1);
+
+        let deserialized = DeserializedArray::NoNeedToCache((array, size));
+        assert_ne!(deserialized.size(), 0);
+        assert_eq!(deserialized.size(), size);
+
+        // {a: [1, 2, 3], b: ["a", "bc", ""]}
+        let tuple = StructArray::from(vec![
+            (
+                Arc::new(Field::new("a", DataType::Int64, false)),
+                Arc::new(Int64Array::from(vec![1, 2, 3])) as ArrayRef,
+            ),
+            (
+                Arc::new(Field::new("b", DataType::Utf8, false)),
+                Arc::new(StringArray::from(vec!["a", "bc", ""])) as ArrayRef,
+            ),
+        ]);
+        // int64 values + string offsets + string values
+        assert_eq!(array_buffer_size(&tuple.to_data()), 3 * 8 + 4 * 4 + 3);
+    }
+}
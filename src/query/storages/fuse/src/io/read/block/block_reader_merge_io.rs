@@ -15,12 +15,16 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use arrow_schema::Field;
 use bytes::Bytes;
 use databend_common_exception::Result;
 use databend_common_expression::ColumnId;
 use databend_storages_common_cache::SizedColumnArray;
 use databend_storages_common_io::MergeIOReadResult;
+use databend_storages_common_table_meta::meta::ColumnMeta;
+use databend_storages_common_table_meta::meta::Compression;
 use enum_as_inner::EnumAsInner;
+use log::warn;
 
 type CachedColumnData = Vec<(ColumnId, Arc<Bytes>)>;
 type CachedColumnArray = Vec<(ColumnId, Arc<SizedColumnArray>)>;
@@ -97,3 +101,36 @@ impl BlockReadResult {
         Ok(res)
     }
 }
+
+/// Check the cached array against the field and the meta of the column being read.
+///
+/// The cached array may be stale, e.g. it is cached before the type of the column is
+/// changed, the mismatched array should not be used and the column is decoded again.
+pub(crate) fn is_cached_array_valid(
+    array: &SizedColumnArray,
+    field: &Field,
+    column_meta: &ColumnMeta,
+) -> bool {
+    let (array, _) = array;
+    if array.data_type() != field.data_type() || array.len() != column_meta.total_rows() {
+        warn!(
+            "mismatched cached array of column {}, expect type {:?} and {} rows, got type {:?} and {} rows",
+            field.name(),
+            field.data_type(),
+            column_meta.total_rows(),
+            array.data_type(),
+            array.len()
+        );
+        return false;
+    }
+    true
+}
+
+/// The compressed bytes of the columns of a block, which are not decoded.
+///
+/// A block can be re-written by copying the bytes when the target compression
+/// is the same as `compression`, without decoding and re-encoding it.
+pub struct RawBlockColumns {
+    pub compression: Compression,
+    pub columns: HashMap<ColumnId, Bytes>,
+}
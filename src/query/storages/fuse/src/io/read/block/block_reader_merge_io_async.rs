@@ -23,9 +23,12 @@ use databend_storages_common_cache::CacheManager;
 use databend_storages_common_cache::TableDataCacheKey;
 use databend_storages_common_io::MergeIOReader;
 use databend_storages_common_io::ReadSettings;
+use databend_storages_common_table_meta::meta::BlockMeta;
 use databend_storages_common_table_meta::meta::ColumnMeta;
 
+use crate::io::read::block::block_reader_merge_io::is_cached_array_valid;
 use crate::io::BlockReader;
+use crate::io::RawBlockColumns;
 use crate::BlockReadResult;
 
 impl BlockReader {
@@ -51,7 +54,7 @@ impl BlockReader {
 
         let column_cache_key_builder = ColumnCacheKeyBuilder::new(location);
 
-        for (_index, (column_id, ..)) in self.project_indices.iter() {
+        for (_index, (column_id, field, _)) in self.project_indices.iter() {
             if let Some(ignore_column_ids) = ignore_column_ids {
                 if ignore_column_ids.contains(column_id) {
                     continue;
@@ -63,19 +66,26 @@ impl BlockReader {
 
                 let column_cache_key = column_cache_key_builder.cache_key(column_id, column_meta);
 
-                // first, check in memory table data cache
-                // column_array_cache
-                if let Some(cache_array) = column_array_cache.get_sized(&column_cache_key, len) {
-                    cached_column_array.push((*column_id, cache_array));
-                    continue;
-                }
-
-                // and then, check on disk table data cache
-                if let Some(cached_column_raw_data) =
-                    column_data_cache.get_sized(&column_cache_key, len)
-                {
-                    cached_column_data.push((*column_id, cached_column_raw_data));
-                    continue;
+                if settings.cache_policy.can_read() {
+                    // first, check in memory table data cache
+                    // column_array_cache
+                    if let Some(cache_array) = column_array_cache.get_sized(&column_cache_key, len)
+                    {
+                        if is_cached_array_valid(&cache_array, field, column_meta) {
+                            cached_column_array.push((*column_id, cache_array));
+                            continue;
+                        }
+                        // evict the stale array, so that the decoded one is cached instead
+                        column_array_cache.evict(column_cache_key.as_ref());
+                    }
+
+                    // and then, check on disk table data cache
+                    if let Some(cached_column_raw_data) =
+                        column_data_cache.get_sized(&column_cache_key, len)
+                    {
+                        cached_column_data.push((*column_id, cached_column_raw_data));
+                        continue;
+                    }
                 }
 
                 // if all caches missed, prepare the ranges to be read
@@ -93,7 +103,7 @@ impl BlockReader {
             MergeIOReader::merge_io_read(settings, self.operator.clone(), location, &ranges)
                 .await?;
 
-        if self.put_cache {
+        if self.put_cache && settings.cache_policy.can_write() {
             // add raw data (compressed raw bytes) to column cache
             for (column_id, (chunk_idx, range)) in &merge_io_result.columns_chunk_offsets {
                 // Should NOT use `range.start` as part of the cache key,
@@ -123,6 +133,47 @@ impl BlockReader {
 
         Ok(block_read_res)
     }
+
+    /// Read the compressed bytes of the projected columns of a block, without decoding them.
+    #[async_backtrace::framed]
+    pub async fn read_raw_columns(
+        &self,
+        settings: &ReadSettings,
+        meta: &BlockMeta,
+    ) -> Result<RawBlockColumns> {
+        let location = &meta.location.0;
+        let read_res = self
+            .read_columns_data_by_merge_io(settings, location, &meta.col_metas, &None)
+            .await?;
+        let mut columns = read_res.column_buffers()?;
+
+        // The columns hit in the array cache are already decoded, read their bytes from the storage.
+        let ranges = read_res
+            .cached_column_array
+            .iter()
+            .map(|(column_id, _)| {
+                // Safe to unwrap here, the column is cached by its meta.
+                let (offset, len) = meta.col_metas.get(column_id).unwrap().offset_length();
+                (*column_id, offset..(offset + len))
+            })
+            .collect::<Vec<_>>();
+        if !ranges.is_empty() {
+            let merge_io_result =
+                MergeIOReader::merge_io_read(settings, self.operator.clone(), location, &ranges)
+                    .await?;
+            for (column_id, (chunk_idx, range)) in &merge_io_result.columns_chunk_offsets {
+                let chunk = merge_io_result
+                    .owner_memory
+                    .get_chunk(*chunk_idx, &merge_io_result.block_path)?;
+                columns.insert(*column_id, chunk.slice(range.clone()));
+            }
+        }
+
+        Ok(RawBlockColumns {
+            compression: meta.compression,
+            columns,
+        })
+    }
 }
 
 struct ColumnCacheKeyBuilder<'a> {
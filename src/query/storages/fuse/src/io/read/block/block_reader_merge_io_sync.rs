@@ -24,6 +24,7 @@ use databend_storages_common_io::MergeIOReader;
 use databend_storages_common_io::ReadSettings;
 
 use crate::fuse_part::FuseBlockPartInfo;
+use crate::io::read::block::block_reader_merge_io::is_cached_array_valid;
 use crate::io::BlockReader;
 use crate::BlockReadResult;
 
@@ -39,7 +40,7 @@ impl BlockReader {
 
         let mut ranges = vec![];
         let mut cached_column_array = vec![];
-        for (_index, (column_id, ..)) in self.project_indices.iter() {
+        for (_index, (column_id, field, _)) in self.project_indices.iter() {
             if let Some(ignore_column_ids) = ignore_column_ids {
                 if ignore_column_ids.contains(column_id) {
                     continue;
@@ -51,9 +52,15 @@ impl BlockReader {
                 // first, check column array object cache
                 let (offset, len) = column_meta.offset_length();
                 let column_cache_key = TableDataCacheKey::new(block_path, *column_id, offset, len);
-                if let Some(cache_array) = column_array_cache.get(&column_cache_key) {
-                    cached_column_array.push((*column_id, cache_array));
-                    continue;
+                if settings.cache_policy.can_read() {
+                    if let Some(cache_array) = column_array_cache.get(&column_cache_key) {
+                        if is_cached_array_valid(&cache_array, field, column_meta) {
+                            cached_column_array.push((*column_id, cache_array));
+                            continue;
+                        }
+                        // evict the stale array, so that the decoded one is cached instead
+                        column_array_cache.evict(column_cache_key.as_ref());
+                    }
                 }
                 ranges.push((*column_id, offset..(offset + len)));
             }
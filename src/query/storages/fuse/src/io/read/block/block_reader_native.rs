@@ -97,6 +97,9 @@ impl BlockReader {
                         .unwrap();
                     let data = column_buffers.get(column_id).unwrap();
                     let reader: Reader = Box::new(std::io::Cursor::new(data.clone()));
+                    // The page buffer is reused by the pages of the column, but not across the
+                    // blocks of a scan, each block starts with an empty one.
+                    // TODO: thread a buffer pool of the scan through here to reuse them across blocks.
                     NativeReader::new(reader, native_meta.pages.clone(), vec![])
                 })
                 .collect();
@@ -27,11 +27,11 @@ use databend_common_native::read::reader::NativeReader;
 use databend_common_native::read::ColumnIter;
 use databend_common_native::read::NativeColumnsReader;
 use databend_common_storage::ColumnNode;
-use databend_storages_common_cache::CacheAccessor;
 use databend_storages_common_cache::CacheManager;
 use databend_storages_common_cache::TableDataCacheKey;
 use databend_storages_common_table_meta::meta::ColumnMeta;
 
+use super::block_reader_deserialize::array_buffer_size;
 use super::block_reader_deserialize::DeserializedArray;
 use super::block_reader_deserialize::FieldDeserializationContext;
 use crate::io::read::block::block_reader_merge_io::DataItem;
@@ -117,7 +117,7 @@ impl BlockReader {
                 DeserializedArray::Deserialized((_, array, ..)) => {
                     chunk_arrays.push(array.clone());
                 }
-                DeserializedArray::NoNeedToCache(array) => {
+                DeserializedArray::NoNeedToCache((array, ..)) => {
                     chunk_arrays.push(array.clone());
                 }
                 DeserializedArray::Cached(sized_column) => {
@@ -126,6 +126,16 @@ impl BlockReader {
             }
         }
 
+        // Perf.
+        {
+            let deserialized_bytes = deserialized_column_arrays
+                .iter()
+                .filter(|array| !matches!(array, DeserializedArray::Cached(_)))
+                .map(|array| array.size())
+                .sum::<usize>();
+            metrics_inc_remote_io_deserialized_bytes(deserialized_bytes as u64);
+        }
+
         // build data block
         let data_block = if need_to_fill_default_val {
             let mut default_vals = Vec::with_capacity(need_default_vals.len());
@@ -154,15 +164,22 @@ impl BlockReader {
         };
 
         // populate cache if necessary
-        if self.put_cache {
+        if self.put_cache && self.read_settings.cache_policy.can_write() {
             if let Some(cache) = CacheManager::instance().get_table_data_array_cache() {
                 // populate array cache items
                 for item in deserialized_column_arrays.into_iter() {
                     if let DeserializedArray::Deserialized((column_id, array, size)) = item {
+                        if !self.read_settings.can_cache_array(size) {
+                            continue;
+                        }
                         let meta = column_metas.get(&column_id).unwrap();
                         let (offset, len) = meta.offset_length();
                         let key = TableDataCacheKey::new(block_path, column_id, offset, len);
-                        cache.insert(key.into(), (array, size));
+                        if self.pinned_columns.contains(&column_id) {
+                            cache.insert_pinned_if_absent(key.into(), (array, size));
+                        } else {
+                            cache.insert_if_absent(key.into(), (array, size));
+                        }
                     }
                 }
             }
@@ -170,7 +187,7 @@ impl BlockReader {
         Ok(data_block)
     }
 
-    fn chunks_to_native_column(
+    pub(super) fn chunks_to_native_column(
         &self,
         metas: Vec<&ColumnMeta>,
         chunks: Vec<&[u8]>,
@@ -209,7 +226,6 @@ impl BlockReader {
         let mut field_column_metas = Vec::with_capacity(estimated_cap);
         let mut field_column_data = Vec::with_capacity(estimated_cap);
         let mut field_leaf_ids = Vec::with_capacity(estimated_cap);
-        let mut field_uncompressed_size = 0;
 
         for (i, leaf_index) in indices.iter().enumerate() {
             let column_id = column_node.leaf_column_ids[i];
@@ -220,7 +236,6 @@ impl BlockReader {
                             field_column_metas.push(column_meta);
                             field_column_data.push(data.as_ref());
                             field_leaf_ids.push(*leaf_index);
-                            field_uncompressed_size += data.len();
                         }
                         DataItem::ColumnArray(column_array) => {
                             if is_nested {
@@ -240,6 +255,8 @@ impl BlockReader {
                     return Ok(None);
                 }
             } else {
+                self.check_missing_column(column_id, deserialization_context.column_metas)?;
+                // the column added after the block was written is filled with the default value
                 break;
             }
         }
@@ -251,18 +268,17 @@ impl BlockReader {
                 column_node.table_field.clone(),
             )?;
             let array = column.clone().into_arrow_rs();
+            let size = array_buffer_size(&array.to_data());
             // mark the array
             if is_nested {
                 // the array is not intended to be cached
                 // currently, caching of compound field columns is not support
-                Ok(Some(DeserializedArray::NoNeedToCache(array)))
+                Ok(Some(DeserializedArray::NoNeedToCache((array, size))))
             } else {
                 // the array is deserialized from raw bytes, should be cached
                 let column_id = column_node.leaf_column_ids[0];
                 Ok(Some(DeserializedArray::Deserialized((
-                    column_id,
-                    array,
-                    field_uncompressed_size,
+                    column_id, array, size,
                 ))))
             }
         } else {
@@ -0,0 +1,201 @@
+// Copyright 2021 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use databend_common_exception::ErrorCode;
+use databend_common_exception::Result;
+use databend_common_expression::types::Bitmap;
+use databend_common_expression::types::DataType;
+use databend_common_expression::Column;
+use databend_common_expression::ColumnBuilder;
+use databend_common_expression::ColumnId;
+use databend_common_expression::Scalar;
+use databend_storages_common_io::MergeIOReader;
+use databend_storages_common_io::ReadSettings;
+use databend_storages_common_table_meta::meta::BlockMeta;
+use futures::stream;
+use futures::StreamExt;
+use futures::TryStreamExt;
+
+use super::block_reader_merge_io::DataItem;
+use crate::io::read::block::parquet::column_chunks_to_record_batch;
+use crate::io::read::block::parquet::validate_page_checksums;
+use crate::io::BlockReader;
+use crate::BlockReadResult;
+use crate::FuseStorageFormat;
+
+impl BlockReader {
+    /// Read and deserialize a single column of the blocks, one column per block.
+    ///
+    /// Only the chunk of the column is fetched from each block, at most `max_concurrent_io` fetches
+    /// are in flight, or `max_storage_io_requests` if it is 0. Each block is deserialized as soon
+    /// as its chunk arrives. The column must be a projected, non-nested column of the reader.
+    /// Blocks written before the column was added get the default value of the column.
+    #[async_backtrace::framed]
+    pub async fn read_single_column(
+        &self,
+        settings: &ReadSettings,
+        column_id: ColumnId,
+        metas: &[BlockMeta],
+        storage_format: &FuseStorageFormat,
+    ) -> Result<Vec<Column>> {
+        let index = self.single_column_index(column_id)?;
+        let max_concurrent_io = match settings.max_concurrent_io {
+            0 => self.ctx.get_settings().get_max_storage_io_requests()? as usize,
+            max_concurrent_io => max_concurrent_io,
+        };
+        let reads = metas.iter().enumerate().map(|(i, meta)| async move {
+            let ranges = match meta.col_metas.get(&column_id) {
+                Some(column_meta) => {
+                    let (offset, len) = column_meta.offset_length();
+                    vec![(column_id, offset..(offset + len))]
+                }
+                None => vec![],
+            };
+            let merge_io_result = MergeIOReader::merge_io_read(
+                settings,
+                self.operator.clone(),
+                &meta.location.0,
+                &ranges,
+            )
+            .await?;
+            Ok::<_, ErrorCode>((i, merge_io_result))
+        });
+        let mut reads = stream::iter(reads).buffer_unordered(max_concurrent_io);
+
+        let mut columns = vec![None; metas.len()];
+        while let Some((i, merge_io_result)) = reads.try_next().await? {
+            let data = BlockReadResult::create(merge_io_result, vec![], vec![]);
+            columns[i] =
+                Some(self.deserialize_single_column(index, &metas[i], storage_format, data)?);
+        }
+        Ok(columns.into_iter().map(Option::unwrap).collect())
+    }
+
+    /// Read only the validity of a single column of the block, for the queries that count
+    /// the nulls, and return it with the null count.
+    ///
+    /// Only the validity is decoded and the values are skipped, that is the validity at the start
+    /// of each page for the native format, and the definition levels of the pages for the parquet
+    /// format. A column that is not nullable is all valid, a column missing from the block is
+    /// all null or all valid depending on its default value.
+    #[async_backtrace::framed]
+    pub async fn read_column_validity(
+        &self,
+        settings: &ReadSettings,
+        column_id: ColumnId,
+        meta: &BlockMeta,
+        storage_format: &FuseStorageFormat,
+    ) -> Result<(Bitmap, usize)> {
+        let index = self.single_column_index(column_id)?;
+        let field = &self.projected_schema.fields()[index];
+        let num_rows = meta.row_count as usize;
+
+        let validity = match meta.col_metas.get(&column_id) {
+            None => {
+                let is_null = self.default_vals[index] == Scalar::Null;
+                Bitmap::new_constant(!is_null, num_rows)
+            }
+            Some(_) if !field.data_type().is_nullable() => Bitmap::new_constant(true, num_rows),
+            Some(column_meta) => {
+                let (offset, len) = column_meta.offset_length();
+                let ranges = vec![(column_id, offset..(offset + len))];
+                let merge_io_result = MergeIOReader::merge_io_read(
+                    settings,
+                    self.operator.clone(),
+                    &meta.location.0,
+                    &ranges,
+                )
+                .await?;
+                let data = BlockReadResult::create(merge_io_result, vec![], vec![]);
+                let column_chunks = data.columns_chunks()?;
+                let Some(DataItem::RawData(chunk)) = column_chunks.get(&column_id) else {
+                    return Err(ErrorCode::Internal(format!(
+                        "the chunk of column {} is not read",
+                        column_id
+                    )));
+                };
+                match storage_format {
+                    FuseStorageFormat::Parquet => {
+                        if self.read_settings.enable_parquet_page_checksum {
+                            validate_page_checksums(column_id, chunk)?;
+                        }
+                        self.parquet_decode_schema()?.to_validity(
+                            column_id,
+                            num_rows,
+                            chunk.clone(),
+                            &meta.compression,
+                        )?
+                    }
+                    FuseStorageFormat::Native => {
+                        let pages = &column_meta.as_native().unwrap().pages;
+                        self.native_columns_reader.batch_read_validity(
+                            chunk.as_ref(),
+                            true,
+                            pages,
+                        )?
+                    }
+                }
+            }
+        };
+        let null_count = validity.null_count();
+        Ok((validity, null_count))
+    }
+
+    fn single_column_index(&self, column_id: ColumnId) -> Result<usize> {
+        self.project_column_nodes
+            .iter()
+            .position(|node| !node.is_nested && node.leaf_column_ids == [column_id])
+            .ok_or_else(|| {
+                ErrorCode::BadArguments(format!(
+                    "column {} is not a projected non-nested column of the block reader",
+                    column_id
+                ))
+            })
+    }
+
+    fn deserialize_single_column(
+        &self,
+        index: usize,
+        meta: &BlockMeta,
+        storage_format: &FuseStorageFormat,
+        data: BlockReadResult,
+    ) -> Result<Column> {
+        let field = &self.projected_schema.fields()[index];
+        let data_type: DataType = field.data_type().into();
+        let num_rows = meta.row_count as usize;
+
+        let column_chunks = data.columns_chunks()?;
+        let Some(DataItem::RawData(chunk)) = column_chunks.get(&field.column_id) else {
+            let default_val = self.default_vals[index].as_ref();
+            return Ok(ColumnBuilder::repeat(&default_val, num_rows, &data_type).build());
+        };
+
+        match storage_format {
+            FuseStorageFormat::Parquet => {
+                let record_batch = column_chunks_to_record_batch(
+                    &self.original_schema,
+                    num_rows,
+                    &column_chunks,
+                    &meta.compression,
+                )?;
+                // only the chunk of the column is deserialized
+                Column::from_arrow_rs(record_batch.column(0).clone(), &data_type)
+            }
+            FuseStorageFormat::Native => {
+                let column_meta = meta.col_metas.get(&field.column_id).unwrap();
+                self.chunks_to_native_column(vec![column_meta], vec![chunk.as_ref()], field.clone())
+            }
+        }
+    }
+}
@@ -20,9 +20,12 @@ mod block_reader_merge_io_sync;
 mod block_reader_native;
 mod block_reader_native_deserialize;
 mod block_reader_parquet_deserialize;
+mod block_reader_single_column;
 pub mod parquet;
 
 pub use block_reader::BlockReader;
 pub use block_reader_merge_io::BlockReadResult;
+pub use block_reader_merge_io::DataItem;
+pub use block_reader_merge_io::RawBlockColumns;
 pub use block_reader_native::NativeReaderExt;
 pub use block_reader_native::NativeSourceData;
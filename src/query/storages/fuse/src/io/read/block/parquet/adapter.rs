@@ -20,19 +20,26 @@ use std::sync::Arc;
 use bytes::Bytes;
 use parquet::arrow::arrow_reader::RowGroups;
 use parquet::basic::Compression;
+use parquet::column::page::Page;
 use parquet::column::page::PageIterator;
+use parquet::column::page::PageMetadata;
 use parquet::column::page::PageReader;
+use parquet::errors::ParquetError;
 use parquet::errors::Result as ParquetResult;
 use parquet::file::metadata::ColumnChunkMetaData;
 use parquet::file::serialized_reader::SerializedPageReader;
 use parquet::schema::types::SchemaDescriptor;
 
+/// Whether the decode should stop, checked before each page is read.
+pub type AbortCheck = Arc<dyn Fn() -> bool + Send + Sync>;
+
 pub struct RowGroupImplBuilder<'a> {
     num_rows: usize,
     column_chunks: HashMap<usize, Bytes>,
     column_chunk_metadatas: HashMap<usize, ColumnChunkMetaData>,
     schema_descriptor: &'a SchemaDescriptor,
     compression: Compression,
+    abort_check: Option<AbortCheck>,
 }
 
 impl<'a> RowGroupImplBuilder<'a> {
@@ -47,9 +54,16 @@ impl<'a> RowGroupImplBuilder<'a> {
             column_chunk_metadatas: HashMap::new(),
             schema_descriptor,
             compression,
+            abort_check: None,
         }
     }
 
+    /// Stop reading the pages of the column chunks once the check returns true.
+    pub fn with_abort_check(mut self, abort_check: AbortCheck) -> Self {
+        self.abort_check = Some(abort_check);
+        self
+    }
+
     pub fn add_column_chunk(&mut self, dfs_id: usize, column_chunk: Bytes) {
         let column_chunk_metadata =
             ColumnChunkMetaData::builder(self.schema_descriptor.column(dfs_id))
@@ -68,6 +82,7 @@ impl<'a> RowGroupImplBuilder<'a> {
             num_rows: self.num_rows,
             column_chunks: self.column_chunks,
             column_chunk_metadatas: self.column_chunk_metadatas,
+            abort_check: self.abort_check,
         }
     }
 }
@@ -77,6 +92,7 @@ pub struct RowGroupImpl {
     num_rows: usize,
     column_chunks: HashMap<usize, Bytes>,
     column_chunk_metadatas: HashMap<usize, ColumnChunkMetaData>,
+    abort_check: Option<AbortCheck>,
 }
 
 impl RowGroups for RowGroupImpl {
@@ -89,12 +105,18 @@ impl RowGroups for RowGroupImpl {
     fn column_chunks(&self, i: usize) -> ParquetResult<Box<dyn PageIterator>> {
         let column_chunk = Arc::new(self.column_chunks.get(&i).unwrap().clone());
         let column_chunk_meta = self.column_chunk_metadatas.get(&i).unwrap();
-        let page_reader = Box::new(SerializedPageReader::new(
+        let mut page_reader: Box<dyn PageReader> = Box::new(SerializedPageReader::new(
             column_chunk,
             column_chunk_meta,
             self.num_rows(),
             None,
         )?);
+        if let Some(abort_check) = &self.abort_check {
+            page_reader = Box::new(AbortablePageReader {
+                inner: page_reader,
+                abort_check: abort_check.clone(),
+            });
+        }
 
         Ok(Box::new(PageIteratorImpl {
             reader: Some(Ok(page_reader)),
@@ -115,3 +137,47 @@ impl Iterator for PageIteratorImpl {
 }
 
 impl PageIterator for PageIteratorImpl {}
+
+// Fails to read the next page once the decode is aborted, so a long decode stops
+// between the pages instead of running to the end.
+struct AbortablePageReader {
+    inner: Box<dyn PageReader>,
+    abort_check: AbortCheck,
+}
+
+impl AbortablePageReader {
+    fn check(&self) -> ParquetResult<()> {
+        if (self.abort_check)() {
+            return Err(ParquetError::General("the decode is aborted".to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for AbortablePageReader {
+    type Item = ParquetResult<Page>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.get_next_page().transpose()
+    }
+}
+
+impl PageReader for AbortablePageReader {
+    fn get_next_page(&mut self) -> ParquetResult<Option<Page>> {
+        self.check()?;
+        self.inner.get_next_page()
+    }
+
+    fn peek_next_page(&mut self) -> ParquetResult<Option<PageMetadata>> {
+        self.inner.peek_next_page()
+    }
+
+    fn skip_next_page(&mut self) -> ParquetResult<()> {
+        self.check()?;
+        self.inner.skip_next_page()
+    }
+
+    fn at_record_boundary(&mut self) -> ParquetResult<bool> {
+        self.inner.at_record_boundary()
+    }
+}
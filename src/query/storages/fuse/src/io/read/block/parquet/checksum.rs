@@ -0,0 +1,128 @@
+// Copyright 2021 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use databend_common_exception::ErrorCode;
+use databend_common_exception::Result;
+use databend_common_expression::ColumnId;
+use parquet::format::PageHeader;
+use parquet::thrift::TSerializable;
+use thrift::protocol::TCompactInputProtocol;
+
+/// Validate the CRC32 checksums of all pages in a parquet column chunk.
+///
+/// Pages written without a checksum are skipped. Fuse writes its blocks with the parquet-rs
+/// writer, which puts no checksum in the page headers, so only the blocks written by other
+/// writers with page checksums are validated.
+pub fn validate_page_checksums(column_id: ColumnId, chunk: &[u8]) -> Result<()> {
+    let mut remaining = chunk;
+    let mut page_index = 0;
+    while !remaining.is_empty() {
+        let header = {
+            let mut prot = TCompactInputProtocol::new(&mut remaining);
+            PageHeader::read_from_in_protocol(&mut prot).map_err(|err| {
+                ErrorCode::CorruptParquet(format!(
+                    "Invalid parquet page header, column id: {}, page index: {}, error: {}",
+                    column_id, page_index, err
+                ))
+            })?
+        };
+
+        let page_size = header.compressed_page_size as usize;
+        if header.compressed_page_size < 0 || page_size > remaining.len() {
+            return Err(ErrorCode::CorruptParquet(format!(
+                "Invalid parquet page size, column id: {}, page index: {}, page size: {}, remaining: {}",
+                column_id,
+                page_index,
+                header.compressed_page_size,
+                remaining.len()
+            )));
+        }
+        let (page, rest) = remaining.split_at(page_size);
+
+        if let Some(expected) = header.crc {
+            let actual = crc32fast::hash(page) as i32;
+            if actual != expected {
+                return Err(ErrorCode::CorruptParquet(format!(
+                    "Parquet page checksum mismatch, column id: {}, page index: {}, expected: {}, actual: {}",
+                    column_id, page_index, expected, actual
+                )));
+            }
+        }
+
+        remaining = rest;
+        page_index += 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use databend_common_exception::ErrorCode;
+    use parquet::format::PageHeader;
+    use parquet::format::PageType;
+    use parquet::thrift::TSerializable;
+    use thrift::protocol::TCompactOutputProtocol;
+
+    use super::validate_page_checksums;
+
+    fn write_page(buf: &mut Vec<u8>, data: &[u8], with_crc: bool) {
+        let crc = with_crc.then(|| crc32fast::hash(data) as i32);
+        let header = PageHeader {
+            type_: PageType::DATA_PAGE,
+            uncompressed_page_size: data.len() as i32,
+            compressed_page_size: data.len() as i32,
+            crc,
+            data_page_header: None,
+            index_page_header: None,
+            dictionary_page_header: None,
+            data_page_header_v2: None,
+        };
+        let mut prot = TCompactOutputProtocol::new(&mut *buf);
+        header.write_to_out_protocol(&mut prot).unwrap();
+        buf.extend_from_slice(data);
+    }
+
+    #[test]
+    fn test_validate_page_checksums() {
+        let mut chunk = vec![];
+        write_page(&mut chunk, b"page 0", true);
+        write_page(&mut chunk, b"page 1", true);
+        assert!(validate_page_checksums(1, &chunk).is_ok());
+
+        // corrupt the last byte of the second page
+        let last = chunk.len() - 1;
+        chunk[last] ^= 0xff;
+        let err = validate_page_checksums(1, &chunk).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::CORRUPT_PARQUET);
+        assert!(err.message().contains("page index: 1"));
+    }
+
+    #[test]
+    fn test_validate_page_without_checksum() {
+        let mut chunk = vec![];
+        write_page(&mut chunk, b"page 0", false);
+        let last = chunk.len() - 1;
+        chunk[last] ^= 0xff;
+        assert!(validate_page_checksums(1, &chunk).is_ok());
+    }
+
+    #[test]
+    fn test_validate_truncated_page() {
+        let mut chunk = vec![];
+        write_page(&mut chunk, b"page 0", true);
+        chunk.pop();
+        let err = validate_page_checksums(1, &chunk).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::CORRUPT_PARQUET);
+    }
+}
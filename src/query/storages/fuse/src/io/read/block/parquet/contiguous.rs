@@ -0,0 +1,343 @@
+// Copyright 2021 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use databend_common_exception::ErrorCode;
+use databend_common_exception::Result;
+use databend_common_expression::types::Bitmap;
+use databend_common_expression::types::DecimalColumn;
+use databend_common_expression::types::NumberColumn;
+use databend_common_expression::Column;
+use databend_common_expression::ColumnId;
+use databend_common_expression::DataBlock;
+use databend_storages_common_table_meta::meta::ColumnMeta;
+use databend_storages_common_table_meta::meta::Compression;
+
+use crate::io::read::block::block_reader_merge_io::DataItem;
+use crate::io::BlockReader;
+
+/// The values of a column, laid out in a single native buffer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContiguousBuffer {
+    /// Fixed-size values in native byte order, `width` bytes per row.
+    /// Booleans take one byte per row.
+    Fixed { width: usize, data: Vec<u8> },
+    /// Variable-length values, the value of row `i` is `data[offsets[i]..offsets[i + 1]]`.
+    Variable { offsets: Vec<u64>, data: Vec<u8> },
+}
+
+/// A column compacted into a contiguous value buffer, with the null mask kept separately.
+///
+/// The columns are compacted after the block is deserialized: the parquet pages are decoded
+/// by the arrow reader into arrow arrays, which own their buffers, so the pages can't be
+/// decoded straight into the caller's buffers without a page decoder of our own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContiguousColumn {
+    /// `None` if the column is not nullable. The values of the null rows are unspecified.
+    pub validity: Option<Bitmap>,
+    pub buffer: ContiguousBuffer,
+}
+
+impl Default for ContiguousColumn {
+    fn default() -> Self {
+        ContiguousColumn {
+            validity: None,
+            buffer: ContiguousBuffer::Fixed {
+                width: 0,
+                data: vec![],
+            },
+        }
+    }
+}
+
+impl ContiguousColumn {
+    pub fn try_from_column(column: &Column) -> Result<ContiguousColumn> {
+        let mut contiguous = ContiguousColumn::default();
+        contiguous.fill_from_column(column)?;
+        Ok(contiguous)
+    }
+
+    /// Compact the column into the buffers of `self`, replacing the previous values.
+    ///
+    /// The allocations of the buffers are reused, they only grow if the column
+    /// is larger than their capacity, or if the buffer has another layout.
+    pub fn fill_from_column(&mut self, column: &Column) -> Result<()> {
+        if let Column::Nullable(nullable) = column {
+            self.fill_from_column(&nullable.column)?;
+            self.validity = Some(nullable.validity.clone());
+            return Ok(());
+        }
+
+        let buffer = &mut self.buffer;
+        match column {
+            Column::Number(column) => match column {
+                NumberColumn::UInt8(values) => fill_fixed_width(buffer, values.as_slice()),
+                NumberColumn::UInt16(values) => fill_fixed_width(buffer, values.as_slice()),
+                NumberColumn::UInt32(values) => fill_fixed_width(buffer, values.as_slice()),
+                NumberColumn::UInt64(values) => fill_fixed_width(buffer, values.as_slice()),
+                NumberColumn::Int8(values) => fill_fixed_width(buffer, values.as_slice()),
+                NumberColumn::Int16(values) => fill_fixed_width(buffer, values.as_slice()),
+                NumberColumn::Int32(values) => fill_fixed_width(buffer, values.as_slice()),
+                NumberColumn::Int64(values) => fill_fixed_width(buffer, values.as_slice()),
+                NumberColumn::Float32(values) => fill_fixed_width(buffer, values.as_slice()),
+                NumberColumn::Float64(values) => fill_fixed_width(buffer, values.as_slice()),
+            },
+            Column::Decimal(DecimalColumn::Decimal128(values, _)) => {
+                fill_fixed_width(buffer, values.as_slice())
+            }
+            Column::Decimal(DecimalColumn::Decimal256(values, _)) => {
+                fill_fixed_width(buffer, values.as_slice())
+            }
+            Column::Timestamp(values) => fill_fixed_width(buffer, values.as_slice()),
+            Column::Date(values) => fill_fixed_width(buffer, values.as_slice()),
+            Column::Interval(values) => fill_fixed_width(buffer, values.as_slice()),
+            Column::Boolean(bitmap) => {
+                let data = fixed_width_data(buffer, 1);
+                data.extend(bitmap.iter().map(u8::from));
+            }
+            Column::String(column) => {
+                fill_variable_width(buffer, column.iter().map(|v| v.as_bytes()))
+            }
+            Column::Binary(column)
+            | Column::Bitmap(column)
+            | Column::Variant(column)
+            | Column::Geometry(column) => fill_variable_width(buffer, column.iter()),
+            Column::Geography(column) => fill_variable_width(buffer, column.0.iter()),
+            _ => {
+                return Err(ErrorCode::Unimplemented(format!(
+                    "Cannot compact column of type {} into a contiguous buffer",
+                    column.data_type()
+                )));
+            }
+        }
+        self.validity = None;
+        Ok(())
+    }
+
+    /// Returns the bytes of the value at `row`, or `None` if it is null.
+    pub fn value_at(&self, row: usize) -> Option<&[u8]> {
+        if let Some(validity) = &self.validity {
+            if !validity.get_bit(row) {
+                return None;
+            }
+        }
+        match &self.buffer {
+            ContiguousBuffer::Fixed { width, data } => Some(&data[row * width..(row + 1) * width]),
+            ContiguousBuffer::Variable { offsets, data } => {
+                Some(&data[offsets[row] as usize..offsets[row + 1] as usize])
+            }
+        }
+    }
+}
+
+// Returns the cleared data of the fixed-size buffer with the given width,
+// the buffer is replaced if it was a variable-length one.
+fn fixed_width_data(buffer: &mut ContiguousBuffer, value_width: usize) -> &mut Vec<u8> {
+    if !matches!(buffer, ContiguousBuffer::Fixed { .. }) {
+        *buffer = ContiguousBuffer::Fixed {
+            width: value_width,
+            data: vec![],
+        };
+    }
+    let ContiguousBuffer::Fixed { width, data } = buffer else {
+        unreachable!()
+    };
+    *width = value_width;
+    data.clear();
+    data
+}
+
+fn fill_fixed_width<T: Copy>(buffer: &mut ContiguousBuffer, values: &[T]) {
+    // SAFETY: the column values are plain old data, reading them as bytes is always valid.
+    let bytes = unsafe {
+        std::slice::from_raw_parts(values.as_ptr() as *const u8, std::mem::size_of_val(values))
+    };
+    fixed_width_data(buffer, std::mem::size_of::<T>()).extend_from_slice(bytes);
+}
+
+fn fill_variable_width<'a>(buffer: &mut ContiguousBuffer, values: impl Iterator<Item = &'a [u8]>) {
+    if !matches!(buffer, ContiguousBuffer::Variable { .. }) {
+        *buffer = ContiguousBuffer::Variable {
+            offsets: vec![],
+            data: vec![],
+        };
+    }
+    let ContiguousBuffer::Variable { offsets, data } = buffer else {
+        unreachable!()
+    };
+    offsets.clear();
+    data.clear();
+    offsets.push(0);
+    for value in values {
+        data.extend_from_slice(value);
+        offsets.push(data.len() as u64);
+    }
+}
+
+impl BlockReader {
+    /// Deserialize the column chunks like `deserialize_parquet_chunks`, and also compact every
+    /// column of the block into a contiguous buffer, for the embedders which process native
+    /// buffers directly, e.g. with SIMD or on GPU.
+    pub fn deserialize_parquet_chunks_contiguous(
+        &self,
+        num_rows: usize,
+        column_metas: &HashMap<ColumnId, ColumnMeta>,
+        column_chunks: HashMap<ColumnId, DataItem>,
+        compression: &Compression,
+        block_path: &str,
+    ) -> Result<(DataBlock, Vec<ContiguousColumn>)> {
+        let block = self.deserialize_parquet_chunks(
+            num_rows,
+            column_metas,
+            column_chunks,
+            compression,
+            block_path,
+        )?;
+        let columns = block
+            .columns()
+            .iter()
+            .map(|entry| {
+                let column = entry
+                    .value
+                    .convert_to_full_column(&entry.data_type, num_rows);
+                ContiguousColumn::try_from_column(&column)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok((block, columns))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use databend_common_expression::types::number::Float64Type;
+    use databend_common_expression::types::number::Int32Type;
+    use databend_common_expression::types::number::Int64Type;
+    use databend_common_expression::types::BooleanType;
+    use databend_common_expression::types::StringType;
+    use databend_common_expression::types::VariantType;
+    use databend_common_expression::FromData;
+
+    use super::ContiguousBuffer;
+    use super::ContiguousColumn;
+
+    #[test]
+    fn test_contiguous_column_values() {
+        let ints = vec![1, -2, 3, i32::MAX];
+        let floats = vec![0.5, -1.25, 3.0, f64::MIN];
+        let bools = vec![true, false, false, true];
+        let strings = vec!["a", "", "databend", "contiguous"];
+        let variants = vec![b"1".to_vec(), b"[]".to_vec(), vec![], b"{}".to_vec()];
+        let validity = vec![true, false, true, false];
+
+        let nullable = Int32Type::from_data_with_validity(ints.clone(), validity.clone());
+        let contiguous = ContiguousColumn::try_from_column(&nullable).unwrap();
+        assert!(matches!(contiguous.buffer, ContiguousBuffer::Fixed {
+            width: 4,
+            ..
+        }));
+        for (row, (value, valid)) in ints.iter().zip(validity.iter()).enumerate() {
+            let actual = contiguous
+                .value_at(row)
+                .map(|v| i32::from_ne_bytes(v.try_into().unwrap()));
+            assert_eq!(actual, valid.then_some(*value));
+        }
+
+        let contiguous =
+            ContiguousColumn::try_from_column(&Float64Type::from_data(floats.clone())).unwrap();
+        assert!(contiguous.validity.is_none());
+        for (row, value) in floats.iter().enumerate() {
+            let actual = f64::from_ne_bytes(contiguous.value_at(row).unwrap().try_into().unwrap());
+            assert_eq!(actual, *value);
+        }
+
+        let contiguous =
+            ContiguousColumn::try_from_column(&BooleanType::from_data(bools.clone())).unwrap();
+        for (row, value) in bools.iter().enumerate() {
+            assert_eq!(contiguous.value_at(row), Some([*value as u8].as_slice()));
+        }
+
+        let contiguous =
+            ContiguousColumn::try_from_column(&StringType::from_data(strings.clone())).unwrap();
+        let ContiguousBuffer::Variable { offsets, .. } = &contiguous.buffer else {
+            panic!("expect variable-length buffer");
+        };
+        assert_eq!(offsets.len(), strings.len() + 1);
+        for (row, value) in strings.iter().enumerate() {
+            assert_eq!(contiguous.value_at(row), Some(value.as_bytes()));
+        }
+
+        let contiguous =
+            ContiguousColumn::try_from_column(&VariantType::from_data(variants.clone())).unwrap();
+        for (row, value) in variants.iter().enumerate() {
+            assert_eq!(contiguous.value_at(row), Some(value.as_slice()));
+        }
+    }
+
+    #[test]
+    fn test_fill_reused_contiguous_column() {
+        let mut contiguous = ContiguousColumn::default();
+        let fixed_data = |contiguous: &ContiguousColumn| match &contiguous.buffer {
+            ContiguousBuffer::Fixed { data, .. } => (data.as_ptr(), data.capacity()),
+            _ => panic!("expect fixed-size buffer"),
+        };
+
+        // the buffer grows for a block larger than its capacity
+        let ints = (0..1024).collect::<Vec<i64>>();
+        contiguous
+            .fill_from_column(&Int64Type::from_data(ints.clone()))
+            .unwrap();
+        let (ptr, capacity) = fixed_data(&contiguous);
+        assert!(capacity >= ints.len() * 8);
+
+        // the smaller blocks are written into the same allocation
+        for round in 0..3 {
+            let ints = (0..100).map(|v| v * round).collect::<Vec<i64>>();
+            let validity = (0..100).map(|v| v % 3 != 0).collect::<Vec<_>>();
+            let column = if round == 1 {
+                Int64Type::from_data_with_validity(ints.clone(), validity.clone())
+            } else {
+                Int64Type::from_data(ints.clone())
+            };
+            contiguous.fill_from_column(&column).unwrap();
+            assert_eq!(fixed_data(&contiguous), (ptr, capacity));
+            assert_eq!(contiguous.validity.is_some(), round == 1);
+            for (row, value) in ints.iter().enumerate() {
+                let actual = contiguous
+                    .value_at(row)
+                    .map(|v| i64::from_ne_bytes(v.try_into().unwrap()));
+                let valid = round != 1 || validity[row];
+                assert_eq!(actual, valid.then_some(*value));
+            }
+        }
+
+        // the buffer is replaced for another layout, the same as a new one
+        let strings = vec!["a", "", "reused"];
+        contiguous
+            .fill_from_column(&StringType::from_data(strings.clone()))
+            .unwrap();
+        assert_eq!(
+            contiguous,
+            ContiguousColumn::try_from_column(&StringType::from_data(strings)).unwrap()
+        );
+        let bools = vec![true, false];
+        contiguous
+            .fill_from_column(&BooleanType::from_data(bools.clone()))
+            .unwrap();
+        assert_eq!(
+            contiguous,
+            ContiguousColumn::try_from_column(&BooleanType::from_data(bools)).unwrap()
+        );
+    }
+}
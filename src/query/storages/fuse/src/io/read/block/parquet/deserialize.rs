@@ -13,17 +13,40 @@
 // limitations under the License.
 
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
 
 use arrow_array::RecordBatch;
+use arrow_array::RecordBatchReader;
+use arrow_schema::DataType as ArrowDataType;
 use arrow_schema::Schema;
+use bytes::Bytes;
+use databend_common_catalog::table_context::AbortChecker;
+use databend_common_exception::ErrorCode;
+use databend_common_expression::types::Bitmap;
 use databend_common_expression::ColumnId;
 use databend_common_expression::TableSchema;
+use databend_common_storage::ColumnNode;
 use databend_storages_common_table_meta::meta::Compression;
+use databend_storages_common_table_meta::table::to_parquet_compression;
+use databend_storages_common_table_meta::table::CompressionFallback;
 use parquet::arrow::arrow_reader::ParquetRecordBatchReader;
+use parquet::arrow::arrow_reader::RowGroups;
 use parquet::arrow::arrow_to_parquet_schema;
 use parquet::arrow::parquet_to_arrow_field_levels;
 use parquet::arrow::ProjectionMask;
-use parquet::basic::Compression as ParquetCompression;
+use parquet::basic::Encoding;
+use parquet::column::reader::decoder::ColumnValueDecoder;
+use parquet::column::reader::decoder::DefinitionLevelDecoderImpl;
+use parquet::column::reader::decoder::RepetitionLevelDecoderImpl;
+use parquet::column::reader::GenericColumnReader;
+use parquet::errors::Result as ParquetResult;
+use parquet::format::PageHeader;
+use parquet::format::PageType;
+use parquet::schema::types::ColumnDescPtr;
+use parquet::schema::types::SchemaDescriptor;
+use parquet::thrift::TSerializable;
+use thrift::protocol::TCompactInputProtocol;
 
 use crate::io::read::block::block_reader_merge_io::DataItem;
 use crate::io::read::block::parquet::adapter::RowGroupImplBuilder;
@@ -35,43 +58,823 @@ pub fn column_chunks_to_record_batch(
     column_chunks: &HashMap<ColumnId, DataItem>,
     compression: &Compression,
 ) -> databend_common_exception::Result<RecordBatch> {
-    let arrow_schema = Schema::from(original_schema);
-    let parquet_schema = arrow_to_parquet_schema(&arrow_schema)?;
-    let column_id_to_dfs_id = original_schema
-        .to_leaf_column_ids()
-        .iter()
-        .enumerate()
-        .map(|(dfs_id, column_id)| (*column_id, dfs_id))
-        .collect::<HashMap<_, _>>();
-    let mut projection_mask = Vec::with_capacity(column_chunks.len());
-    let mut builder = RowGroupImplBuilder::new(
+    ParquetDecodeSchema::try_create(original_schema)?.to_record_batch(
         num_rows,
-        &parquet_schema,
-        ParquetCompression::from(*compression),
-    );
-    for (column_id, data_item) in column_chunks.iter() {
-        match data_item {
-            DataItem::RawData(bytes) => {
-                let dfs_id = column_id_to_dfs_id.get(column_id).cloned().unwrap();
-                projection_mask.push(dfs_id);
-                builder.add_column_chunk(dfs_id, bytes.clone());
+        column_chunks,
+        compression,
+    )
+}
+
+/// The arrow and parquet schemas to decode the column chunks of the blocks of a table.
+///
+/// The schemas only depend on the table schema, they can be built once and shared by
+/// all the blocks of the same schema.
+pub struct ParquetDecodeSchema {
+    arrow_schema: Schema,
+    parquet_schema: SchemaDescriptor,
+    column_id_to_dfs_id: HashMap<ColumnId, usize>,
+    // the positions and column ids of the top level string fields.
+    string_fields: Vec<(usize, ColumnId)>,
+    dictionary_strings: bool,
+    compression_fallback: CompressionFallback,
+    abort_checker: Option<AbortChecker>,
+}
+
+impl ParquetDecodeSchema {
+    pub fn try_create(original_schema: &TableSchema) -> databend_common_exception::Result<Self> {
+        let arrow_schema = Schema::from(original_schema);
+        let parquet_schema = arrow_to_parquet_schema(&arrow_schema)?;
+        let column_id_to_dfs_id = original_schema
+            .to_leaf_column_ids()
+            .iter()
+            .enumerate()
+            .map(|(dfs_id, column_id)| (*column_id, dfs_id))
+            .collect::<HashMap<_, _>>();
+        let string_fields = original_schema
+            .fields()
+            .iter()
+            .zip(arrow_schema.fields().iter())
+            .enumerate()
+            .filter(|(_, (_, arrow_field))| {
+                matches!(
+                    arrow_field.data_type(),
+                    ArrowDataType::Utf8 | ArrowDataType::Utf8View
+                )
+            })
+            .map(|(i, (field, _))| (i, field.column_id))
+            .collect();
+        Ok(Self {
+            arrow_schema,
+            parquet_schema,
+            column_id_to_dfs_id,
+            string_fields,
+            dictionary_strings: false,
+            compression_fallback: CompressionFallback::default(),
+            abort_checker: None,
+        })
+    }
+
+    /// Read the blocks whose codec has no parquet counterpart with the fallback
+    /// instead of failing.
+    pub fn with_compression_fallback(mut self, fallback: CompressionFallback) -> Self {
+        self.compression_fallback = fallback;
+        self
+    }
+
+    /// Decode the top level string columns whose chunks are dictionary encoded as dictionary
+    /// arrays instead of materializing the strings, which saves memory for low cardinality
+    /// columns. The chunks of plain pages, such as the ones written by fuse, are decoded as usual.
+    pub fn with_dictionary_strings(mut self, enabled: bool) -> Self {
+        self.dictionary_strings = enabled;
+        self
+    }
+
+    /// Stop decoding the blocks once the query is aborted, the checker is consulted before
+    /// each block and each page, and its error is returned.
+    pub fn with_abort_checker(mut self, abort_checker: AbortChecker) -> Self {
+        self.abort_checker = Some(abort_checker);
+        self
+    }
+
+    /// Same as [`column_chunks_to_record_batch`], with the schemas built before.
+    pub fn to_record_batch(
+        &self,
+        num_rows: usize,
+        column_chunks: &HashMap<ColumnId, DataItem>,
+        compression: &Compression,
+    ) -> databend_common_exception::Result<RecordBatch> {
+        match self.dictionary_schema(column_chunks) {
+            Some(schema) => {
+                self.deserialize_record_batch(&schema, num_rows, column_chunks, compression)
             }
-            DataItem::ColumnArray(_) => {}
-        }
-    }
-    let row_group = Box::new(builder.build());
-    let field_levels = parquet_to_arrow_field_levels(
-        &parquet_schema,
-        ProjectionMask::leaves(&parquet_schema, projection_mask),
-        Some(arrow_schema.fields()),
-    )?;
-    let mut record_reader = ParquetRecordBatchReader::try_new_with_row_groups(
-        &field_levels,
-        row_group.as_ref(),
-        num_rows,
-        None,
-    )?;
-    let record = record_reader.next().unwrap()?;
-    assert!(record_reader.next().is_none());
-    Ok(record)
+            None => self.deserialize_record_batch(
+                &self.arrow_schema,
+                num_rows,
+                column_chunks,
+                compression,
+            ),
+        }
+    }
+
+    // The schema which decodes the string fields of the dictionary encoded chunks as
+    // dictionary arrays, none if there is no such field.
+    fn dictionary_schema(&self, column_chunks: &HashMap<ColumnId, DataItem>) -> Option<Schema> {
+        if !self.dictionary_strings {
+            return None;
+        }
+        let mut fields = self.arrow_schema.fields().to_vec();
+        let mut found = false;
+        for (i, column_id) in &self.string_fields {
+            if let Some(DataItem::RawData(bytes)) = column_chunks.get(column_id) {
+                if is_dictionary_encoded(bytes) {
+                    let data_type = ArrowDataType::Dictionary(
+                        Box::new(ArrowDataType::Int32),
+                        Box::new(ArrowDataType::Utf8),
+                    );
+                    fields[*i] = Arc::new(fields[*i].as_ref().clone().with_data_type(data_type));
+                    found = true;
+                }
+            }
+        }
+        found.then(|| Schema::new_with_metadata(fields, self.arrow_schema.metadata().clone()))
+    }
+
+    /// Decode only the definition levels of the chunk of a top level column, the values of the
+    /// pages are skipped. Returns the validity of the rows of the chunk.
+    pub fn to_validity(
+        &self,
+        column_id: ColumnId,
+        num_rows: usize,
+        chunk: Bytes,
+        compression: &Compression,
+    ) -> databend_common_exception::Result<Bitmap> {
+        let dfs_id = *self.column_id_to_dfs_id.get(&column_id).ok_or_else(|| {
+            ErrorCode::BadArguments(format!("column {} is not in the schema", column_id))
+        })?;
+        let column_descr = self.parquet_schema.column(dfs_id);
+        let mut builder = self.row_group_builder(num_rows, compression)?;
+        builder.add_column_chunk(dfs_id, chunk);
+        let row_group = builder.build();
+        let page_reader = match row_group.column_chunks(dfs_id)?.next() {
+            Some(page_reader) => page_reader?,
+            None => return Ok(Bitmap::new_constant(true, num_rows)),
+        };
+
+        let mut reader = LevelsReader::new(column_descr.clone(), page_reader);
+        let mut def_levels = Vec::with_capacity(num_rows);
+        let mut num_records = 0;
+        while num_records < num_rows {
+            let (records, _, _) = reader
+                .read_records(num_rows - num_records, Some(&mut def_levels), None, &mut ())
+                .map_err(|err| self.abort_error().unwrap_or_else(|| err.into()))?;
+            if records == 0 {
+                break;
+            }
+            num_records += records;
+        }
+        if num_records != num_rows {
+            return Err(ErrorCode::StorageOther(format!(
+                "{} levels are decoded from the chunk of column {}, expect {} rows",
+                num_records, column_id, num_rows
+            )));
+        }
+
+        let max_def_level = column_descr.max_def_level();
+        if max_def_level == 0 {
+            return Ok(Bitmap::new_constant(true, num_rows));
+        }
+        Ok(def_levels
+            .iter()
+            .map(|level| *level == max_def_level)
+            .collect())
+    }
+
+    fn row_group_builder(
+        &self,
+        num_rows: usize,
+        compression: &Compression,
+    ) -> databend_common_exception::Result<RowGroupImplBuilder<'_>> {
+        let compression = to_parquet_compression(*compression, &self.compression_fallback)?;
+        let mut builder = RowGroupImplBuilder::new(num_rows, &self.parquet_schema, compression);
+        if let Some(abort_checker) = &self.abort_checker {
+            abort_checker.try_check_aborting()?;
+            let abort_checker = abort_checker.clone();
+            builder = builder.with_abort_check(Arc::new(move || {
+                abort_checker.try_check_aborting().is_err()
+            }));
+        }
+        Ok(builder)
+    }
+
+    fn deserialize_record_batch(
+        &self,
+        output_schema: &Schema,
+        num_rows: usize,
+        column_chunks: &HashMap<ColumnId, DataItem>,
+        compression: &Compression,
+    ) -> databend_common_exception::Result<RecordBatch> {
+        let parquet_schema = &self.parquet_schema;
+        let mut projection_mask = Vec::with_capacity(column_chunks.len());
+        let mut builder = self.row_group_builder(num_rows, compression)?;
+        for (column_id, data_item) in column_chunks.iter() {
+            match data_item {
+                DataItem::RawData(bytes) => {
+                    let dfs_id = self.column_id_to_dfs_id.get(column_id).cloned().unwrap();
+                    projection_mask.push(dfs_id);
+                    builder.add_column_chunk(dfs_id, bytes.clone());
+                }
+                DataItem::ColumnArray(_) => {}
+            }
+        }
+        let row_group = Box::new(builder.build());
+        let field_levels = parquet_to_arrow_field_levels(
+            parquet_schema,
+            ProjectionMask::leaves(parquet_schema, projection_mask),
+            Some(output_schema.fields()),
+        )?;
+        let mut record_reader = ParquetRecordBatchReader::try_new_with_row_groups(
+            &field_levels,
+            row_group.as_ref(),
+            num_rows,
+            None,
+        )
+        .map_err(|err| self.abort_error().unwrap_or_else(|| err.into()))?;
+        let record = match record_reader.next() {
+            Some(record) => {
+                record.map_err(|err| self.abort_error().unwrap_or_else(|| err.into()))?
+            }
+            // No batch is yielded for a block without rows, the columns are empty arrays.
+            None if num_rows == 0 => RecordBatch::new_empty(record_reader.schema()),
+            None => {
+                return Err(ErrorCode::StorageOther(format!(
+                    "no record batch is decoded from the column chunks of {} rows",
+                    num_rows
+                )));
+            }
+        };
+        assert!(record_reader.next().is_none());
+        Ok(record)
+    }
+
+    // The error of the aborted query, which replaces the decode error caused by the abort.
+    fn abort_error(&self) -> Option<ErrorCode> {
+        self.abort_checker
+            .as_ref()
+            .and_then(|abort_checker| abort_checker.try_check_aborting().err())
+    }
+}
+
+// The column reader which decodes the levels of the pages only.
+type LevelsReader =
+    GenericColumnReader<RepetitionLevelDecoderImpl, DefinitionLevelDecoderImpl, SkippedValues>;
+
+// Skips the values of the pages, the reader is told all the values are read.
+struct SkippedValues;
+
+impl ColumnValueDecoder for SkippedValues {
+    type Buffer = ();
+
+    fn new(_col: &ColumnDescPtr) -> Self {
+        SkippedValues
+    }
+
+    fn set_dict(
+        &mut self,
+        _buf: Bytes,
+        _num_values: u32,
+        _encoding: Encoding,
+        _is_sorted: bool,
+    ) -> ParquetResult<()> {
+        Ok(())
+    }
+
+    fn set_data(
+        &mut self,
+        _encoding: Encoding,
+        _data: Bytes,
+        _num_levels: usize,
+        _num_values: Option<usize>,
+    ) -> ParquetResult<()> {
+        Ok(())
+    }
+
+    fn read(&mut self, _out: &mut (), num_values: usize) -> ParquetResult<usize> {
+        Ok(num_values)
+    }
+
+    fn skip_values(&mut self, num_values: usize) -> ParquetResult<usize> {
+        Ok(num_values)
+    }
+}
+
+// A dictionary encoded chunk starts with its dictionary page.
+fn is_dictionary_encoded(chunk: &[u8]) -> bool {
+    let mut remaining = chunk;
+    let mut prot = TCompactInputProtocol::new(&mut remaining);
+    PageHeader::read_from_in_protocol(&mut prot)
+        .is_ok_and(|header| header.type_ == PageType::DICTIONARY_PAGE)
+}
+
+/// Remove the chunks which do not belong to the leaf columns of `column_nodes`.
+///
+/// The chunks of a block may be fetched for more columns than projected, e.g. all the leaves of a
+/// tuple while only one of its inner fields is projected, they should not be decoded.
+pub fn prune_column_chunks(
+    column_chunks: &mut HashMap<ColumnId, DataItem>,
+    column_nodes: &[ColumnNode],
+) {
+    let leaf_column_ids = column_nodes
+        .iter()
+        .flat_map(|column_node| column_node.leaf_column_ids.iter().cloned())
+        .collect::<HashSet<_>>();
+    column_chunks.retain(|column_id, _| leaf_column_ids.contains(column_id));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::collections::HashMap;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use arrow_array::Array;
+    use arrow_array::StructArray;
+    use arrow_schema::DataType as ArrowDataType;
+    use arrow_schema::Schema;
+    use bytes::Bytes;
+    use databend_common_catalog::plan::Projection;
+    use databend_common_catalog::table_context::AbortChecker;
+    use databend_common_catalog::table_context::CheckAbort;
+    use databend_common_exception::ErrorCode;
+    use databend_common_exception::Result;
+    use databend_common_expression::types::number::Int32Type;
+    use databend_common_expression::types::DataType;
+    use databend_common_expression::types::NumberDataType;
+    use databend_common_expression::types::StringType;
+    use databend_common_expression::Column;
+    use databend_common_expression::ColumnBuilder;
+    use databend_common_expression::ColumnId;
+    use databend_common_expression::DataBlock;
+    use databend_common_expression::FromData;
+    use databend_common_expression::ScalarRef;
+    use databend_common_expression::TableDataType;
+    use databend_common_expression::TableField;
+    use databend_common_expression::TableSchemaRef;
+    use databend_common_expression::TableSchemaRefExt;
+    use databend_common_storage::ColumnNodes;
+    use databend_storages_common_table_meta::meta::Compression;
+    use databend_storages_common_table_meta::table::CompressionFallback;
+    use parquet::arrow::arrow_to_parquet_schema;
+    use parquet::arrow::arrow_writer::get_column_writers;
+    use parquet::arrow::ArrowWriter;
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+
+    use super::column_chunks_to_record_batch;
+    use super::prune_column_chunks;
+    use super::ParquetDecodeSchema;
+    use crate::io::read::block::block_reader_merge_io::DataItem;
+    use crate::operations::column_parquet_metas;
+
+    fn write_column_chunks(
+        schema: &TableSchemaRef,
+        block: &DataBlock,
+    ) -> HashMap<ColumnId, DataItem<'static>> {
+        let mut buf = vec![];
+        let arrow_schema = Arc::new(Schema::from(schema.as_ref()));
+        let mut writer = ArrowWriter::try_new(&mut buf, arrow_schema, None).unwrap();
+        writer
+            .write(&block.to_record_batch(schema).unwrap())
+            .unwrap();
+        let file_meta = writer.close().unwrap();
+
+        let data = Bytes::from(buf);
+        column_parquet_metas(&file_meta, schema)
+            .unwrap()
+            .into_iter()
+            .map(|(column_id, meta)| {
+                let (offset, len) = meta.offset_length();
+                let range = offset as usize..(offset + len) as usize;
+                (column_id, DataItem::RawData(data.slice(range)))
+            })
+            .collect()
+    }
+
+    // `ArrowWriter` skips the empty batches, write the chunks of a row group without rows by the column writers.
+    fn write_empty_column_chunks(schema: &TableSchemaRef) -> HashMap<ColumnId, DataItem<'static>> {
+        let mut buf = vec![];
+        let arrow_schema = Arc::new(Schema::from(schema.as_ref()));
+        let parquet_schema = arrow_to_parquet_schema(&arrow_schema).unwrap();
+        let props = Arc::new(WriterProperties::default());
+        let mut writer =
+            SerializedFileWriter::new(&mut buf, parquet_schema.root_schema_ptr(), props.clone())
+                .unwrap();
+        let mut row_group = writer.next_row_group().unwrap();
+        for column_writer in get_column_writers(&parquet_schema, &props, &arrow_schema).unwrap() {
+            let chunk = column_writer.close().unwrap();
+            chunk.append_to_row_group(&mut row_group).unwrap();
+        }
+        row_group.close().unwrap();
+        let file_meta = writer.close().unwrap();
+
+        let data = Bytes::from(buf);
+        column_parquet_metas(&file_meta, schema)
+            .unwrap()
+            .into_iter()
+            .map(|(column_id, meta)| {
+                let (offset, len) = meta.offset_length();
+                let range = offset as usize..(offset + len) as usize;
+                (column_id, DataItem::RawData(data.slice(range)))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_zero_rows_record_batch() {
+        let schema = TableSchemaRefExt::create(vec![
+            TableField::new("a", TableDataType::Number(NumberDataType::Int32)),
+            TableField::new(
+                "b",
+                TableDataType::Nullable(Box::new(TableDataType::String)),
+            ),
+            TableField::new(
+                "c",
+                TableDataType::Array(Box::new(TableDataType::Number(NumberDataType::Int32))),
+            ),
+            TableField::new("d", TableDataType::Tuple {
+                fields_name: vec!["d1".to_string(), "d2".to_string()],
+                fields_type: vec![
+                    TableDataType::Number(NumberDataType::Int32),
+                    TableDataType::String,
+                ],
+            }),
+        ]);
+        let column_chunks = write_empty_column_chunks(&schema);
+        assert_eq!(column_chunks.len(), 5);
+
+        let record_batch =
+            column_chunks_to_record_batch(&schema, 0, &column_chunks, &Compression::None).unwrap();
+        assert_eq!(record_batch.num_rows(), 0);
+        assert_eq!(record_batch.num_columns(), schema.num_fields());
+        // each projected column is an empty array of its type
+        for field in schema.fields() {
+            let array = record_batch.column_by_name(field.name()).unwrap().clone();
+            assert_eq!(array.len(), 0);
+            let data_type = DataType::from(field.data_type());
+            let column = Column::from_arrow_rs(array, &data_type).unwrap();
+            assert_eq!(column.len(), 0);
+            assert_eq!(column.data_type(), data_type);
+        }
+
+        let record_batch = ParquetDecodeSchema::try_create(&schema)
+            .unwrap()
+            .with_dictionary_strings(true)
+            .to_record_batch(0, &column_chunks, &Compression::None)
+            .unwrap();
+        assert_eq!(record_batch.num_rows(), 0);
+        assert_eq!(record_batch.num_columns(), schema.num_fields());
+    }
+
+    #[test]
+    fn test_dictionary_record_batch() {
+        let schema = TableSchemaRefExt::create(vec![TableField::new("s", TableDataType::String)]);
+        let values = (0..1000)
+            .map(|i| format!("value_{}", i % 7))
+            .collect::<Vec<_>>();
+        let column = StringType::from_data(values);
+        let block = DataBlock::new_from_columns(vec![column.clone()]);
+        let decode_schema = ParquetDecodeSchema::try_create(&schema)
+            .unwrap()
+            .with_dictionary_strings(true);
+
+        for dictionary_enabled in [true, false] {
+            let props = WriterProperties::builder()
+                .set_max_row_group_size(usize::MAX)
+                .set_dictionary_enabled(dictionary_enabled)
+                .build();
+            let mut buf = vec![];
+            let arrow_schema = Arc::new(Schema::from(schema.as_ref()));
+            let mut writer = ArrowWriter::try_new(&mut buf, arrow_schema, Some(props)).unwrap();
+            writer
+                .write(&block.to_record_batch(&schema).unwrap())
+                .unwrap();
+            let file_meta = writer.close().unwrap();
+            let chunk_meta = file_meta.row_groups[0].columns[0].meta_data.as_ref();
+            let dictionary_page_offset = chunk_meta.unwrap().dictionary_page_offset;
+            assert_eq!(dictionary_page_offset.is_some(), dictionary_enabled);
+
+            let data = Bytes::from(buf);
+            let column_chunks = column_parquet_metas(&file_meta, &schema)
+                .unwrap()
+                .into_iter()
+                .map(|(column_id, meta)| {
+                    let (offset, len) = meta.offset_length();
+                    let range = offset as usize..(offset + len) as usize;
+                    (column_id, DataItem::RawData(data.slice(range)))
+                })
+                .collect::<HashMap<_, _>>();
+
+            // only the dictionary encoded chunk is decoded as a dictionary array
+            let record_batch = decode_schema
+                .to_record_batch(1000, &column_chunks, &Compression::None)
+                .unwrap();
+            let array = record_batch.column(0).clone();
+            assert_eq!(
+                matches!(array.data_type(), ArrowDataType::Dictionary(_, _)),
+                dictionary_enabled
+            );
+            assert_eq!(
+                Column::from_arrow_rs(array, &DataType::String).unwrap(),
+                column
+            );
+
+            // the default read path materializes the strings
+            let record_batch =
+                column_chunks_to_record_batch(&schema, 1000, &column_chunks, &Compression::None)
+                    .unwrap();
+            assert!(!matches!(
+                record_batch.column(0).data_type(),
+                ArrowDataType::Dictionary(_, _)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validity_of_levels() {
+        let schema = TableSchemaRefExt::create(vec![
+            TableField::new(
+                "a",
+                TableDataType::Nullable(Box::new(TableDataType::Number(NumberDataType::Int32))),
+            ),
+            TableField::new("b", TableDataType::String),
+        ]);
+        let values = (0..1000)
+            .map(|i| if i % 3 == 0 { None } else { Some(i) })
+            .collect::<Vec<_>>();
+        let a = Int32Type::from_opt_data(values.clone());
+        let b = StringType::from_data((0..1000).map(|i| i.to_string()).collect::<Vec<_>>());
+        let block = DataBlock::new_from_columns(vec![a, b]);
+
+        // the levels span many pages
+        let props = WriterProperties::builder()
+            .set_data_page_row_count_limit(64)
+            .set_write_batch_size(64)
+            .build();
+        let mut buf = vec![];
+        let arrow_schema = Arc::new(Schema::from(schema.as_ref()));
+        let mut writer = ArrowWriter::try_new(&mut buf, arrow_schema, Some(props)).unwrap();
+        writer
+            .write(&block.to_record_batch(&schema).unwrap())
+            .unwrap();
+        let file_meta = writer.close().unwrap();
+        let data = Bytes::from(buf);
+        let column_chunks = column_parquet_metas(&file_meta, &schema)
+            .unwrap()
+            .into_iter()
+            .map(|(column_id, meta)| {
+                let (offset, len) = meta.offset_length();
+                let range = offset as usize..(offset + len) as usize;
+                (column_id, data.slice(range))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let decode_schema = ParquetDecodeSchema::try_create(&schema).unwrap();
+        let a_id = schema.field(0).column_id;
+        let validity = decode_schema
+            .to_validity(a_id, 1000, column_chunks[&a_id].clone(), &Compression::None)
+            .unwrap();
+        let expected = values.iter().map(|v| v.is_some()).collect::<Vec<_>>();
+        assert_eq!(validity.iter().collect::<Vec<_>>(), expected);
+        assert_eq!(validity.null_count(), 334);
+
+        // a required column is all valid
+        let b_id = schema.field(1).column_id;
+        let validity = decode_schema
+            .to_validity(b_id, 1000, column_chunks[&b_id].clone(), &Compression::None)
+            .unwrap();
+        assert_eq!(validity.null_count(), 0);
+
+        // the chunk of fewer rows than expected
+        let err = decode_schema
+            .to_validity(a_id, 1001, column_chunks[&a_id].clone(), &Compression::None)
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::STORAGE_OTHER);
+    }
+
+    #[test]
+    fn test_compression_fallback_record_batch() {
+        let schema = TableSchemaRefExt::create(vec![TableField::new(
+            "a",
+            TableDataType::Number(NumberDataType::Int32),
+        )]);
+        let column = Int32Type::from_data(vec![1, 2, 3]);
+        let block = DataBlock::new_from_columns(vec![column.clone()]);
+        // the chunks are written uncompressed
+        let column_chunks = write_column_chunks(&schema, &block);
+
+        // the deprecated lz4 has no parquet counterpart
+        let decode_schema = ParquetDecodeSchema::try_create(&schema).unwrap();
+        assert!(decode_schema
+            .to_record_batch(3, &column_chunks, &Compression::Lz4)
+            .is_err());
+
+        let decode_schema = ParquetDecodeSchema::try_create(&schema)
+            .unwrap()
+            .with_compression_fallback(CompressionFallback::Uncompressed);
+        let record_batch = decode_schema
+            .to_record_batch(3, &column_chunks, &Compression::Lz4)
+            .unwrap();
+        let data_type = DataType::Number(NumberDataType::Int32);
+        assert_eq!(
+            Column::from_arrow_rs(record_batch.column(0).clone(), &data_type).unwrap(),
+            column
+        );
+    }
+
+    // Aborts after `limit` successful checks.
+    struct CountingAbortChecker {
+        calls: AtomicUsize,
+        limit: usize,
+    }
+
+    impl CountingAbortChecker {
+        fn create(limit: usize) -> Arc<Self> {
+            Arc::new(Self {
+                calls: AtomicUsize::new(0),
+                limit,
+            })
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    impl CheckAbort for CountingAbortChecker {
+        fn try_check_aborting(&self) -> Result<()> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) >= self.limit {
+                return Err(ErrorCode::AbortedQuery("aborted by the test"));
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_abort_record_batch() {
+        let schema = TableSchemaRefExt::create(vec![
+            TableField::new("a", TableDataType::Number(NumberDataType::Int32)),
+            TableField::new("b", TableDataType::String),
+        ]);
+        let block = DataBlock::new_from_columns(vec![
+            Int32Type::from_data(vec![1, 2, 3]),
+            StringType::from_data(vec!["x", "y", "z"]),
+        ]);
+        let column_chunks = write_column_chunks(&schema, &block);
+        let decode = |column_chunks: &HashMap<ColumnId, DataItem>, checker: AbortChecker| {
+            ParquetDecodeSchema::try_create(&schema)
+                .unwrap()
+                .with_abort_checker(checker)
+                .to_record_batch(3, column_chunks, &Compression::None)
+        };
+
+        // the checks of decoding both columns, and only the first column
+        let checker = CountingAbortChecker::create(usize::MAX);
+        let record_batch = decode(&column_chunks, checker.clone()).unwrap();
+        assert_eq!(record_batch.num_rows(), 3);
+        let total_checks = checker.calls();
+        let first_column_chunks = write_column_chunks(&schema, &block)
+            .into_iter()
+            .filter(|(column_id, _)| *column_id == schema.field(0).column_id)
+            .collect::<HashMap<_, _>>();
+        let checker = CountingAbortChecker::create(usize::MAX);
+        decode(&first_column_chunks, checker.clone()).unwrap();
+        let first_column_checks = checker.calls();
+        assert!(first_column_checks < total_checks);
+
+        // aborted after the first column, the rest is not decoded
+        let checker = CountingAbortChecker::create(first_column_checks);
+        let err = decode(&column_chunks, checker).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::ABORTED_QUERY);
+
+        // aborted before the decode
+        let checker = CountingAbortChecker::create(0);
+        let err = decode(&column_chunks, checker.clone()).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::ABORTED_QUERY);
+        assert_eq!(checker.calls(), 1);
+    }
+
+    #[test]
+    fn test_list_of_struct_record_batch() {
+        // ARRAY(TUPLE(INT, STRING NULL)) NULL
+        let tuple_type = TableDataType::Tuple {
+            fields_name: vec!["a".to_string(), "b".to_string()],
+            fields_type: vec![
+                TableDataType::Number(NumberDataType::Int32),
+                TableDataType::Nullable(Box::new(TableDataType::String)),
+            ],
+        };
+        let table_type =
+            TableDataType::Nullable(Box::new(TableDataType::Array(Box::new(tuple_type))));
+        let schema = TableSchemaRefExt::create(vec![TableField::new("c", table_type.clone())]);
+        let data_type = DataType::from(&table_type);
+
+        let tuples = |rows: &[(i32, Option<&str>)]| {
+            Column::Tuple(vec![
+                Int32Type::from_data(rows.iter().map(|(a, _)| *a).collect::<Vec<_>>()),
+                StringType::from_opt_data(rows.iter().map(|(_, b)| *b).collect::<Vec<_>>()),
+            ])
+        };
+        let rows = [
+            Some(tuples(&[(1, Some("a")), (2, Some("bc"))])),
+            // empty inner array
+            Some(tuples(&[])),
+            None,
+            Some(tuples(&[(3, None), (4, Some(""))])),
+            Some(tuples(&[(5, Some("d"))])),
+        ];
+        let mut builder = ColumnBuilder::with_capacity(&data_type, rows.len());
+        for row in &rows {
+            match row {
+                Some(tuples) => builder.push(ScalarRef::Array(tuples.clone())),
+                None => builder.push(ScalarRef::Null),
+            }
+        }
+        let column = builder.build();
+        let block = DataBlock::new_from_columns(vec![column.clone()]);
+
+        let column_chunks = write_column_chunks(&schema, &block);
+        // one chunk for each leaf column of the tuple
+        assert_eq!(column_chunks.len(), 2);
+
+        let record_batch =
+            column_chunks_to_record_batch(&schema, rows.len(), &column_chunks, &Compression::None)
+                .unwrap();
+        let actual = Column::from_arrow_rs(record_batch.column(0).clone(), &data_type).unwrap();
+        assert_eq!(actual, column);
+        for (i, row) in rows.iter().enumerate() {
+            match (row, actual.index(i).unwrap()) {
+                (Some(tuples), ScalarRef::Array(values)) => assert_eq!(&values, tuples),
+                (None, ScalarRef::Null) => {}
+                (_, value) => panic!("unexpected value {:?} at row {}", value, i),
+            }
+        }
+    }
+
+    #[test]
+    fn test_prune_nested_leaves() {
+        // c TUPLE(c1 INT, c2 STRING, c3 TUPLE(d1 INT, d2 INT), c4 INT)
+        let inner_type = TableDataType::Tuple {
+            fields_name: vec!["d1".to_string(), "d2".to_string()],
+            fields_type: vec![
+                TableDataType::Number(NumberDataType::Int32),
+                TableDataType::Number(NumberDataType::Int32),
+            ],
+        };
+        let tuple_type = TableDataType::Tuple {
+            fields_name: vec![
+                "c1".to_string(),
+                "c2".to_string(),
+                "c3".to_string(),
+                "c4".to_string(),
+            ],
+            fields_type: vec![
+                TableDataType::Number(NumberDataType::Int32),
+                TableDataType::String,
+                inner_type,
+                TableDataType::Number(NumberDataType::Int32),
+            ],
+        };
+        let schema = TableSchemaRefExt::create(vec![
+            TableField::new("a", TableDataType::Number(NumberDataType::Int32)),
+            TableField::new("c", tuple_type),
+        ]);
+
+        let num_rows = 3;
+        let c2 = StringType::from_data(vec!["x", "y", "z"]);
+        let block = DataBlock::new_from_columns(vec![
+            Int32Type::from_data(vec![1, 2, 3]),
+            Column::Tuple(vec![
+                Int32Type::from_data(vec![4, 5, 6]),
+                c2.clone(),
+                Column::Tuple(vec![
+                    Int32Type::from_data(vec![7, 8, 9]),
+                    Int32Type::from_data(vec![10, 11, 12]),
+                ]),
+                Int32Type::from_data(vec![13, 14, 15]),
+            ]),
+        ]);
+        let mut column_chunks = write_column_chunks(&schema, &block);
+        // all the leaves of the block are fetched
+        assert_eq!(column_chunks.len(), 6);
+
+        // project `c.c2` only
+        let projection = Projection::InnerColumns(BTreeMap::from([(1, vec![1, 1])]));
+        let arrow_schema = Schema::from(schema.as_ref());
+        let column_nodes = ColumnNodes::new_from_schema(&arrow_schema, Some(&schema));
+        let project_column_nodes = projection
+            .project_column_nodes(&column_nodes)
+            .unwrap()
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        prune_column_chunks(&mut column_chunks, &project_column_nodes);
+        assert_eq!(column_chunks.len(), 1);
+        assert!(column_chunks.contains_key(&project_column_nodes[0].leaf_column_ids[0]));
+
+        let record_batch =
+            column_chunks_to_record_batch(&schema, num_rows, &column_chunks, &Compression::None)
+                .unwrap();
+        // only the requested leaf is decoded
+        assert_eq!(record_batch.num_columns(), 1);
+        let array = record_batch.column_by_name("c").unwrap();
+        let struct_array = array.as_any().downcast_ref::<StructArray>().unwrap();
+        assert_eq!(struct_array.num_columns(), 1);
+        let actual = struct_array.column_by_name("c2").unwrap().clone();
+        assert_eq!(actual.len(), num_rows);
+        assert_eq!(
+            Column::from_arrow_rs(actual, &DataType::String).unwrap(),
+            c2
+        );
+    }
 }
@@ -13,6 +13,8 @@
 // limitations under the License.
 
 use std::collections::HashMap;
+use std::time::Duration;
+use std::time::Instant;
 
 use arrow_array::ArrayRef;
 use arrow_array::RecordBatch;
@@ -26,18 +28,30 @@ use databend_common_expression::DataBlock;
 use databend_common_expression::TableDataType;
 use databend_common_expression::TableSchema;
 use databend_common_expression::Value;
-use databend_storages_common_cache::CacheAccessor;
+use databend_common_metrics::storage::metrics_inc_remote_io_deserialized_bytes;
 use databend_storages_common_cache::CacheManager;
 use databend_storages_common_cache::TableDataCacheKey;
+use databend_storages_common_table_meta::meta::BlockMeta;
 use databend_storages_common_table_meta::meta::ColumnMeta;
 use databend_storages_common_table_meta::meta::Compression;
 
 mod adapter;
+mod checksum;
+mod contiguous;
 mod deserialize;
+mod staged;
 
+pub use adapter::AbortCheck;
 pub use adapter::RowGroupImplBuilder;
+pub use checksum::validate_page_checksums;
+pub use contiguous::ContiguousBuffer;
+pub use contiguous::ContiguousColumn;
 pub use deserialize::column_chunks_to_record_batch;
+pub use deserialize::prune_column_chunks;
+pub use deserialize::ParquetDecodeSchema;
+pub use staged::StagedDeserializeState;
 
+use crate::io::read::block::block_reader_deserialize::array_buffer_size;
 use crate::io::read::block::block_reader_merge_io::DataItem;
 use crate::io::BlockReader;
 
@@ -50,19 +64,145 @@ impl BlockReader {
         compression: &Compression,
         block_path: &str,
     ) -> databend_common_exception::Result<DataBlock> {
+        let decode_schema = self.parquet_decode_schema()?;
+        let name_paths = column_name_paths(&self.projection, &self.original_schema);
+        self.deserialize_parquet_chunks_with_schema(
+            &decode_schema,
+            &name_paths,
+            num_rows,
+            column_metas,
+            column_chunks,
+            compression,
+            block_path,
+        )
+    }
+
+    /// Deserialize the parquet column chunks of many blocks of the table, `chunks_per_block`
+    /// are the column chunks of the blocks of `metas` in the same order.
+    ///
+    /// The schemas to decode the chunks are built once and shared by all the blocks,
+    /// instead of being built for each block by `deserialize_parquet_chunks`.
+    pub fn deserialize_many(
+        &self,
+        metas: &[BlockMeta],
+        chunks_per_block: Vec<HashMap<ColumnId, DataItem>>,
+    ) -> databend_common_exception::Result<Vec<DataBlock>> {
+        if metas.len() != chunks_per_block.len() {
+            return Err(ErrorCode::Internal(format!(
+                "the column chunks of {} blocks are given for {} block metas",
+                chunks_per_block.len(),
+                metas.len()
+            )));
+        }
+        let decode_schema = self.parquet_decode_schema()?;
+        let name_paths = column_name_paths(&self.projection, &self.original_schema);
+        metas
+            .iter()
+            .zip(chunks_per_block)
+            .map(|(meta, column_chunks)| {
+                self.deserialize_parquet_chunks_with_schema(
+                    &decode_schema,
+                    &name_paths,
+                    meta.row_count as usize,
+                    &meta.col_metas,
+                    column_chunks,
+                    &meta.compression,
+                    &meta.location.0,
+                )
+            })
+            .collect()
+    }
+
+    /// Same as `deserialize_parquet_chunks`, but the fields are deserialized one by one to
+    /// report the decode duration of each field read from storage, keyed by the column id.
+    ///
+    /// The fields hit the array cache or filled with default values are not reported.
+    pub fn deserialize_parquet_chunks_with_timings(
+        &self,
+        num_rows: usize,
+        column_metas: &HashMap<ColumnId, ColumnMeta>,
+        column_chunks: &HashMap<ColumnId, DataItem>,
+        compression: &Compression,
+        block_path: &str,
+    ) -> databend_common_exception::Result<(DataBlock, HashMap<ColumnId, Duration>)> {
+        let decode_schema = self.parquet_decode_schema()?;
+        let name_paths = column_name_paths(&self.projection, &self.original_schema);
+
+        let mut columns = Vec::with_capacity(self.projected_schema.fields.len());
+        let mut timings = HashMap::new();
+        for (i, column_node) in self.project_column_nodes.iter().enumerate() {
+            let mut field_chunks = HashMap::new();
+            for column_id in &column_node.leaf_column_ids {
+                match column_chunks.get(column_id) {
+                    Some(DataItem::RawData(data)) => {
+                        field_chunks.insert(*column_id, DataItem::RawData(data.clone()));
+                    }
+                    Some(DataItem::ColumnArray(cached)) => {
+                        field_chunks.insert(*column_id, DataItem::ColumnArray(*cached));
+                    }
+                    None if !column_metas.contains_key(column_id) => {
+                        self.check_missing_column(*column_id, column_metas)?
+                    }
+                    None => {}
+                }
+            }
+
+            // the other fields of the block are filled with their default values
+            let column_id = self.projected_schema.fields[i].column_id;
+            let from_storage = matches!(field_chunks.get(&column_id), Some(DataItem::RawData(_)));
+            let start = Instant::now();
+            let block = self.deserialize_parquet_chunks_with_schema(
+                &decode_schema,
+                &name_paths,
+                num_rows,
+                column_metas,
+                field_chunks,
+                compression,
+                block_path,
+            )?;
+            if from_storage {
+                timings.insert(column_id, start.elapsed());
+            }
+            columns.push(block.get_by_offset(i).clone());
+        }
+        Ok((DataBlock::new(columns, num_rows), timings))
+    }
+
+    // The schemas to decode the parquet chunks of the blocks, the same for all the decode paths.
+    pub(crate) fn parquet_decode_schema(
+        &self,
+    ) -> databend_common_exception::Result<ParquetDecodeSchema> {
+        Ok(ParquetDecodeSchema::try_create(&self.original_schema)?
+            .with_dictionary_strings(self.dictionary_strings)
+            .with_abort_checker(self.ctx.clone().get_abort_checker()))
+    }
+
+    fn deserialize_parquet_chunks_with_schema(
+        &self,
+        decode_schema: &ParquetDecodeSchema,
+        name_paths: &[Vec<String>],
+        num_rows: usize,
+        column_metas: &HashMap<ColumnId, ColumnMeta>,
+        mut column_chunks: HashMap<ColumnId, DataItem>,
+        compression: &Compression,
+        block_path: &str,
+    ) -> databend_common_exception::Result<DataBlock> {
+        prune_column_chunks(&mut column_chunks, &self.project_column_nodes);
         if column_chunks.is_empty() {
             return self.build_default_values_block(num_rows);
         }
-        let record_batch = column_chunks_to_record_batch(
-            &self.original_schema,
-            num_rows,
-            &column_chunks,
-            compression,
-        )?;
+        if self.read_settings.enable_parquet_page_checksum {
+            for (column_id, data_item) in column_chunks.iter() {
+                if let DataItem::RawData(bytes) = data_item {
+                    validate_page_checksums(*column_id, bytes)?;
+                }
+            }
+        }
+        let record_batch = decode_schema.to_record_batch(num_rows, &column_chunks, compression)?;
         let mut columns = Vec::with_capacity(self.projected_schema.fields.len());
-        let name_paths = column_name_paths(&self.projection, &self.original_schema);
 
-        let array_cache = if self.put_cache {
+        let mut deserialized_bytes = 0;
+        let array_cache = if self.put_cache && self.read_settings.cache_policy.can_write() {
             CacheManager::instance().get_table_data_array_cache()
         } else {
             None
@@ -92,16 +232,23 @@ impl BlockReader {
             //  Yes, it is too obscure, we need to polish it later.
 
             let value = match column_chunks.get(&field.column_id) {
-                Some(DataItem::RawData(data)) => {
+                Some(DataItem::RawData(_)) => {
                     // get the deserialized arrow array, which may be a nested array
                     let arrow_array = column_by_name(&record_batch, &name_paths[i]);
-                    if !column_node.is_nested {
+                    let size = array_buffer_size(&arrow_array.to_data());
+                    deserialized_bytes += size;
+                    if !column_node.is_nested && self.read_settings.can_cache_array(size) {
                         if let Some(cache) = &array_cache {
                             let meta = column_metas.get(&field.column_id).unwrap();
                             let (offset, len) = meta.offset_length();
                             let key =
                                 TableDataCacheKey::new(block_path, field.column_id, offset, len);
-                            cache.insert(key.into(), (arrow_array.clone(), data.len()));
+                            let value = (arrow_array.clone(), size);
+                            if self.pinned_columns.contains(&field.column_id) {
+                                cache.insert_pinned_if_absent(key.into(), value);
+                            } else {
+                                cache.insert_if_absent(key.into(), value);
+                            }
                         }
                     }
                     Value::Column(Column::from_arrow_rs(arrow_array, &data_type)?)
@@ -115,10 +262,18 @@ impl BlockReader {
                     }
                     Value::Column(Column::from_arrow_rs(cached.0.clone(), &data_type)?)
                 }
-                None => Value::Scalar(self.default_vals[i].clone()),
+                None => {
+                    for column_id in &column_node.leaf_column_ids {
+                        if !column_metas.contains_key(column_id) {
+                            self.check_missing_column(*column_id, column_metas)?;
+                        }
+                    }
+                    Value::Scalar(self.default_vals[i].clone())
+                }
             };
             columns.push(BlockEntry::new(data_type, value));
         }
+        metrics_inc_remote_io_deserialized_bytes(deserialized_bytes as u64);
         Ok(DataBlock::new(columns, num_rows))
     }
 }
@@ -0,0 +1,199 @@
+// Copyright 2021 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use databend_common_exception::ErrorCode;
+use databend_common_exception::Result;
+use databend_common_expression::BlockEntry;
+use databend_common_expression::Column;
+use databend_common_expression::ColumnId;
+use databend_common_expression::DataBlock;
+use databend_common_expression::Value;
+use databend_common_metrics::storage::metrics_inc_remote_io_deserialized_bytes;
+use databend_storages_common_table_meta::meta::ColumnMeta;
+use databend_storages_common_table_meta::meta::Compression;
+
+use super::column_by_name;
+use super::column_name_paths;
+use super::validate_page_checksums;
+use super::ParquetDecodeSchema;
+use crate::io::read::block::block_reader_deserialize::array_buffer_size;
+use crate::io::read::block::block_reader_merge_io::DataItem;
+use crate::io::BlockReader;
+
+/// The progress of deserializing a parquet block in stages.
+///
+/// Each stage deserializes a group of the projected fields, the caller may spill the
+/// deserialized columns of a stage before running the next one, so that only one group
+/// of the decoded columns has to be kept in memory.
+pub struct StagedDeserializeState {
+    num_rows: usize,
+    // indexed by the position of the projected field.
+    finished: Vec<bool>,
+    // built once and shared by all the stages.
+    decode_schema: ParquetDecodeSchema,
+    name_paths: Vec<Vec<String>>,
+}
+
+impl StagedDeserializeState {
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.iter().all(|finished| *finished)
+    }
+
+    /// The positions of the projected fields which are not deserialized yet.
+    pub fn pending_fields(&self) -> Vec<usize> {
+        self.finished
+            .iter()
+            .enumerate()
+            .filter(|(_, finished)| !**finished)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+impl BlockReader {
+    pub fn create_staged_deserialize_state(
+        &self,
+        num_rows: usize,
+    ) -> Result<StagedDeserializeState> {
+        let finished = vec![false; self.projected_schema.fields.len()];
+        Ok(StagedDeserializeState {
+            num_rows,
+            finished,
+            decode_schema: self.parquet_decode_schema()?,
+            name_paths: column_name_paths(&self.projection, &self.original_schema),
+        })
+    }
+
+    /// Deserialize at most `max_fields` of the pending fields of the block.
+    ///
+    /// Only the chunks of these fields are decoded, and the decoded arrays are not put into the
+    /// array cache. Returns the deserialized columns along with the positions of their fields.
+    ///
+    /// `column_metas` are the column metas of the block, to check the missing columns of the
+    /// projection in strict mode, same as `deserialize_parquet_chunks`.
+    pub fn deserialize_parquet_stage(
+        &self,
+        state: &mut StagedDeserializeState,
+        column_metas: &HashMap<ColumnId, ColumnMeta>,
+        column_chunks: &HashMap<ColumnId, DataItem>,
+        compression: &Compression,
+        max_fields: usize,
+    ) -> Result<Vec<(usize, BlockEntry)>> {
+        let stage_fields = state
+            .pending_fields()
+            .into_iter()
+            .take(max_fields.max(1))
+            .collect::<Vec<_>>();
+
+        let mut stage_chunks = HashMap::new();
+        for i in &stage_fields {
+            let column_node = &self.project_column_nodes[*i];
+            for column_id in &column_node.leaf_column_ids {
+                if let Some(DataItem::RawData(data)) = column_chunks.get(column_id) {
+                    stage_chunks.insert(*column_id, DataItem::RawData(data.clone()));
+                }
+            }
+        }
+
+        if self.read_settings.enable_parquet_page_checksum {
+            for (column_id, data_item) in stage_chunks.iter() {
+                if let DataItem::RawData(bytes) = data_item {
+                    validate_page_checksums(*column_id, bytes)?;
+                }
+            }
+        }
+        let record_batch = if stage_chunks.is_empty() {
+            None
+        } else {
+            Some(
+                state
+                    .decode_schema
+                    .to_record_batch(state.num_rows, &stage_chunks, compression)?,
+            )
+        };
+
+        let mut deserialized_bytes = 0;
+        let mut entries = Vec::with_capacity(stage_fields.len());
+        for i in stage_fields {
+            let field = &self.projected_schema.fields[i];
+            let data_type = field.data_type().into();
+            // same as `deserialize_parquet_chunks`, the chunks of a nested field
+            // can be looked up by the column id of the field.
+            let value = match (column_chunks.get(&field.column_id), &record_batch) {
+                (Some(DataItem::RawData(_)), Some(record_batch)) => {
+                    let arrow_array = column_by_name(record_batch, &state.name_paths[i]);
+                    deserialized_bytes += array_buffer_size(&arrow_array.to_data());
+                    Value::Column(Column::from_arrow_rs(arrow_array, &data_type)?)
+                }
+                (Some(DataItem::ColumnArray(cached)), _) => {
+                    if self.project_column_nodes[i].is_nested {
+                        // a defensive check, should never happen
+                        return Err(ErrorCode::StorageOther(
+                            "unexpected nested field: nested leaf field hits cached",
+                        ));
+                    }
+                    Value::Column(Column::from_arrow_rs(cached.0.clone(), &data_type)?)
+                }
+                _ => {
+                    for column_id in &self.project_column_nodes[i].leaf_column_ids {
+                        if !column_metas.contains_key(column_id) {
+                            self.check_missing_column(*column_id, column_metas)?;
+                        }
+                    }
+                    Value::Scalar(self.default_vals[i].clone())
+                }
+            };
+            state.finished[i] = true;
+            entries.push((i, BlockEntry::new(data_type, value)));
+        }
+        metrics_inc_remote_io_deserialized_bytes(deserialized_bytes as u64);
+        Ok(entries)
+    }
+
+    /// Assemble the block from the columns of all the stages, which may be given in any order.
+    pub fn finish_staged_deserialize(
+        &self,
+        state: StagedDeserializeState,
+        mut columns: Vec<(usize, BlockEntry)>,
+    ) -> Result<DataBlock> {
+        if !state.is_finished() {
+            return Err(ErrorCode::Internal(format!(
+                "staged deserialization is not finished, pending fields: {:?}",
+                state.pending_fields()
+            )));
+        }
+        columns.sort_by_key(|(i, _)| *i);
+
+        let expected = self.projected_schema.fields.len();
+        if columns.len() != expected {
+            return Err(ErrorCode::Internal(format!(
+                "staged deserialization expects {} columns, but got {}",
+                expected,
+                columns.len()
+            )));
+        }
+
+        let columns = columns
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .collect::<Vec<_>>();
+        Ok(DataBlock::new(columns, state.num_rows))
+    }
+}
@@ -22,10 +22,16 @@ mod utils;
 mod virtual_column;
 
 pub use agg_index::AggIndexReader;
+pub use block::parquet::ContiguousBuffer;
+pub use block::parquet::ContiguousColumn;
+pub use block::parquet::ParquetDecodeSchema;
+pub use block::parquet::StagedDeserializeState;
 pub use block::BlockReadResult;
 pub use block::BlockReader;
+pub use block::DataItem;
 pub use block::NativeReaderExt;
 pub use block::NativeSourceData;
+pub use block::RawBlockColumns;
 pub use bloom::BloomBlockFilterReader;
 pub use inverted_index::InvertedIndexReader;
 pub use meta::CompactSegmentInfoReader;
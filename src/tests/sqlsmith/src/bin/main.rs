@@ -13,8 +13,13 @@
 // limitations under the License.
 
 use clap::Parser;
+use databend_common_exception::ErrorCode;
 use databend_common_exception::Result;
+use databend_sqlsmith::diff_configs;
+use databend_sqlsmith::ErrorClassifier;
+use databend_sqlsmith::GeneratorConfig;
 use databend_sqlsmith::Runner;
+use databend_sqlsmith::SeedCorpus;
 use tracing::metadata::LevelFilter;
 use tracing_subscriber::EnvFilter;
 
@@ -52,6 +57,49 @@ pub struct Args {
     /// The fuzz query test file path.
     #[clap(long, default_value = "")]
     fuzz_path: String,
+
+    /// Only generate calls of the given functions, separated by commas.
+    #[clap(long, value_delimiter = ',')]
+    functions: Vec<String>,
+
+    /// Inject random whitespaces and comments into the generated queries.
+    #[clap(long)]
+    inject_noise: bool,
+
+    /// Generate spatial aggregate functions over geometry expressions.
+    #[clap(long)]
+    spatial: bool,
+
+    /// Place string literals where numbers are expected and vice versa, to test the implicit casts.
+    #[clap(long)]
+    coercion: bool,
+
+    /// Generate the statements which are expected to fail, to test the error paths.
+    #[clap(long)]
+    error_cases: bool,
+
+    /// More error codes which are expected and not reported, separated by commas.
+    #[clap(long, value_delimiter = ',')]
+    expected_error_codes: Vec<u16>,
+
+    /// More error message prefixes which are expected and not reported.
+    #[clap(long)]
+    expected_errors: Vec<String>,
+
+    /// The corpus file of the seeds of the queries which found bugs,
+    /// they are replayed first and the new ones are appended to it.
+    #[clap(long)]
+    corpus: Option<String>,
+
+    /// The maximum length in bytes of the generated queries,
+    /// the longer ones are regenerated with less complexity.
+    #[clap(long)]
+    max_sql_bytes: Option<usize>,
+
+    /// Compare the functions generated with the two expression depths, separated by commas,
+    /// over the same `count` seeds instead of running the queries.
+    #[clap(long, value_delimiter = ',')]
+    diff_max_expr_depth: Vec<usize>,
 }
 
 #[tokio::main(flavor = "multi_thread", worker_threads = 5)]
@@ -63,6 +111,10 @@ async fn main() -> Result<()> {
     tracing_subscriber::fmt().with_env_filter(filter).init();
 
     let args = Args::parse();
+    if !args.diff_max_expr_depth.is_empty() {
+        return diff_max_expr_depth(&args);
+    }
+
     let host = format!("http://{}:{}", args.host, args.port);
     let mut runner = Runner::try_new(
         host,
@@ -72,8 +124,23 @@ async fn main() -> Result<()> {
         args.count,
         None,
         args.timeout,
+        args.functions.clone(),
+        args.inject_noise,
+        args.spatial,
     )
     .await?;
+    runner.set_coercion_mode(args.coercion);
+    runner.set_error_cases_mode(args.error_cases);
+    runner.set_error_classifier(ErrorClassifier::default().with_expected(
+        args.expected_error_codes.clone(),
+        args.expected_errors.clone(),
+    ));
+    if let Some(corpus) = &args.corpus {
+        runner.set_corpus(SeedCorpus::load(corpus)?);
+    }
+    if let Some(max_sql_bytes) = args.max_sql_bytes {
+        runner.set_max_sql_bytes(max_sql_bytes);
+    }
 
     if !args.fuzz_path.is_empty() {
         runner.run_fuzz(&args.fuzz_path).await?;
@@ -83,3 +150,33 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+// Generate the queries without a server and log the differences of their functions.
+fn diff_max_expr_depth(args: &Args) -> Result<()> {
+    let [left, right] = args.diff_max_expr_depth[..] else {
+        return Err(ErrorCode::BadArguments(
+            "--diff-max-expr-depth expects two depths",
+        ));
+    };
+    let config = GeneratorConfig {
+        functions: args.functions.clone(),
+        spatial: args.spatial,
+        coercion: args.coercion,
+        error_cases: args.error_cases,
+        max_sql_bytes: args.max_sql_bytes,
+        max_expr_depth: None,
+    };
+    let diff = diff_configs(
+        &GeneratorConfig {
+            max_expr_depth: Some(left),
+            ..config.clone()
+        },
+        &GeneratorConfig {
+            max_expr_depth: Some(right),
+            ..config
+        },
+        0..args.count as u64,
+    );
+    diff.report(20);
+    Ok(())
+}
@@ -0,0 +1,291 @@
+// Copyright 2021 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use databend_common_ast::ast::Identifier;
+use databend_common_ast::ast::Query;
+use databend_common_exception::Result;
+use databend_common_expression::types::DataType;
+use databend_common_expression::TableSchema;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::sql_gen::SqlGenerator;
+use crate::sql_gen::Table;
+
+/// The options of the generator which affect the generated statements.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct GeneratorConfig {
+    pub functions: Vec<String>,
+    pub spatial: bool,
+    pub coercion: bool,
+    /// Entries written before the error cases were added have no error cases.
+    #[serde(default)]
+    pub error_cases: bool,
+    /// Entries written before the budget was added have no budget.
+    #[serde(default)]
+    pub max_sql_bytes: Option<usize>,
+    /// The default depth of the generator if not set.
+    #[serde(default)]
+    pub max_expr_depth: Option<usize>,
+}
+
+/// The schema of a table which the query of an entry was generated over.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CorpusTable {
+    pub database: Option<String>,
+    pub name: String,
+    pub schema: TableSchema,
+}
+
+impl CorpusTable {
+    pub(crate) fn from_table(table: &Table) -> Self {
+        Self {
+            database: table.db_name.as_ref().map(|db| db.name.clone()),
+            name: table.name.name.clone(),
+            schema: table.schema.as_ref().clone(),
+        }
+    }
+
+    pub(crate) fn to_table(&self) -> Table {
+        Table::new(
+            self.database
+                .as_ref()
+                .map(|db| Identifier::from_name(None, db)),
+            Identifier::from_name(None, &self.name),
+            Arc::new(self.schema.clone()),
+        )
+    }
+
+    /// The statement to recreate the table, without the options and the default values.
+    pub(crate) fn create_sql(&self) -> String {
+        let columns = self
+            .schema
+            .fields()
+            .iter()
+            .map(|field| {
+                format!(
+                    "{} {}",
+                    field.name(),
+                    field.data_type().sql_name_explicit_null()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        match &self.database {
+            Some(database) => {
+                format!(
+                    "CREATE OR REPLACE TABLE {database}.{} ({columns})",
+                    self.name
+                )
+            }
+            None => format!("CREATE OR REPLACE TABLE {} ({columns})", self.name),
+        }
+    }
+}
+
+/// A query is regenerated from the seed and the config, over the recorded tables.
+///
+/// The tables are recorded because they are generated randomly on each run,
+/// a seed only reproduces the query over the same tables.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CorpusEntry {
+    pub seed: u64,
+    pub config: GeneratorConfig,
+    /// Entries written before the tables were recorded have no tables, they can't be replayed.
+    #[serde(default)]
+    pub tables: Vec<CorpusTable>,
+    /// The seed of the noise injected into the SQL, if any.
+    #[serde(default)]
+    pub noise_seed: Option<u64>,
+}
+
+impl CorpusEntry {
+    pub(crate) fn new(seed: u64, config: GeneratorConfig, tables: &[Table]) -> Self {
+        Self {
+            seed,
+            config,
+            tables: tables.iter().map(CorpusTable::from_table).collect(),
+            noise_seed: None,
+        }
+    }
+
+    pub(crate) fn tables(&self) -> Vec<Table> {
+        self.tables.iter().map(CorpusTable::to_table).collect()
+    }
+
+    /// Generate the query and its SQL, with the recorded noise injected.
+    pub(crate) fn gen_sql(&self, settings: &[(String, DataType)]) -> (Query, String) {
+        let query = self.gen_query(settings);
+        let mut sql = query.to_string();
+        if let Some(noise_seed) = self.noise_seed {
+            let mut rng = SmallRng::seed_from_u64(noise_seed);
+            let mut generator = SqlGenerator::new(&mut rng, vec![]);
+            sql = generator.inject_noise(&sql);
+        }
+        (query, sql)
+    }
+
+    pub(crate) fn gen_query(&self, settings: &[(String, DataType)]) -> Query {
+        let mut rng = SmallRng::seed_from_u64(self.seed);
+        let mut generator = SqlGenerator::new(&mut rng, settings.to_vec());
+        if !self.config.functions.is_empty() {
+            generator.set_allowed_funcs(&self.config.functions);
+        }
+        generator.enable_spatial = self.config.spatial;
+        generator.coercion_mode = self.config.coercion;
+        generator.gen_error_cases = self.config.error_cases;
+        if let Some(max_expr_depth) = self.config.max_expr_depth {
+            generator.max_expr_depth = max_expr_depth;
+            generator.expr_depth = max_expr_depth;
+        }
+        generator.tables = self.tables();
+        match self.config.max_sql_bytes {
+            Some(max_sql_bytes) => generator.gen_query_within(max_sql_bytes),
+            None => generator.gen_query(),
+        }
+    }
+}
+
+/// The seeds of the queries which found bugs, replayed first on each run as a regression guard.
+///
+/// The corpus file has one JSON entry per line, new entries are appended as soon as
+/// they are found, so they are kept even if the run is interrupted.
+pub struct SeedCorpus {
+    path: PathBuf,
+    entries: Vec<CorpusEntry>,
+}
+
+impl SeedCorpus {
+    /// Load the corpus file, a missing file is an empty corpus.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut entries = vec![];
+        if path.exists() {
+            let content = fs::read_to_string(&path)?;
+            for line in content.lines() {
+                let line = line.trim();
+                if !line.is_empty() {
+                    entries.push(serde_json::from_str(line)?);
+                }
+            }
+        }
+        Ok(Self { path, entries })
+    }
+
+    pub fn entries(&self) -> &[CorpusEntry] {
+        &self.entries
+    }
+
+    /// Persist the entry into the corpus file, the duplicated entries are ignored.
+    pub fn add(&mut self, entry: CorpusEntry) -> Result<()> {
+        if self.entries.contains(&entry) {
+            return Ok(());
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::CorpusEntry;
+    use super::GeneratorConfig;
+    use super::SeedCorpus;
+    use crate::sql_gen::mock_tables;
+
+    #[test]
+    fn test_replay_corpus() {
+        let path =
+            std::env::temp_dir().join(format!("sqlsmith_corpus_{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let tables = mock_tables();
+
+        let mut corpus = SeedCorpus::load(&path).unwrap();
+        assert!(corpus.entries().is_empty());
+        let mut entries = vec![
+            CorpusEntry::new(1, GeneratorConfig::default(), &tables),
+            CorpusEntry::new(
+                42,
+                GeneratorConfig {
+                    functions: vec![],
+                    spatial: false,
+                    coercion: true,
+                    error_cases: false,
+                    max_sql_bytes: None,
+                    max_expr_depth: None,
+                },
+                &tables,
+            ),
+            CorpusEntry::new(
+                7,
+                GeneratorConfig {
+                    functions: vec!["plus".to_string(), "concat".to_string()],
+                    spatial: true,
+                    coercion: false,
+                    error_cases: true,
+                    max_sql_bytes: Some(256),
+                    max_expr_depth: Some(3),
+                },
+                &tables,
+            ),
+        ];
+        entries[1].noise_seed = Some(3);
+        let mut originals = vec![];
+        for entry in &entries {
+            originals.push(entry.gen_sql(&[]).1);
+            corpus.add(entry.clone()).unwrap();
+        }
+        // the duplicated entry is not persisted again
+        corpus.add(entries[0].clone()).unwrap();
+        assert_eq!(corpus.entries().len(), entries.len());
+
+        // the replayed queries are the same as the original ones, without the original tables
+        let corpus = SeedCorpus::load(&path).unwrap();
+        assert_eq!(corpus.entries(), entries.as_slice());
+        let replayed = corpus
+            .entries()
+            .iter()
+            .map(|entry| entry.gen_sql(&[]).1)
+            .collect::<Vec<_>>();
+        assert_eq!(replayed, originals);
+        // the noise is injected into the SQL of the query
+        assert_ne!(originals[1], entries[1].gen_query(&[]).to_string());
+
+        let table = &corpus.entries()[0].tables[0];
+        assert_eq!(
+            table.create_sql(),
+            "CREATE OR REPLACE TABLE t1 (c0 BIGINT NOT NULL, c1 INT UNSIGNED NULL, \
+            c2 VARCHAR NOT NULL, c3 TIMESTAMP NOT NULL, c4 VARIANT NOT NULL, \
+            c5 ARRAY(DOUBLE NOT NULL) NOT NULL, c6 BOOLEAN NOT NULL)"
+        );
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,218 @@
+// Copyright 2021 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use databend_common_ast::ast::FunctionCall;
+use derive_visitor::Drive;
+use derive_visitor::Visitor;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use crate::corpus::CorpusEntry;
+use crate::corpus::GeneratorConfig;
+use crate::runner::Runner;
+use crate::sql_gen::SqlGenerator;
+use crate::sql_gen::Table;
+
+/// The number of calls of each function in the queries generated from a range of seeds.
+#[derive(Visitor, Clone, Debug, Default, PartialEq, Eq)]
+#[visitor(FunctionCall(enter))]
+pub(crate) struct FunctionDistribution {
+    counts: BTreeMap<String, usize>,
+    total: usize,
+}
+
+impl FunctionDistribution {
+    /// Generate a query from each seed with the config and count the called functions.
+    pub(crate) fn collect(config: &GeneratorConfig, seeds: Range<u64>, tables: &[Table]) -> Self {
+        let mut distribution = Self::default();
+        for seed in seeds {
+            let entry = CorpusEntry::new(seed, config.clone(), tables);
+            entry.gen_query(&[]).drive(&mut distribution);
+        }
+        distribution
+    }
+
+    fn enter_function_call(&mut self, func: &FunctionCall) {
+        *self
+            .counts
+            .entry(func.name.name.to_lowercase())
+            .or_default() += 1;
+        self.total += 1;
+    }
+
+    fn frequency(&self, name: &str) -> f64 {
+        match self.counts.get(name) {
+            Some(count) => *count as f64 / self.total as f64,
+            None => 0.0,
+        }
+    }
+
+    /// Compare the relative frequencies of the functions of the two distributions.
+    pub(crate) fn compare(&self, other: &FunctionDistribution) -> DistributionDiff {
+        let names = self
+            .counts
+            .keys()
+            .chain(other.counts.keys())
+            .collect::<BTreeSet<_>>();
+        let mut changes = names
+            .into_iter()
+            .filter_map(|name| {
+                let left = self.frequency(name);
+                let right = other.frequency(name);
+                (left != right).then(|| FrequencyChange {
+                    name: name.clone(),
+                    left,
+                    right,
+                })
+            })
+            .collect::<Vec<_>>();
+        // the most changed functions first
+        changes.sort_by(|a, b| b.delta().total_cmp(&a.delta()));
+        let distance = changes.iter().map(|change| change.delta()).sum::<f64>() / 2.0;
+        DistributionDiff {
+            distance,
+            total_calls: (self.total, other.total),
+            changes,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrequencyChange {
+    pub name: String,
+    pub left: f64,
+    pub right: f64,
+}
+
+impl FrequencyChange {
+    pub fn delta(&self) -> f64 {
+        (self.left - self.right).abs()
+    }
+}
+
+/// The differences between the functions generated by two configs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DistributionDiff {
+    /// The total variation distance of the relative frequencies, from 0 for the same
+    /// distributions to 1 for the distributions without any function in common.
+    pub distance: f64,
+    pub total_calls: (usize, usize),
+    pub changes: Vec<FrequencyChange>,
+}
+
+impl DistributionDiff {
+    /// Whether the relative frequencies or the total number of the calls
+    /// changed by more than the threshold.
+    pub fn is_significant(&self, threshold: f64) -> bool {
+        let (left, right) = self.total_calls;
+        let total_change = left.abs_diff(right) as f64 / left.max(right).max(1) as f64;
+        self.distance > threshold || total_change > threshold
+    }
+
+    /// Log the distance and the most changed functions.
+    pub fn report(&self, top: usize) {
+        tracing::info!(
+            "function distance: {:.4}, total calls: {} vs {}",
+            self.distance,
+            self.total_calls.0,
+            self.total_calls.1
+        );
+        for change in self.changes.iter().take(top) {
+            tracing::info!(
+                "  {}: {:.4} vs {:.4}",
+                change.name,
+                change.left,
+                change.right
+            );
+        }
+    }
+}
+
+/// Run the generator with two configs over the same seeds and compare the functions
+/// they generate, to detect the unintended changes of the generator behavior.
+///
+/// The queries are generated over the base tables without running them,
+/// so no server is needed.
+pub fn diff_configs(
+    left: &GeneratorConfig,
+    right: &GeneratorConfig,
+    seeds: Range<u64>,
+) -> DistributionDiff {
+    let tables = base_tables();
+    let left = FunctionDistribution::collect(left, seeds.clone(), &tables);
+    let right = FunctionDistribution::collect(right, seeds, &tables);
+    left.compare(&right)
+}
+
+fn base_tables() -> Vec<Table> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut generator = SqlGenerator::new(&mut rng, vec![]);
+    generator
+        .gen_base_tables("sqlsmith_test")
+        .into_iter()
+        .map(|(_, create_table_stmt)| Runner::base_table(create_table_stmt))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff_configs;
+    use super::FunctionDistribution;
+    use crate::corpus::GeneratorConfig;
+    use crate::sql_gen::mock_tables;
+
+    #[test]
+    fn test_diff_configs() {
+        let tables = mock_tables();
+        let config = GeneratorConfig {
+            max_expr_depth: Some(1),
+            ..Default::default()
+        };
+
+        // the same config generates the same functions from the same seeds
+        let left = FunctionDistribution::collect(&config, 0..200, &tables);
+        let right = FunctionDistribution::collect(&config, 0..200, &tables);
+        assert!(left.total > 0);
+        assert_eq!(left, right);
+        let diff = left.compare(&right);
+        assert_eq!(diff.distance, 0.0);
+        assert!(diff.changes.is_empty());
+        assert!(!diff.is_significant(0.0));
+
+        // the deeper expressions call more functions
+        let deeper = GeneratorConfig {
+            max_expr_depth: Some(4),
+            ..Default::default()
+        };
+        let diff = FunctionDistribution::collect(&config, 0..200, &tables)
+            .compare(&FunctionDistribution::collect(&deeper, 0..200, &tables));
+        assert!(diff.total_calls.0 < diff.total_calls.1);
+        assert!(diff.distance > 0.0);
+        assert!(diff.is_significant(0.05));
+
+        // the allowed functions restrict the distribution
+        let allowed = GeneratorConfig {
+            functions: vec!["plus".to_string()],
+            max_expr_depth: Some(1),
+            ..Default::default()
+        };
+        let diff = diff_configs(&config, &allowed, 0..200);
+        assert!(diff.distance > 0.1);
+        assert!(diff.is_significant(0.1));
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright 2021 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+
+use databend_common_exception::ErrorCode;
+
+const KNOWN_ERRORS: &[&str] = &[
+    // Errors caused by illegal parameters
+    "Overflow on date YMD",
+    "timestamp is out of range",
+    "unable to cast type",
+    "unable to cast to type",
+    "invalid digit found in string",
+    "number overflowed",
+    "date is out of range",
+    "Odd number of digits",
+    "The maximum sleep time is 3 seconds",
+    "Too many times to repeat",
+    "Incorrect arguments to",
+    "divided by zero",
+    "Decimal overflow at line",
+    "cannot parse to type",
+    "invalid resolution",
+    "invalid cell index",
+    "invalid directed edge index",
+    "invalid coordinate range",
+    "window function calls cannot be nested",
+    "attempt to shift left with overflow",
+    "attempt to shift right with overflow",
+    "attempt to subtract with overflow",
+    "attempt to add with overflow",
+    // Unsupported features
+    "Row format is not yet support for",
+    "to_decimal not support this DataType",
+    "AggregateSumFunction does not support type",
+    "AggregateArrayMovingAvgFunction does not support type",
+    "AggregateArrayMovingSumFunction does not support type",
+    "The arguments of AggregateRetention should be an expression which returns a Boolean result",
+    "AggregateWindowFunnelFunction does not support type",
+    "nth_value should count from 1",
+    "step must not be zero",
+    "start must be less than or equal to end when step is positive",
+    "start must be greater than or equal to end when step is negative",
+    "Expected Number, Date or Timestamp type, but got",
+    "Unsupported data type for generate_series",
+    "Having clause can't contain window functions",
+    "Cannot find common type for",
+    "null value in column",
+];
+
+// The errors caused by the random arguments of the generated expressions.
+const ARGUMENT_ERROR_CODES: [u16; 2] =
+    [ErrorCode::INVALID_ARGUMENT, ErrorCode::BAD_DATA_VALUE_TYPE];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// The error is caused by the randomly generated query itself,
+    /// e.g. syntax and semantic errors, overflows and illegal arguments.
+    Expected,
+    /// The error may indicate a bug, e.g. internal errors and panics, which should be reported.
+    Suspicious,
+}
+
+/// Classify the errors returned by the server, only the suspicious errors are reported.
+///
+/// An error is expected if its code or the prefix of its message is in the classification table,
+/// all the other errors, including the unknown ones, are suspicious.
+#[derive(Clone, Debug)]
+pub struct ErrorClassifier {
+    expected_codes: HashSet<u16>,
+    expected_messages: Vec<String>,
+}
+
+impl Default for ErrorClassifier {
+    fn default() -> Self {
+        let expected_codes = [ErrorCode::SYNTAX_EXCEPTION, ErrorCode::SEMANTIC_ERROR];
+        Self {
+            expected_codes: expected_codes
+                .into_iter()
+                .chain(ARGUMENT_ERROR_CODES)
+                .collect(),
+            expected_messages: KNOWN_ERRORS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl ErrorClassifier {
+    /// Extend the classification table with more expected error codes and message prefixes.
+    pub fn with_expected(
+        mut self,
+        codes: impl IntoIterator<Item = u16>,
+        messages: impl IntoIterator<Item = String>,
+    ) -> Self {
+        self.expected_codes.extend(codes);
+        self.expected_messages.extend(messages);
+        self
+    }
+
+    /// The stricter classifier for the statements which set up the tables and run the DML,
+    /// where the invalid argument and bad data value errors are suspicious.
+    pub fn for_setup(&self) -> Self {
+        let mut classifier = self.clone();
+        for code in ARGUMENT_ERROR_CODES {
+            classifier.expected_codes.remove(&code);
+        }
+        classifier
+    }
+
+    pub fn classify(&self, code: u64, message: &str) -> ErrorClass {
+        let is_expected_code = u16::try_from(code)
+            .map(|code| self.expected_codes.contains(&code))
+            .unwrap_or(false);
+        if is_expected_code
+            || self
+                .expected_messages
+                .iter()
+                .any(|known_err| message.starts_with(known_err.as_str()))
+        {
+            ErrorClass::Expected
+        } else {
+            ErrorClass::Suspicious
+        }
+    }
+
+    pub fn is_suspicious(&self, code: u64, message: &str) -> bool {
+        self.classify(code, message) == ErrorClass::Suspicious
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use databend_common_exception::ErrorCode;
+
+    use super::ErrorClass;
+    use super::ErrorClassifier;
+
+    #[test]
+    fn test_classify_errors() {
+        let classifier = ErrorClassifier::default();
+        // errors caused by the generated queries are filtered
+        let expected = [
+            (ErrorCode::SYNTAX_EXCEPTION, "error: unexpected `)`"),
+            (ErrorCode::SEMANTIC_ERROR, "column c0 doesn't exist"),
+            (ErrorCode::BAD_ARGUMENTS, "divided by zero"),
+            (ErrorCode::PANIC_ERROR, "attempt to add with overflow"),
+        ];
+        for (code, message) in expected {
+            assert_eq!(
+                classifier.classify(code as u64, message),
+                ErrorClass::Expected
+            );
+        }
+
+        // internal errors, panics and the unknown errors are surfaced
+        let suspicious = [
+            (ErrorCode::INTERNAL, "Logical error, please report a bug"),
+            (ErrorCode::PANIC_ERROR, "index out of bounds"),
+            (ErrorCode::UNKNOWN_EXCEPTION, "unknown"),
+            (65535, "error with an unknown code"),
+        ];
+        for (code, message) in suspicious {
+            assert!(classifier.is_suspicious(code as u64, message));
+        }
+        assert!(classifier.is_suspicious(u64::MAX, "error with an invalid code"));
+
+        // the classification table is configurable
+        let classifier = ErrorClassifier::default()
+            .with_expected([ErrorCode::PANIC_ERROR], ["Logical error".to_string()]);
+        assert!(!classifier.is_suspicious(ErrorCode::PANIC_ERROR as u64, "index out of bounds"));
+        assert!(!classifier.is_suspicious(
+            ErrorCode::INTERNAL as u64,
+            "Logical error, please report a bug"
+        ));
+        assert!(classifier.is_suspicious(ErrorCode::UNKNOWN_EXCEPTION as u64, "unknown"));
+
+        // the setup statements only expect the syntax and semantic errors, and the known messages
+        let classifier = ErrorClassifier::default().for_setup();
+        assert!(!classifier.is_suspicious(ErrorCode::SYNTAX_EXCEPTION as u64, "error"));
+        assert!(!classifier.is_suspicious(ErrorCode::SEMANTIC_ERROR as u64, "error"));
+        assert!(!classifier.is_suspicious(ErrorCode::BAD_ARGUMENTS as u64, "divided by zero"));
+        assert!(classifier.is_suspicious(ErrorCode::INVALID_ARGUMENT as u64, "invalid"));
+        assert!(classifier.is_suspicious(ErrorCode::BAD_DATA_VALUE_TYPE as u64, "bad value"));
+    }
+}
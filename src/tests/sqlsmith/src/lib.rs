@@ -14,10 +14,22 @@
 
 #![feature(box_patterns)]
 
+mod corpus;
+mod differential;
+mod error_classifier;
 mod http_client;
 mod query_fuzzer;
 mod reducer;
 mod runner;
 mod sql_gen;
 
+pub use corpus::CorpusEntry;
+pub use corpus::CorpusTable;
+pub use corpus::GeneratorConfig;
+pub use corpus::SeedCorpus;
+pub use differential::diff_configs;
+pub use differential::DistributionDiff;
+pub use differential::FrequencyChange;
+pub use error_classifier::ErrorClass;
+pub use error_classifier::ErrorClassifier;
 pub use runner::Runner;
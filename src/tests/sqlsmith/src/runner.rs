@@ -37,55 +37,18 @@ use rand::rngs::SmallRng;
 use rand::Rng;
 use rand::SeedableRng;
 
+use crate::corpus::CorpusEntry;
+use crate::corpus::GeneratorConfig;
+use crate::corpus::SeedCorpus;
+use crate::error_classifier::ErrorClassifier;
 use crate::http_client::HttpClient;
 use crate::http_client::QueryResponse;
 use crate::query_fuzzer::QueryFuzzer;
 use crate::sql_gen::SqlGenerator;
+use crate::sql_gen::StatementWeights;
 use crate::sql_gen::Table;
 
-const KNOWN_ERRORS: &[&str] = &[
-    // Errors caused by illegal parameters
-    "Overflow on date YMD",
-    "timestamp is out of range",
-    "unable to cast type",
-    "unable to cast to type",
-    "invalid digit found in string",
-    "number overflowed",
-    "date is out of range",
-    "Odd number of digits",
-    "The maximum sleep time is 3 seconds",
-    "Too many times to repeat",
-    "Incorrect arguments to",
-    "divided by zero",
-    "Decimal overflow at line",
-    "cannot parse to type",
-    "invalid resolution",
-    "invalid cell index",
-    "invalid directed edge index",
-    "invalid coordinate range",
-    "window function calls cannot be nested",
-    "attempt to shift left with overflow",
-    "attempt to shift right with overflow",
-    "attempt to subtract with overflow",
-    "attempt to add with overflow",
-    // Unsupported features
-    "Row format is not yet support for",
-    "to_decimal not support this DataType",
-    "AggregateSumFunction does not support type",
-    "AggregateArrayMovingAvgFunction does not support type",
-    "AggregateArrayMovingSumFunction does not support type",
-    "The arguments of AggregateRetention should be an expression which returns a Boolean result",
-    "AggregateWindowFunnelFunction does not support type",
-    "nth_value should count from 1",
-    "step must not be zero",
-    "start must be less than or equal to end when step is positive",
-    "start must be greater than or equal to end when step is negative",
-    "Expected Number, Date or Timestamp type, but got",
-    "Unsupported data type for generate_series",
-    "Having clause can't contain window functions",
-    "Cannot find common type for",
-    "null value in column",
-];
+const ROW_COUNT: usize = 10;
 
 pub struct Runner {
     count: usize,
@@ -93,6 +56,15 @@ pub struct Runner {
     pub(crate) client: HttpClient,
     db: String,
     timeout: u64,
+    functions: Vec<String>,
+    inject_noise: bool,
+    spatial: bool,
+    coercion: bool,
+    error_cases: bool,
+    error_classifier: ErrorClassifier,
+    setup_error_classifier: ErrorClassifier,
+    corpus: Option<SeedCorpus>,
+    max_sql_bytes: Option<usize>,
 }
 
 impl Runner {
@@ -104,6 +76,9 @@ impl Runner {
         count: usize,
         seed: Option<u64>,
         timeout: u64,
+        functions: Vec<String>,
+        inject_noise: bool,
+        spatial: bool,
     ) -> Result<Self> {
         let client = HttpClient::create(host, username, password).await?;
 
@@ -113,31 +88,87 @@ impl Runner {
             client,
             db,
             timeout,
+            functions,
+            inject_noise,
+            spatial,
+            coercion: false,
+            error_cases: false,
+            error_classifier: ErrorClassifier::default(),
+            setup_error_classifier: ErrorClassifier::default().for_setup(),
+            corpus: None,
+            max_sql_bytes: None,
         })
     }
 
-    pub async fn run(&mut self) -> Result<()> {
-        let create_db_sql = format!("CREATE OR REPLACE database {}", self.db);
-        let _ = self.client.query(&create_db_sql).await?;
-        let use_db_sql = format!("USE {}", self.db);
-        let _ = self.client.query(&use_db_sql).await?;
+    /// Place literals of another type category in the function arguments,
+    /// to test the implicit casts between strings and numbers.
+    pub fn set_coercion_mode(&mut self, coercion: bool) {
+        self.coercion = coercion;
+    }
+
+    /// Generate the statements which are expected to fail, such as unknown settings
+    /// and unsupported casts, to test the error paths.
+    pub fn set_error_cases_mode(&mut self, error_cases: bool) {
+        self.error_cases = error_cases;
+    }
 
+    /// Replace the table to classify the expected and suspicious errors,
+    /// only the suspicious errors are reported.
+    ///
+    /// The setup and DML statements are checked with the stricter `for_setup` of the table.
+    pub fn set_error_classifier(&mut self, error_classifier: ErrorClassifier) {
+        self.setup_error_classifier = error_classifier.for_setup();
+        self.error_classifier = error_classifier;
+    }
+
+    /// Replay the queries of the corpus before generating new ones,
+    /// and persist the seeds of the new queries which found bugs into it.
+    pub fn set_corpus(&mut self, corpus: SeedCorpus) {
+        self.corpus = Some(corpus);
+    }
+
+    /// Regenerate the queries with less complexity until their SQL fits in the budget.
+    pub fn set_max_sql_bytes(&mut self, max_sql_bytes: usize) {
+        self.max_sql_bytes = Some(max_sql_bytes);
+    }
+
+    pub async fn run(&mut self) -> Result<()> {
         let settings = self.get_settings().await?;
+        // replay the queries which found bugs before
+        self.replay_corpus(&settings).await?;
+
+        self.reset_database().await?;
 
         let mut rng = Self::generate_rng(self.seed);
-        let mut generator = SqlGenerator::new(&mut rng, settings);
+        let mut generator = SqlGenerator::new(&mut rng, settings.clone());
+        if !self.functions.is_empty() {
+            generator.set_allowed_funcs(&self.functions);
+        }
+        generator.enable_spatial = self.spatial;
+        generator.coercion_mode = self.coercion;
+        generator.gen_error_cases = self.error_cases;
         let table_stmts = generator.gen_base_tables(&self.db);
-        let tables = self.create_base_table(table_stmts).await?;
-        let row_count = 10;
+        let mut tables = self.create_base_table(table_stmts).await?;
+
+        // a table with random columns and options, in addition to the base tables
+        let (create_table_stmt, table) = generator.gen_create_table(&self.db, "t5");
+        let create_table_sql = create_table_stmt.to_string();
+        tracing::info!("create_table_sql: {}", create_table_sql);
+        let responses = self.client.query(&create_table_sql).await;
+        let created = matches!(&responses, Ok(responses) if responses[0].error.is_none());
+        self.check_res(responses);
+        if created {
+            tables.push(table);
+        }
 
         let mut new_tables = tables.clone();
         for (i, table) in tables.iter().enumerate() {
-            let insert_stmt = generator.gen_insert(table, row_count);
+            let insert_stmt = generator.gen_insert(table, ROW_COUNT);
             let insert_sql = insert_stmt.to_string();
             tracing::info!("insert_sql: {}", insert_sql);
-            Self::check_res(self.client.query(&insert_sql).await);
+            self.check_res(self.client.query(&insert_sql).await);
 
-            let alter_stmt_opt = generator.gen_alter(table, row_count);
+            let alter_stmt_opt = generator.gen_alter(table, ROW_COUNT);
             if let Some((alter_stmt, new_table, insert_stmt_opt)) = alter_stmt_opt {
                 if let AlterTableAction::RenameTable { ref new_table } = alter_stmt.action {
                     let drop_table_stmt = DropTableStmt {
@@ -149,50 +180,150 @@ impl Runner {
                     };
                     let drop_table_sql = drop_table_stmt.to_string();
                     tracing::info!("drop_table_sql: {}", drop_table_sql);
-                    Self::check_res(self.client.query(&drop_table_sql).await);
+                    self.check_res(self.client.query(&drop_table_sql).await);
                 }
                 let alter_sql = alter_stmt.to_string();
                 tracing::info!("alter_sql: {}", alter_sql);
-                Self::check_res(self.client.query(&alter_sql).await);
+                self.check_res(self.client.query(&alter_sql).await);
                 // save new table schema
                 new_tables[i] = new_table;
                 if let Some(insert_stmt) = insert_stmt_opt {
                     let insert_sql = insert_stmt.to_string();
                     tracing::info!("after alter insert_sql: {}", insert_sql);
-                    Self::check_res(self.client.query(&insert_sql).await);
+                    self.check_res(self.client.query(&insert_sql).await);
                 }
             }
         }
         generator.tables = new_tables;
 
         let enable_merge = "set enable_experimental_merge_into = 1".to_string();
-        Self::check_res(self.client.query(&enable_merge).await);
+        self.check_res(self.client.query(&enable_merge).await);
         // generate merge, replace, update, delete
+        let dml_weights = StatementWeights::dml();
         for _ in 0..20 {
-            let sql = match generator.rng.gen_range(0..=20) {
-                0..=10 => generator.gen_merge().to_string(),
-                11..=15 => generator.gen_replace().to_string(),
-                16..=19 => generator.gen_update().to_string(),
-                20 => generator.gen_delete().to_string(),
-                _ => unreachable!(),
+            let is_transaction = generator.rng.gen_bool(0.2);
+            let stmts = if is_transaction {
+                generator.gen_transaction(&dml_weights)
+            } else {
+                let stmt = generator.gen_statement(&dml_weights);
+                if generator.rng.gen_bool(0.1) {
+                    vec![generator.gen_statement_with_settings(stmt)]
+                } else {
+                    vec![stmt]
+                }
             };
-            let mut timeout_err = None;
-            tracing::info!("dml sql: {}", sql);
-            Self::check_timeout(
-                async { Self::check_res(self.client.query(&sql).await) },
-                self.timeout,
-                &mut timeout_err,
-            )
-            .await;
-            if let Some(timeout_err) = timeout_err {
-                tracing::error!("sql timeout: {}", timeout_err);
+            for stmt in stmts {
+                let sql = stmt.to_string();
+                let mut timeout_err = None;
+                let mut failed = false;
+                tracing::info!("dml sql: {}", sql);
+                Self::check_timeout(
+                    async {
+                        let responses = self.client.query(&sql).await;
+                        failed = Self::has_error(&responses);
+                        self.check_res(responses)
+                    },
+                    self.timeout,
+                    &mut timeout_err,
+                )
+                .await;
+                if let Some(timeout_err) = timeout_err {
+                    tracing::error!("sql timeout: {}", timeout_err);
+                    failed = true;
+                }
+                // the later statements of the transaction would fail as it is aborted
+                if failed && is_transaction {
+                    let abort_sql = Statement::Abort.to_string();
+                    tracing::info!("dml sql: {}", abort_sql);
+                    self.check_res(self.client.query(&abort_sql).await);
+                    break;
+                }
             }
         }
 
-        // generate query
+        // generate describe and show statements of the tables
+        let show_weights = StatementWeights {
+            show: 1,
+            ..Default::default()
+        };
+        for _ in 0..10 {
+            let show_sql = generator.gen_statement(&show_weights).to_string();
+            tracing::info!("show sql: {}", show_sql);
+            self.check_res(self.client.query(&show_sql).await);
+        }
+
+        // generate query, each one from its own seed to be replayed later
+        let config = GeneratorConfig {
+            functions: self.functions.clone(),
+            spatial: self.spatial,
+            coercion: self.coercion,
+            error_cases: self.error_cases,
+            max_sql_bytes: self.max_sql_bytes,
+            max_expr_depth: None,
+        };
         for _ in 0..self.count {
-            let query = generator.gen_query();
-            let query_sql = query.to_string();
+            let mut entry =
+                CorpusEntry::new(generator.rng.gen(), config.clone(), &generator.tables);
+            if self.inject_noise {
+                entry.noise_seed = Some(generator.rng.gen());
+            }
+            let (query, query_sql) = entry.gen_sql(&settings);
+            let is_error = self.run_sql(query_sql, Some(query)).await;
+            if is_error {
+                if let Some(corpus) = &mut self.corpus {
+                    corpus.add(entry)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn reset_database(&mut self) -> Result<()> {
+        let create_db_sql = format!("CREATE OR REPLACE database {}", self.db);
+        let _ = self.client.query(&create_db_sql).await?;
+        let use_db_sql = format!("USE {}", self.db);
+        let _ = self.client.query(&use_db_sql).await?;
+        Ok(())
+    }
+
+    // Replay each query of the corpus over its recorded tables, in a new database.
+    // The tables are filled with the rows generated from the seed of the entry,
+    // the rows of the run which found the query are not recorded.
+    async fn replay_corpus(&mut self, settings: &[(String, DataType)]) -> Result<()> {
+        let entries = self
+            .corpus
+            .as_ref()
+            .map(|corpus| corpus.entries().to_vec())
+            .unwrap_or_default();
+        for mut entry in entries {
+            if entry.tables.is_empty() {
+                tracing::warn!(
+                    "skip replay seed: {}, its tables are not recorded",
+                    entry.seed
+                );
+                continue;
+            }
+            tracing::info!("replay seed: {}", entry.seed);
+            self.reset_database().await?;
+            // the entry may be recorded by a run in another database
+            for table in entry.tables.iter_mut() {
+                if table.database.is_some() {
+                    table.database = Some(self.db.clone());
+                }
+            }
+            let mut rng = SmallRng::seed_from_u64(entry.seed);
+            let mut generator = SqlGenerator::new(&mut rng, settings.to_vec());
+            for table in entry.tables.iter() {
+                let create_table_sql = table.create_sql();
+                tracing::info!("create_table_sql: {}", create_table_sql);
+                self.check_res(self.client.query(&create_table_sql).await);
+                let insert_sql = generator
+                    .gen_insert(&table.to_table(), ROW_COUNT)
+                    .to_string();
+                tracing::info!("insert_sql: {}", insert_sql);
+                self.check_res(self.client.query(&insert_sql).await);
+            }
+            let (query, query_sql) = entry.gen_sql(settings);
             self.run_sql(query_sql, Some(query)).await;
         }
         Ok(())
@@ -222,7 +353,8 @@ impl Runner {
         Ok(())
     }
 
-    async fn run_sql(&mut self, query_sql: String, query: Option<Query>) {
+    // Returns true if the query timed out or hit a suspicious error.
+    async fn run_sql(&mut self, query_sql: String, query: Option<Query>) -> bool {
         let mut timeout_err = None;
         let mut is_error = false;
         let mut try_reduce = false;
@@ -236,13 +368,7 @@ impl Runner {
                             let value = error.as_object().unwrap();
                             let code = value["code"].as_u64().unwrap();
                             let message = value["message"].as_str().unwrap();
-                            if code == 1005 || code == 1065 || code == 2004 || code == 1010 {
-                                return;
-                            }
-                            if KNOWN_ERRORS
-                                .iter()
-                                .any(|known_err| message.starts_with(known_err))
-                            {
+                            if !self.error_classifier.is_suspicious(code, message) {
                                 return;
                             }
                             is_error = true;
@@ -262,6 +388,7 @@ impl Runner {
         )
         .await;
 
+        let found = timeout_err.is_some() || is_error;
         if let Some(timeout_err) = timeout_err {
             tracing::info!("query_sql: {}", query_sql);
             tracing::error!("sql timeout: {}", timeout_err);
@@ -275,6 +402,7 @@ impl Runner {
             }
             tracing::error!(err_message);
         }
+        found
     }
 
     fn generate_rng(seed: Option<u64>) -> impl Rng {
@@ -293,29 +421,33 @@ impl Runner {
         for (drop_table_stmt, create_table_stmt) in table_stmts {
             let drop_table_sql = drop_table_stmt.to_string();
             tracing::info!("drop_table_sql: {}", drop_table_sql);
-            Self::check_res(self.client.query(&drop_table_sql).await);
+            self.check_res(self.client.query(&drop_table_sql).await);
             let create_table_sql = create_table_stmt.to_string();
             tracing::info!("create_table_sql: {}", create_table_sql);
-            Self::check_res(self.client.query(&create_table_sql).await);
-
-            let db_name = create_table_stmt.database.clone();
-            let table_name = create_table_stmt.table.clone();
-            let mut fields = Vec::new();
-            if let CreateTableSource::Columns(columns, _) = create_table_stmt.source.unwrap() {
-                for column in columns {
-                    let data_type = resolve_type_name(&column.data_type, true).unwrap();
-                    let field = TableField::new(&column.name.name, data_type);
-                    fields.push(field);
-                }
-            }
-            let schema = TableSchemaRefExt::create(fields);
+            self.check_res(self.client.query(&create_table_sql).await);
 
-            let table = Table::new(db_name, table_name, schema);
+            let table = Self::base_table(create_table_stmt);
             tables.push(table);
         }
         Ok(tables)
     }
 
+    // The table of the columns defined in the create statement.
+    pub(crate) fn base_table(create_table_stmt: CreateTableStmt) -> Table {
+        let db_name = create_table_stmt.database.clone();
+        let table_name = create_table_stmt.table.clone();
+        let mut fields = Vec::new();
+        if let CreateTableSource::Columns(columns, _) = create_table_stmt.source.unwrap() {
+            for column in columns {
+                let data_type = resolve_type_name(&column.data_type, true).unwrap();
+                let field = TableField::new(&column.name.name, data_type);
+                fields.push(field);
+            }
+        }
+        let schema = TableSchemaRefExt::create(fields);
+        Table::new(db_name, table_name, schema)
+    }
+
     async fn get_settings(&mut self) -> Result<Vec<(String, DataType)>> {
         let show_settings = "show settings".to_string();
         let responses = self.client.query(&show_settings).await?;
@@ -351,20 +483,21 @@ impl Runner {
         }
     }
 
-    fn check_res(responses: Result<Vec<QueryResponse>>) {
+    fn has_error(responses: &Result<Vec<QueryResponse>>) -> bool {
+        match responses {
+            Ok(responses) => responses[0].error.is_some(),
+            Err(_) => true,
+        }
+    }
+
+    fn check_res(&self, responses: Result<Vec<QueryResponse>>) {
         match responses {
             Ok(responses) => {
                 if let Some(error) = &responses[0].error {
                     let value = error.as_object().unwrap();
                     let code = value["code"].as_u64().unwrap();
                     let message = value["message"].as_str().unwrap();
-                    if code == 1005 || code == 1065 {
-                        return;
-                    }
-                    if KNOWN_ERRORS
-                        .iter()
-                        .any(|known_err| message.starts_with(known_err))
-                    {
+                    if !self.setup_error_classifier.is_suspicious(code, message) {
                         return;
                     }
                     let err = format!("sql exec err code: {} message: {}", code, message);
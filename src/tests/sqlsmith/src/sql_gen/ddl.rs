@@ -14,8 +14,12 @@
 
 use std::collections::BTreeMap;
 
+use databend_common_ast::ast::ClusterOption;
+use databend_common_ast::ast::ClusterType;
 use databend_common_ast::ast::ColumnDefinition;
 use databend_common_ast::ast::ColumnExpr;
+use databend_common_ast::ast::ColumnID;
+use databend_common_ast::ast::ColumnRef;
 use databend_common_ast::ast::CreateOption;
 use databend_common_ast::ast::CreateTableSource;
 use databend_common_ast::ast::CreateTableStmt;
@@ -26,10 +30,14 @@ use databend_common_ast::ast::Identifier;
 use databend_common_ast::ast::Literal;
 use databend_common_ast::ast::TableType;
 use databend_common_ast::ast::TypeName;
+use databend_common_expression::TableField;
+use databend_common_expression::TableSchemaRefExt;
+use databend_common_sql::resolve_type_name;
 use rand::distributions::Alphanumeric;
 use rand::Rng;
 
 use crate::sql_gen::SqlGenerator;
+use crate::sql_gen::Table;
 
 const BASE_TABLE_NAMES: [&str; 4] = ["t1", "t2", "t3", "t4"];
 
@@ -104,6 +112,131 @@ impl<R: Rng> SqlGenerator<'_, R> {
         tables
     }
 
+    /// Generate a `CREATE TABLE` statement with random columns, defaults, cluster keys and
+    /// table options, and the schema of the created table for the following statements.
+    pub(crate) fn gen_create_table(
+        &mut self,
+        db_name: &str,
+        table_name: &str,
+    ) -> (CreateTableStmt, Table) {
+        let len = self.rng.gen_range(1..=10);
+        let mut column_defs = Vec::with_capacity(len);
+        let mut fields = Vec::with_capacity(len);
+        for i in 0..len {
+            let name = format!("c{}", i);
+            let data_type = if self.rng.gen_bool(0.8) {
+                self.gen_data_type_name(None)
+            } else {
+                // deeper nested types with nested default values
+                let depth = self.rng.gen_range(1..=2);
+                self.gen_nested_type(depth)
+            };
+            let default_expr = if self.rng.gen_bool(0.5) && support_default_expr(&data_type) {
+                Some(ColumnExpr::Default(Box::new(gen_default_expr(&data_type))))
+            } else {
+                None
+            };
+
+            let field = TableField::new(&name, resolve_type_name(&data_type, true).unwrap());
+            fields.push(field);
+            column_defs.push(ColumnDefinition {
+                name: Identifier::from_name(None, name),
+                data_type,
+                expr: default_expr,
+                comment: None,
+            });
+        }
+
+        let mut table_options = BTreeMap::new();
+        let mut cluster_by = None;
+        let engine = if self.rng.gen_bool(0.9) {
+            if self.rng.gen_bool(0.5) {
+                let storage_format = if self.rng.gen_bool(0.5) {
+                    "native"
+                } else {
+                    "parquet"
+                };
+                table_options.insert("storage_format".to_string(), storage_format.to_string());
+            }
+            if self.rng.gen_bool(0.3) {
+                let compression = match self.rng.gen_range(0..=3) {
+                    0 => "none",
+                    1 => "zstd",
+                    2 => "lz4",
+                    3 => "snappy",
+                    _ => unreachable!(),
+                };
+                table_options.insert("compression".to_string(), compression.to_string());
+            }
+            if self.rng.gen_bool(0.3) {
+                let block_per_segment = self.rng.gen_range(1..=1000);
+                table_options.insert(
+                    "block_per_segment".to_string(),
+                    block_per_segment.to_string(),
+                );
+            }
+            if self.rng.gen_bool(0.3) {
+                let row_per_block = self.rng.gen_range(1..=100000);
+                table_options.insert("row_per_block".to_string(), row_per_block.to_string());
+            }
+            cluster_by = self.gen_cluster_option(&column_defs);
+            Engine::Fuse
+        } else {
+            Engine::Memory
+        };
+
+        let create_table = CreateTableStmt {
+            create_option: CreateOption::CreateOrReplace,
+            catalog: None,
+            database: Some(Identifier::from_name(None, db_name)),
+            table: Identifier::from_name(None, table_name),
+            source: Some(CreateTableSource::Columns(column_defs, None)),
+            engine: Some(engine),
+            uri_location: None,
+            cluster_by,
+            table_options,
+            as_query: None,
+            table_type: TableType::Normal,
+        };
+        let table = Table::new(
+            create_table.database.clone(),
+            create_table.table.clone(),
+            TableSchemaRefExt::create(fields),
+        );
+        (create_table, table)
+    }
+
+    fn gen_cluster_option(&mut self, column_defs: &[ColumnDefinition]) -> Option<ClusterOption> {
+        if self.rng.gen_bool(0.7) {
+            return None;
+        }
+        let cluster_exprs = column_defs
+            .iter()
+            .filter(|column_def| is_cluster_key_type(&column_def.data_type))
+            .filter(|_| self.rng.gen_bool(0.5))
+            .map(|column_def| Expr::ColumnRef {
+                span: None,
+                column: ColumnRef {
+                    database: None,
+                    table: None,
+                    column: ColumnID::Name(column_def.name.clone()),
+                },
+            })
+            .collect::<Vec<_>>();
+        if cluster_exprs.is_empty() {
+            return None;
+        }
+        let cluster_type = if self.rng.gen_bool(0.8) {
+            ClusterType::Linear
+        } else {
+            ClusterType::Hilbert
+        };
+        Some(ClusterOption {
+            cluster_type,
+            cluster_exprs,
+        })
+    }
+
     fn gen_nested_type(&mut self, depth: u8) -> TypeName {
         if depth == 0 {
             // TODO: fix
@@ -212,13 +345,7 @@ impl<R: Rng> SqlGenerator<'_, R> {
             let data_type = self.gen_data_type_name(Some(i));
 
             // TODO: computed expr
-            // TODO: fix binary default value
-            // TODO: fix interval default value
-            // TODO: support `to_geography` function.
-            let default_expr = if data_type != TypeName::NotNull(Box::new(TypeName::Binary))
-                && data_type != TypeName::NotNull(Box::new(TypeName::Geography))
-                && data_type != TypeName::NotNull(Box::new(TypeName::Interval))
-            {
+            let default_expr = if support_default_expr(&data_type) {
                 Some(ColumnExpr::Default(Box::new(gen_default_expr(&data_type))))
             } else {
                 None
@@ -235,6 +362,37 @@ impl<R: Rng> SqlGenerator<'_, R> {
     }
 }
 
+// TODO: fix binary default value
+// TODO: fix interval default value
+// TODO: support `to_geography` function.
+fn support_default_expr(type_name: &TypeName) -> bool {
+    type_name != &TypeName::NotNull(Box::new(TypeName::Binary))
+        && type_name != &TypeName::NotNull(Box::new(TypeName::Geography))
+        && type_name != &TypeName::NotNull(Box::new(TypeName::Interval))
+}
+
+fn is_cluster_key_type(type_name: &TypeName) -> bool {
+    match type_name {
+        TypeName::Nullable(box ty) | TypeName::NotNull(box ty) => is_cluster_key_type(ty),
+        TypeName::Boolean
+        | TypeName::UInt8
+        | TypeName::UInt16
+        | TypeName::UInt32
+        | TypeName::UInt64
+        | TypeName::Int8
+        | TypeName::Int16
+        | TypeName::Int32
+        | TypeName::Int64
+        | TypeName::Float32
+        | TypeName::Float64
+        | TypeName::Decimal { .. }
+        | TypeName::Date
+        | TypeName::Timestamp
+        | TypeName::String => true,
+        _ => false,
+    }
+}
+
 fn gen_default_expr(type_name: &TypeName) -> Expr {
     match type_name {
         TypeName::Boolean => Expr::Literal {
@@ -319,3 +477,72 @@ fn gen_default_expr(type_name: &TypeName) -> Expr {
         TypeName::NotNull(box ty) => gen_default_expr(ty),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use databend_common_ast::ast::ColumnID;
+    use databend_common_ast::ast::CreateTableSource;
+    use databend_common_ast::ast::Engine;
+    use databend_common_ast::ast::Expr;
+    use databend_common_ast::ast::Statement;
+    use databend_common_ast::parser::parse_sql;
+    use databend_common_ast::parser::tokenize_sql;
+    use databend_common_ast::parser::Dialect;
+    use databend_common_sql::resolve_type_name;
+
+    use super::SIMPLE_COLUMN_TYPES;
+    use crate::sql_gen::test_generator;
+
+    #[test]
+    fn test_create_table() {
+        for ty in SIMPLE_COLUMN_TYPES.iter() {
+            assert!(
+                resolve_type_name(ty, true).is_ok(),
+                "unsupported type: {ty}"
+            );
+        }
+
+        let mut generator = test_generator();
+        let (mut clustered, mut memory) = (0, 0);
+        for _ in 0..100 {
+            let (create_table, table) = generator.gen_create_table("db", "t");
+
+            let sql = create_table.to_string();
+            let tokens = tokenize_sql(&sql).unwrap();
+            let (stmt, _) = parse_sql(&tokens, Dialect::PostgreSQL)
+                .unwrap_or_else(|e| panic!("failed to parse {sql}: {e}"));
+            assert!(matches!(stmt, Statement::CreateTable(_)), "{sql}");
+
+            let Some(CreateTableSource::Columns(columns, _)) = &create_table.source else {
+                panic!("expect column definitions");
+            };
+            assert_eq!(columns.len(), table.schema.num_fields());
+            for column in columns {
+                let field = table.schema.field_with_name(&column.name.name).unwrap();
+                let data_type = resolve_type_name(&column.data_type, true).unwrap();
+                assert_eq!(field.data_type(), &data_type);
+            }
+
+            if let Some(cluster_by) = &create_table.cluster_by {
+                clustered += 1;
+                assert!(!cluster_by.cluster_exprs.is_empty());
+                for expr in &cluster_by.cluster_exprs {
+                    let Expr::ColumnRef { column, .. } = expr else {
+                        panic!("unexpected cluster key: {expr}");
+                    };
+                    let ColumnID::Name(name) = &column.column else {
+                        panic!("unexpected cluster key: {expr}");
+                    };
+                    assert!(table.schema.field_with_name(&name.name).is_ok());
+                }
+            }
+            if create_table.engine == Some(Engine::Memory) {
+                memory += 1;
+                assert!(create_table.cluster_by.is_none());
+                assert!(create_table.table_options.is_empty());
+            }
+        }
+        assert!(clustered > 0);
+        assert!(memory > 0);
+    }
+}
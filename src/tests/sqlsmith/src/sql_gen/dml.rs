@@ -20,6 +20,7 @@ use databend_common_ast::ast::AlterTableAction;
 use databend_common_ast::ast::AlterTableStmt;
 use databend_common_ast::ast::ColumnDefinition;
 use databend_common_ast::ast::DeleteStmt;
+use databend_common_ast::ast::Expr;
 use databend_common_ast::ast::Hint;
 use databend_common_ast::ast::HintItem;
 use databend_common_ast::ast::Identifier;
@@ -93,8 +94,9 @@ impl<'a, R: Rng + 'a> SqlGenerator<'a, R> {
 
     pub(crate) fn gen_delete(&mut self) -> DeleteStmt {
         let hints = self.gen_hints();
-        let (_table, table_reference) = self.random_select_table();
-        let selection = Some(self.gen_expr(&DataType::Boolean));
+        let (table, table_reference) = self.random_select_table();
+        self.bound_target_table(table);
+        let selection = Some(self.gen_dml_predicate());
 
         DeleteStmt {
             hints,
@@ -107,18 +109,27 @@ impl<'a, R: Rng + 'a> SqlGenerator<'a, R> {
     pub(crate) fn gen_update(&mut self) -> UpdateStmt {
         let hints = self.gen_hints();
         let (table, table_reference) = self.random_select_table();
-        let selection = if self.rng.gen_bool(0.8) {
+        self.bound_target_table(table.clone());
+        let selection = if self.rng.gen_bool(0.5) {
             None
         } else {
-            Some(self.gen_expr(&DataType::Boolean))
+            Some(self.gen_dml_predicate())
         };
 
         let fields = self.random_select_fields(&table);
         let mut update_list = Vec::with_capacity(fields.len());
         for field in fields {
+            let ty = DataType::from(field.data_type());
+            let expr = if self.rng.gen_bool(0.5) {
+                self.gen_scalar_value(&ty)
+            } else {
+                // can reference columns of the table, scalar functions and subqueries
+                self.only_scalar_expr = true;
+                self.gen_expr(&ty)
+            };
             update_list.push(UpdateExpr {
                 name: Identifier::from_name(None, field.name().clone()),
-                expr: self.gen_scalar_value(&DataType::from(field.data_type())),
+                expr,
             });
         }
         UpdateStmt {
@@ -130,17 +141,44 @@ impl<'a, R: Rng + 'a> SqlGenerator<'a, R> {
         }
     }
 
-    pub(crate) fn gen_replace(&mut self) -> ReplaceStmt {
+    // the target table of UPDATE and DELETE can be referenced in expressions.
+    fn bound_target_table(&mut self, table: Table) {
+        self.cte_tables.clear();
+        self.bound_tables.clear();
+        self.bound_columns.clear();
+        self.is_join = false;
+        self.bound_table(table);
+    }
+
+    // WHERE predicate of UPDATE and DELETE, aggregate and window functions are not allowed.
+    fn gen_dml_predicate(&mut self) -> Expr {
+        if self.rng.gen_bool(0.3) {
+            // IN / EXISTS subqueries, etc.
+            self.gen_other_expr(&DataType::Boolean)
+        } else {
+            self.only_scalar_expr = true;
+            self.gen_expr(&DataType::Boolean)
+        }
+    }
+
+    // Returns None if the table has no column which can be used as a conflict key.
+    pub(crate) fn gen_replace(&mut self) -> Option<ReplaceStmt> {
         let hints = self.gen_hints();
         let (table, _) = self.random_select_table();
-        let fields = self.random_select_fields(&table);
+        let mut fields = self.random_select_fields(&table);
+
+        let conflict_fields = self.gen_conflict_fields(&table)?;
+        // conflict columns must also appear in the insert column list
+        for conflict_field in &conflict_fields {
+            if !fields.iter().any(|f| f.name() == conflict_field.name()) {
+                fields.push(conflict_field.clone());
+            }
+        }
 
         let columns = self.fields_to_identifiers(&fields);
         let data_types = self.fields_to_data_types(&fields);
         let source = self.gen_insert_source(&data_types, 2);
-
-        let conflict_idx = self.rng.gen_range(0..columns.len());
-        let on_conflict_columns = vec![columns[conflict_idx].clone()];
+        let on_conflict_columns = self.fields_to_identifiers(&conflict_fields);
 
         let delete_when = if self.rng.gen_bool(0.8) {
             None
@@ -148,7 +186,7 @@ impl<'a, R: Rng + 'a> SqlGenerator<'a, R> {
             Some(self.gen_expr(&DataType::Boolean))
         };
 
-        ReplaceStmt {
+        Some(ReplaceStmt {
             hints,
             catalog: None,
             database: table.db_name.clone(),
@@ -157,7 +195,7 @@ impl<'a, R: Rng + 'a> SqlGenerator<'a, R> {
             columns,
             source,
             delete_when,
-        }
+        })
     }
 
     pub(crate) fn gen_merge(&mut self) -> MergeIntoStmt {
@@ -322,6 +360,32 @@ impl<'a, R: Rng + 'a> SqlGenerator<'a, R> {
         }
     }
 
+    // There are no primary or unique keys in the table schema,
+    // use the columns whose values can be compared as conflict keys.
+    fn gen_conflict_fields(&mut self, table: &Table) -> Option<Vec<TableField>> {
+        let key_fields = table
+            .schema
+            .fields
+            .iter()
+            .filter(|f| is_conflict_key_type(&DataType::from(f.data_type())))
+            .cloned()
+            .collect::<Vec<_>>();
+        if key_fields.is_empty() {
+            return None;
+        }
+
+        let len = self.rng.gen_range(1..=key_fields.len().min(3));
+        let mut conflict_fields: Vec<TableField> = Vec::with_capacity(len);
+        for _ in 0..len {
+            let idx = self.rng.gen_range(0..key_fields.len());
+            let field = &key_fields[idx];
+            if !conflict_fields.iter().any(|f| f.name() == field.name()) {
+                conflict_fields.push(field.clone());
+            }
+        }
+        Some(conflict_fields)
+    }
+
     fn random_select_field(&mut self, table: &Table) -> TableField {
         let field_index = self.rng.gen_range(0..table.schema.num_fields());
         table.schema.fields[field_index].clone()
@@ -596,3 +660,115 @@ impl<'a, R: Rng + 'a> SqlGenerator<'a, R> {
             .collect()
     }
 }
+
+fn is_conflict_key_type(ty: &DataType) -> bool {
+    matches!(
+        ty.remove_nullable(),
+        DataType::Boolean
+            | DataType::String
+            | DataType::Number(_)
+            | DataType::Decimal(_)
+            | DataType::Date
+            | DataType::Timestamp
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use databend_common_ast::ast::ColumnID;
+    use databend_common_ast::ast::ColumnRef;
+    use databend_common_ast::ast::Expr;
+    use databend_common_ast::ast::Identifier;
+    use databend_common_ast::ast::TableReference;
+    use databend_common_expression::types::DataType;
+    use databend_common_expression::types::NumberDataType;
+    use databend_common_expression::TableDataType;
+    use databend_common_expression::TableField;
+    use databend_common_expression::TableSchemaRef;
+    use databend_common_expression::TableSchemaRefExt;
+
+    use super::is_conflict_key_type;
+    use crate::sql_gen::test_generator;
+    use crate::sql_gen::Table;
+
+    #[test]
+    fn test_replace_on_conflict_columns() {
+        let mut generator = test_generator();
+        for _ in 0..100 {
+            let stmt = generator.gen_replace().unwrap();
+            let table = generator
+                .tables
+                .iter()
+                .find(|t| t.name.name == stmt.table.name)
+                .unwrap();
+            assert!(!stmt.on_conflict_columns.is_empty());
+            for column in &stmt.on_conflict_columns {
+                assert!(stmt.columns.iter().any(|c| c.name == column.name));
+                let field = table.schema.field_with_name(&column.name).unwrap();
+                assert!(is_conflict_key_type(&DataType::from(field.data_type())));
+            }
+            for column in &stmt.columns {
+                assert!(table.schema.field_with_name(&column.name).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_replace_without_conflict_columns() {
+        let mut generator = test_generator();
+        let schema = TableSchemaRefExt::create(vec![
+            TableField::new("c0", TableDataType::Variant),
+            TableField::new(
+                "c1",
+                TableDataType::Array(Box::new(TableDataType::Number(NumberDataType::Int32))),
+            ),
+        ]);
+        generator.tables = vec![Table::new(None, Identifier::from_name(None, "t2"), schema)];
+        for _ in 0..10 {
+            assert!(generator.gen_replace().is_none());
+        }
+    }
+
+    // the data type of the column if the expression is a column reference of the table
+    fn column_ref_type(schema: &TableSchemaRef, expr: &Expr) -> Option<DataType> {
+        match expr {
+            Expr::ColumnRef {
+                column:
+                    ColumnRef {
+                        column: ColumnID::Name(ident),
+                        ..
+                    },
+                ..
+            } => {
+                let field = schema.field_with_name(&ident.name).unwrap();
+                Some(DataType::from(field.data_type()))
+            }
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_update_assignments_and_predicate() {
+        let mut generator = test_generator();
+        let schema = generator.tables[0].schema.clone();
+        for _ in 0..100 {
+            let stmt = generator.gen_update();
+            let TableReference::Table { table, .. } = &stmt.table else {
+                panic!("expect table reference");
+            };
+            assert_eq!(table.name, "t1");
+
+            for update_expr in &stmt.update_list {
+                let field = schema.field_with_name(&update_expr.name.name).unwrap();
+                if let Some(ty) = column_ref_type(&schema, &update_expr.expr) {
+                    assert_eq!(ty, DataType::from(field.data_type()));
+                }
+            }
+            if let Some(selection) = &stmt.selection {
+                if let Some(ty) = column_ref_type(&schema, selection) {
+                    assert_eq!(ty, DataType::Boolean);
+                }
+            }
+        }
+    }
+}
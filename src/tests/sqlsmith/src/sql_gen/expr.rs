@@ -35,16 +35,57 @@ use rand::Rng;
 
 use crate::sql_gen::SqlGenerator;
 
+// All the units supported by EXTRACT and DATE_PART.
+const DATE_PART_UNITS: &[IntervalKind] = &[
+    IntervalKind::Year,
+    IntervalKind::Quarter,
+    IntervalKind::Month,
+    IntervalKind::Week,
+    IntervalKind::Day,
+    IntervalKind::Hour,
+    IntervalKind::Minute,
+    IntervalKind::Second,
+    IntervalKind::Doy,
+    IntervalKind::Dow,
+];
+
+// The maximum nesting depth of the generated CASE expressions.
+const MAX_CASE_DEPTH: usize = 3;
+
+// The operands of the arithmetic generated to compare the constant folding
+// with the evaluation at runtime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FoldOperands {
+    // only literals, like `1 + 2 * 3`, folded into a constant
+    Constant,
+    // only columns, like `c0 + c1 * c2`, evaluated at runtime
+    Runtime,
+    // a column side and a literal side, like `c0 + 2 * 3`, partially folded
+    Mixed,
+}
+
 impl<R: Rng> SqlGenerator<'_, R> {
     pub(crate) fn gen_expr(&mut self, ty: &DataType) -> Expr {
         // avoid generate too complex expression
         if self.expr_depth == 0 {
             // reset `expr_depth` for generate next expression
-            self.expr_depth = 2;
+            self.expr_depth = self.max_expr_depth;
             return self.gen_simple_expr(ty);
         }
         self.expr_depth -= 1;
 
+        // only generate scalar functions in the allowed list,
+        // other function calls are skipped.
+        if self.allowed_funcs.is_some() {
+            self.only_scalar_expr = false;
+            return match self.rng.gen_range(0..=9) {
+                0..=3 => self.gen_simple_expr(ty),
+                4..=8 => self.gen_scalar_func(ty),
+                9 => self.gen_cast_expr(ty),
+                _ => unreachable!(),
+            };
+        }
+
         // only column, scalar value and scalar functions
         // not generate aggregate, window and lambda functions
         if self.only_scalar_expr {
@@ -79,7 +120,80 @@ impl<R: Rng> SqlGenerator<'_, R> {
         }
     }
 
-    fn gen_column(&mut self, ty: &DataType) -> Expr {
+    // EXTRACT(unit FROM expr) or DATE_PART(unit, expr)
+    pub(crate) fn gen_date_part(&mut self, expr: Expr) -> Expr {
+        let kind = DATE_PART_UNITS[self.rng.gen_range(0..DATE_PART_UNITS.len())];
+        if self.rng.gen_bool(0.5) {
+            Expr::Extract {
+                span: None,
+                kind,
+                expr: Box::new(expr),
+            }
+        } else {
+            Expr::DatePart {
+                span: None,
+                kind,
+                expr: Box::new(expr),
+            }
+        }
+    }
+
+    // SUBSTRING(expr FROM expr [FOR expr])
+    pub(crate) fn gen_substring(&mut self) -> Expr {
+        let expr_ty = DataType::String;
+        let from_ty = DataType::Number(NumberDataType::Int64);
+
+        let expr = self.gen_expr(&expr_ty);
+        let from_expr = self.gen_expr(&from_ty);
+        let for_expr = if self.rng.gen_bool(0.5) {
+            Some(Box::new(self.gen_expr(&from_ty)))
+        } else {
+            None
+        };
+        Expr::Substring {
+            span: None,
+            expr: Box::new(expr),
+            substring_from: Box::new(from_expr),
+            substring_for: for_expr,
+        }
+    }
+
+    // TRIM([BOTH | LEADING | TRAILING] expr FROM expr) or TRIM(expr)
+    pub(crate) fn gen_trim(&mut self) -> Expr {
+        let expr_ty = DataType::String;
+        let expr = self.gen_expr(&expr_ty);
+        let trim_where_expr = if self.rng.gen_bool(0.5) {
+            let trim_where = match self.rng.gen_range(0..=2) {
+                0 => TrimWhere::Both,
+                1 => TrimWhere::Leading,
+                2 => TrimWhere::Trailing,
+                _ => unreachable!(),
+            };
+            let where_expr = self.gen_expr(&expr_ty);
+            Some((trim_where, Box::new(where_expr)))
+        } else {
+            None
+        };
+        Expr::Trim {
+            span: None,
+            expr: Box::new(expr),
+            trim_where: trim_where_expr,
+        }
+    }
+
+    // POSITION(expr IN expr)
+    pub(crate) fn gen_position(&mut self) -> Expr {
+        let expr_ty = DataType::String;
+        let substr_expr = self.gen_expr(&expr_ty);
+        let str_expr = self.gen_expr(&expr_ty);
+        Expr::Position {
+            span: None,
+            substr_expr: Box::new(substr_expr),
+            str_expr: Box::new(str_expr),
+        }
+    }
+
+    pub(crate) fn gen_column(&mut self, ty: &DataType) -> Expr {
         for bound_column in &self.bound_columns {
             if bound_column.data_type == *ty {
                 let column = if bound_column.table_name.is_some() && self.rng.gen_bool(0.2) {
@@ -315,10 +429,8 @@ impl<R: Rng> SqlGenerator<'_, R> {
             DataType::Null => Literal::Null,
             DataType::Boolean => Literal::Boolean(self.rng.gen_bool(0.5)),
             DataType::String => Literal::String(
-                rand::thread_rng()
-                    .sample_iter(&Alphanumeric)
-                    .take(7)
-                    .map(char::from)
+                (0..7)
+                    .map(|_| char::from(self.rng.sample(Alphanumeric)))
                     .collect::<String>(),
             ),
             DataType::Number(num_type) => match num_type {
@@ -356,17 +468,18 @@ impl<R: Rng> SqlGenerator<'_, R> {
     fn gen_identifier(&mut self) -> Identifier {
         Identifier::from_name(
             None,
-            rand::thread_rng()
-                .sample_iter(&Alphanumeric)
-                .take(5)
-                .map(char::from)
+            (0..5)
+                .map(|_| char::from(self.rng.sample(Alphanumeric)))
                 .collect::<String>(),
         )
     }
 
-    fn gen_other_expr(&mut self, ty: &DataType) -> Expr {
+    pub(crate) fn gen_other_expr(&mut self, ty: &DataType) -> Expr {
+        if self.rng.gen_bool(0.1) {
+            return self.gen_conditional(ty);
+        }
         match ty.remove_nullable() {
-            DataType::Boolean => match self.rng.gen_range(0..=9) {
+            DataType::Boolean => match self.rng.gen_range(0..=11) {
                 0 => {
                     let inner_ty = self.gen_data_type();
                     Expr::IsNull {
@@ -376,14 +489,8 @@ impl<R: Rng> SqlGenerator<'_, R> {
                     }
                 }
                 1 => {
-                    let left_ty = self.gen_data_type();
-                    let right_ty = self.gen_data_type();
-                    Expr::IsDistinctFrom {
-                        span: None,
-                        left: Box::new(self.gen_expr(&left_ty)),
-                        right: Box::new(self.gen_expr(&right_ty)),
-                        not: self.rng.gen_bool(0.5),
-                    }
+                    let operand_ty = self.gen_data_type();
+                    self.gen_is_distinct_from(&operand_ty)
                 }
                 2 => {
                     let expr_ty = self.gen_data_type();
@@ -411,7 +518,15 @@ impl<R: Rng> SqlGenerator<'_, R> {
                 4..=6 => self.gen_binary_expr(),
                 7 => {
                     let not = self.rng.gen_bool(0.5);
-                    let (subquery, _) = self.gen_subquery(false);
+                    let correlated = if self.rng.gen_bool(0.5) {
+                        self.gen_correlated_exists_subquery()
+                    } else {
+                        None
+                    };
+                    let subquery = match correlated {
+                        Some(subquery) => subquery,
+                        None => self.gen_subquery(false).0,
+                    };
                     Expr::Exists {
                         span: None,
                         not,
@@ -445,49 +560,25 @@ impl<R: Rng> SqlGenerator<'_, R> {
                         not,
                     }
                 }
+                10 => self.gen_tuple_in_list(),
+                11 => {
+                    let operand_ty = if self.flip_coin() {
+                        DataType::Boolean
+                    } else {
+                        DataType::Nullable(Box::new(DataType::Boolean))
+                    };
+                    self.gen_truth_test(&operand_ty)
+                }
                 _ => unreachable!(),
             },
             DataType::String => {
                 if self.rng.gen_bool(0.5) {
-                    let expr_ty = DataType::String;
-                    let from_ty = DataType::Number(NumberDataType::Int64);
-
-                    let expr = self.gen_expr(&expr_ty);
-                    let from_expr = self.gen_expr(&from_ty);
-                    let for_expr = if self.rng.gen_bool(0.5) {
-                        Some(Box::new(self.gen_expr(&from_ty)))
-                    } else {
-                        None
-                    };
-                    Expr::Substring {
-                        span: None,
-                        expr: Box::new(expr),
-                        substring_from: Box::new(from_expr),
-                        substring_for: for_expr,
-                    }
+                    self.gen_substring()
                 } else {
-                    let expr_ty = DataType::String;
-                    let expr = self.gen_expr(&expr_ty);
-                    let trim_where_expr = if self.rng.gen_bool(0.5) {
-                        let trim_where = match self.rng.gen_range(0..=2) {
-                            0 => TrimWhere::Both,
-                            1 => TrimWhere::Leading,
-                            2 => TrimWhere::Trailing,
-                            _ => unreachable!(),
-                        };
-                        let where_expr = self.gen_expr(&expr_ty);
-                        Some((trim_where, Box::new(where_expr)))
-                    } else {
-                        None
-                    };
-                    Expr::Trim {
-                        span: None,
-                        expr: Box::new(expr),
-                        trim_where: trim_where_expr,
-                    }
+                    self.gen_trim()
                 }
             }
-            DataType::Number(_) => match self.rng.gen_range(0..=3) {
+            DataType::Number(_) => match self.rng.gen_range(0..=5) {
                 0 => {
                     let expr_ty = if self.rng.gen_bool(0.5) {
                         DataType::Date
@@ -495,35 +586,9 @@ impl<R: Rng> SqlGenerator<'_, R> {
                         DataType::Timestamp
                     };
                     let expr = self.gen_expr(&expr_ty);
-                    let kind = match self.rng.gen_range(0..=9) {
-                        0 => IntervalKind::Year,
-                        1 => IntervalKind::Quarter,
-                        2 => IntervalKind::Month,
-                        3 => IntervalKind::Day,
-                        4 => IntervalKind::Hour,
-                        5 => IntervalKind::Minute,
-                        6 => IntervalKind::Second,
-                        7 => IntervalKind::Doy,
-                        8 => IntervalKind::Dow,
-                        9 => IntervalKind::Week,
-                        _ => unreachable!(),
-                    };
-                    Expr::Extract {
-                        span: None,
-                        kind,
-                        expr: Box::new(expr),
-                    }
-                }
-                1 => {
-                    let expr_ty = DataType::String;
-                    let substr_expr = self.gen_expr(&expr_ty);
-                    let str_expr = self.gen_expr(&expr_ty);
-                    Expr::Position {
-                        span: None,
-                        substr_expr: Box::new(substr_expr),
-                        str_expr: Box::new(str_expr),
-                    }
+                    self.gen_date_part(expr)
                 }
+                1 => self.gen_position(),
                 2 => Expr::CountAll {
                     span: None,
                     window: None,
@@ -548,6 +613,16 @@ impl<R: Rng> SqlGenerator<'_, R> {
                         expr: Box::new(expr),
                     }
                 }
+                4 => self.gen_mixed_arithmetic(),
+                5 => {
+                    let operands = match self.rng.gen_range(0..=2) {
+                        0 => FoldOperands::Constant,
+                        1 => FoldOperands::Runtime,
+                        2 => FoldOperands::Mixed,
+                        _ => unreachable!(),
+                    };
+                    self.gen_fold_arithmetic(ty, operands)
+                }
                 _ => unreachable!(),
             },
             DataType::Date | DataType::Timestamp => {
@@ -670,26 +745,7 @@ impl<R: Rng> SqlGenerator<'_, R> {
             }
             _ => {
                 if self.rng.gen_bool(0.3) {
-                    let cond_ty = DataType::Boolean;
-                    let len = self.rng.gen_range(1..=3);
-                    let mut conditions = Vec::with_capacity(len);
-                    let mut results = Vec::with_capacity(len);
-                    for _ in 0..len {
-                        conditions.push(self.gen_expr(&cond_ty));
-                        results.push(self.gen_expr(ty));
-                    }
-                    let else_result = if self.rng.gen_bool(0.5) {
-                        Some(Box::new(self.gen_expr(ty)))
-                    } else {
-                        None
-                    };
-                    Expr::Case {
-                        span: None,
-                        operand: None,
-                        conditions,
-                        results,
-                        else_result,
-                    }
+                    self.gen_conditional(ty)
                 } else {
                     // no suitable expr exist, generate scalar value instead
                     self.gen_scalar_value(ty)
@@ -698,6 +754,254 @@ impl<R: Rng> SqlGenerator<'_, R> {
         }
     }
 
+    // CASE, COALESCE, NULLIF, IFNULL or NVL, the common type of all the branches is `ty`.
+    pub(crate) fn gen_conditional(&mut self, ty: &DataType) -> Expr {
+        let (name, args_type) = match self.rng.gen_range(0..=3) {
+            0 if self.case_depth < MAX_CASE_DEPTH => return self.gen_case(ty),
+            // `NULLIF` returns NULL if the arguments are equal
+            1 if ty.is_nullable() => ("nullif", vec![ty.clone(); 2]),
+            2 => {
+                let len = self.rng.gen_range(2..=4);
+                ("coalesce", self.gen_coalesce_args_type(ty, len))
+            }
+            _ => {
+                let name = if self.flip_coin() { "ifnull" } else { "nvl" };
+                (name, self.gen_coalesce_args_type(ty, 2))
+            }
+        };
+        let args = args_type
+            .iter()
+            .map(|arg_ty| self.gen_expr(arg_ty))
+            .collect::<Vec<_>>();
+        Expr::FunctionCall {
+            span: None,
+            func: FunctionCall {
+                distinct: false,
+                name: Identifier::from_name(None, name),
+                args,
+                params: vec![],
+                window: None,
+                lambda: None,
+            },
+        }
+    }
+
+    // The arguments of a nullable type mix nullable and not null values,
+    // at least one of them is nullable to keep the common type.
+    pub(crate) fn gen_coalesce_args_type(&mut self, ty: &DataType, len: usize) -> Vec<DataType> {
+        if !ty.is_nullable() {
+            return vec![ty.clone(); len];
+        }
+        let nullable_idx = self.rng.gen_range(0..len);
+        (0..len)
+            .map(|i| {
+                if i == nullable_idx || self.flip_coin() {
+                    ty.clone()
+                } else {
+                    ty.remove_nullable()
+                }
+            })
+            .collect()
+    }
+
+    // Searched CASE or simple CASE, the results may be nested CASE
+    // until the depth reaches `MAX_CASE_DEPTH`.
+    pub(crate) fn gen_case(&mut self, ty: &DataType) -> Expr {
+        self.case_depth += 1;
+
+        // the simple form `CASE operand WHEN value` or the searched form `CASE WHEN cond`
+        let (operand, operand_ty) = if self.flip_coin() {
+            let operand_ty = self.gen_case_operand_type();
+            let operand = self.gen_expr(&operand_ty);
+            (Some(Box::new(operand)), Some(operand_ty))
+        } else {
+            (None, None)
+        };
+        let len = self.rng.gen_range(1..=3);
+        let mut conditions = Vec::with_capacity(len);
+        let mut results = Vec::with_capacity(len);
+        for _ in 0..len {
+            let condition = match &operand_ty {
+                Some(operand_ty) => {
+                    let value_ty = self.gen_case_value_type(operand_ty);
+                    if self.rng.gen_bool(0.8) {
+                        self.gen_scalar_value(&value_ty)
+                    } else {
+                        self.gen_expr(&value_ty)
+                    }
+                }
+                None => self.gen_expr(&DataType::Boolean),
+            };
+            conditions.push(condition);
+            results.push(self.gen_case_result(ty));
+        }
+        let else_result = if self.rng.gen_bool(0.5) {
+            Some(Box::new(self.gen_case_result(ty)))
+        } else {
+            None
+        };
+
+        self.case_depth -= 1;
+        Expr::Case {
+            span: None,
+            operand,
+            conditions,
+            results,
+            else_result,
+        }
+    }
+
+    // The operand of the simple form is compared with the WHEN values by equality,
+    // so only the types with an equality are generated.
+    fn gen_case_operand_type(&mut self) -> DataType {
+        let ty = match self.rng.gen_range(0..=5) {
+            0 => DataType::Boolean,
+            1 => DataType::String,
+            2..=3 => self.gen_all_number_data_type(),
+            4 => DataType::Date,
+            5 => DataType::Timestamp,
+            _ => unreachable!(),
+        };
+        if self.rng.gen_bool(0.2) {
+            ty.wrap_nullable()
+        } else {
+            ty
+        }
+    }
+
+    // The type of a WHEN value comparable with the operand, the numbers of another type
+    // are compared in the common super type of both.
+    pub(crate) fn gen_case_value_type(&mut self, operand_ty: &DataType) -> DataType {
+        let ty = match operand_ty.remove_nullable() {
+            DataType::Number(_) | DataType::Decimal(_) if self.flip_coin() => {
+                self.gen_all_number_data_type()
+            }
+            ty => ty,
+        };
+        if self.rng.gen_bool(0.2) {
+            ty.wrap_nullable()
+        } else {
+            ty
+        }
+    }
+
+    fn gen_case_result(&mut self, ty: &DataType) -> Expr {
+        if self.case_depth < MAX_CASE_DEPTH && self.rng.gen_bool(0.3) {
+            self.gen_case(ty)
+        } else {
+            self.gen_expr(ty)
+        }
+    }
+
+    // `IS [NOT] DISTINCT FROM` treats NULLs as comparable values,
+    // `IS NOT DISTINCT FROM` is the null-safe equality.
+    pub(crate) fn gen_is_distinct_from(&mut self, operand_ty: &DataType) -> Expr {
+        let (left_ty, right_ty) = if operand_ty.is_nullable() || self.rng.gen_bool(0.5) {
+            (operand_ty.clone(), operand_ty.clone())
+        } else {
+            // compare a nullable operand with a not null operand
+            let nullable_ty = operand_ty.wrap_nullable();
+            if self.flip_coin() {
+                (nullable_ty, operand_ty.clone())
+            } else {
+                (operand_ty.clone(), nullable_ty)
+            }
+        };
+        Expr::IsDistinctFrom {
+            span: None,
+            left: Box::new(self.gen_expr(&left_ty)),
+            right: Box::new(self.gen_expr(&right_ty)),
+            not: self.rng.gen_bool(0.5),
+        }
+    }
+
+    // The three-valued logic predicates `x IS [NOT] TRUE`, `x IS [NOT] FALSE` and `x IS [NOT] UNKNOWN`,
+    // which never return NULL, unlike `x = TRUE`. The parser doesn't support them,
+    // generate the equivalent `is_true(x)`, `is_true(NOT x)` and `x IS NULL` instead.
+    pub(crate) fn gen_truth_test(&mut self, operand_ty: &DataType) -> Expr {
+        debug_assert_eq!(operand_ty.remove_nullable(), DataType::Boolean);
+        let operand = self.gen_expr(operand_ty);
+        let not = |expr: Expr| Expr::UnaryOp {
+            span: None,
+            op: UnaryOperator::Not,
+            expr: Box::new(expr),
+        };
+        let is_true = |expr: Expr| Expr::FunctionCall {
+            span: None,
+            func: FunctionCall {
+                distinct: false,
+                name: Identifier::from_name(None, "is_true"),
+                args: vec![expr],
+                params: vec![],
+                window: None,
+                lambda: None,
+            },
+        };
+        match self.rng.gen_range(0..=5) {
+            0 => is_true(operand),
+            1 => not(is_true(operand)),
+            2 => is_true(not(operand)),
+            3 => not(is_true(not(operand))),
+            4 => Expr::IsNull {
+                span: None,
+                expr: Box::new(operand),
+                not: false,
+            },
+            5 => Expr::IsNull {
+                span: None,
+                expr: Box::new(operand),
+                not: true,
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    // row constructor IN predicate, like `(a, b) IN ((1, 2), (3, 4))`
+    pub(crate) fn gen_tuple_in_list(&mut self) -> Expr {
+        let arity = self.rng.gen_range(2..=4);
+        let mut tys = Vec::with_capacity(arity);
+        let mut exprs = Vec::with_capacity(arity);
+        for _ in 0..arity {
+            let ty = self.gen_simple_data_type();
+            exprs.push(self.gen_simple_expr(&ty));
+            tys.push(ty);
+        }
+
+        // single row list is an edge case
+        let len = if self.rng.gen_bool(0.2) {
+            1
+        } else {
+            self.rng.gen_range(2..=5)
+        };
+        let mut list = Vec::with_capacity(len);
+        for _ in 0..len {
+            let mut row_tys = tys.clone();
+            if self.gen_error_cases && self.rng.gen_bool(0.2) {
+                // mismatched arity
+                if self.flip_coin() {
+                    row_tys.pop();
+                } else {
+                    row_tys.push(self.gen_simple_data_type());
+                }
+            }
+            let row_exprs = row_tys
+                .iter()
+                .map(|ty| self.gen_scalar_value(ty))
+                .collect::<Vec<_>>();
+            list.push(Expr::Tuple {
+                span: None,
+                exprs: row_exprs,
+            });
+        }
+
+        Expr::InList {
+            span: None,
+            expr: Box::new(Expr::Tuple { span: None, exprs }),
+            list,
+            not: self.rng.gen_bool(0.5),
+        }
+    }
+
     pub(crate) fn gen_point(&mut self) -> String {
         let x: f64 = self.rng.gen_range(-1.7e10..=1.7e10);
         let y: f64 = self.rng.gen_range(-1.7e10..=1.7e10);
@@ -799,6 +1103,9 @@ impl<R: Rng> SqlGenerator<'_, R> {
     }
 
     pub(crate) fn gen_binary_expr(&mut self) -> Expr {
+        if self.gen_error_cases && self.rng.gen_bool(0.2) {
+            return self.gen_short_circuit_chain();
+        }
         let (op, left, right) = match self.rng.gen_range(0..=3) {
             0..=1 => {
                 let inner_ty = self.gen_simple_data_type();
@@ -841,6 +1148,225 @@ impl<R: Rng> SqlGenerator<'_, R> {
         }
     }
 
+    // Arithmetic like `CAST(a AS Int8) + CAST(b AS UInt64)`, the operands are casted to
+    // different number types to check the type promotion of the result.
+    pub(crate) fn gen_mixed_arithmetic(&mut self) -> Expr {
+        let (left_ty, right_ty) = self.gen_mixed_number_types();
+        let op = match self.rng.gen_range(0..=5) {
+            0 => BinaryOperator::Plus,
+            1 => BinaryOperator::Minus,
+            2 => BinaryOperator::Multiply,
+            3 => BinaryOperator::Divide,
+            4 => BinaryOperator::IntDiv,
+            5 => BinaryOperator::Modulo,
+            _ => unreachable!(),
+        };
+        self.expr_depth = 0;
+        let left = self.gen_expr(&left_ty);
+        self.expr_depth = 1;
+        let right = self.gen_expr(&right_ty);
+        Expr::BinaryOp {
+            span: None,
+            op,
+            left: Box::new(Expr::Cast {
+                span: None,
+                expr: Box::new(left),
+                target_type: convert_to_type_name(&left_ty),
+                pg_style: self.rng.gen_bool(0.5),
+            }),
+            right: Box::new(Expr::Cast {
+                span: None,
+                expr: Box::new(right),
+                target_type: convert_to_type_name(&right_ty),
+                pg_style: self.rng.gen_bool(0.5),
+            }),
+        }
+    }
+
+    // An expression without any column reference, which is folded into a constant.
+    pub(crate) fn gen_constant_expr(&mut self, ty: &DataType) -> Expr {
+        match ty.remove_nullable() {
+            DataType::Number(_) => self.gen_fold_arithmetic(ty, FoldOperands::Constant),
+            DataType::Boolean => Expr::BinaryOp {
+                span: None,
+                op: BinaryOperator::Lt,
+                left: Box::new(self.gen_fold_operand(FoldOperands::Constant, 2)),
+                right: Box::new(self.gen_fold_operand(FoldOperands::Constant, 2)),
+            },
+            _ => self.gen_scalar_value(ty),
+        }
+    }
+
+    // Arithmetic of the number type whose operands are literals, columns or both.
+    pub(crate) fn gen_fold_arithmetic(&mut self, ty: &DataType, operands: FoldOperands) -> Expr {
+        let depth = self.rng.gen_range(1..=3);
+        let expr = self.gen_fold_operand(operands, depth);
+        Expr::Cast {
+            span: None,
+            expr: Box::new(expr),
+            target_type: convert_to_type_name(&ty.remove_nullable()),
+            pg_style: self.rng.gen_bool(0.5),
+        }
+    }
+
+    fn gen_fold_operand(&mut self, operands: FoldOperands, depth: usize) -> Expr {
+        if depth == 0 {
+            return match operands {
+                FoldOperands::Runtime => self.gen_number_column(),
+                _ => Expr::Literal {
+                    span: None,
+                    value: Literal::UInt64(self.rng.gen_range(0..=100)),
+                },
+            };
+        }
+        let op = match self.rng.gen_range(0..=2) {
+            0 => BinaryOperator::Plus,
+            1 => BinaryOperator::Minus,
+            2 => BinaryOperator::Multiply,
+            _ => unreachable!(),
+        };
+        let (left, right) = match operands {
+            FoldOperands::Mixed => (FoldOperands::Runtime, FoldOperands::Constant),
+            _ => (operands, operands),
+        };
+        Expr::BinaryOp {
+            span: None,
+            op,
+            left: Box::new(self.gen_fold_operand(left, depth - 1)),
+            right: Box::new(self.gen_fold_operand(right, depth - 1)),
+        }
+    }
+
+    // A column of any number type, or a literal if there is no such column.
+    fn gen_number_column(&mut self) -> Expr {
+        let number_types = self
+            .bound_columns
+            .iter()
+            .filter(|column| matches!(column.data_type.remove_nullable(), DataType::Number(_)))
+            .map(|column| column.data_type.clone())
+            .collect::<Vec<_>>();
+        if number_types.is_empty() {
+            return self.gen_scalar_value(&DataType::Number(NumberDataType::Int64));
+        }
+        let ty = number_types[self.rng.gen_range(0..number_types.len())].clone();
+        self.gen_column(&ty)
+    }
+
+    // Two number types of different widths, mostly integers, the signed and unsigned
+    // pairs whose common type needs a wider integer are more likely.
+    fn gen_mixed_number_types(&mut self) -> (DataType, DataType) {
+        let (left, right) = match self.rng.gen_range(0..=9) {
+            0 => (NumberDataType::Int8, NumberDataType::UInt64),
+            1 => (NumberDataType::UInt32, NumberDataType::Int16),
+            2 => (NumberDataType::Int64, NumberDataType::UInt64),
+            3 => (NumberDataType::UInt8, NumberDataType::Int8),
+            4 => (NumberDataType::UInt16, NumberDataType::Int32),
+            5 => {
+                let int_ty = self.gen_integer_type();
+                let float_ty = if self.flip_coin() {
+                    NumberDataType::Float32
+                } else {
+                    NumberDataType::Float64
+                };
+                (int_ty, float_ty)
+            }
+            6..=9 => {
+                let left = self.gen_integer_type();
+                let mut right = self.gen_integer_type();
+                while right == left {
+                    right = self.gen_integer_type();
+                }
+                (left, right)
+            }
+            _ => unreachable!(),
+        };
+        let (left, right) = if self.flip_coin() {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        (DataType::Number(left), DataType::Number(right))
+    }
+
+    fn gen_integer_type(&mut self) -> NumberDataType {
+        match self.rng.gen_range(0..=7) {
+            0 => NumberDataType::UInt8,
+            1 => NumberDataType::UInt16,
+            2 => NumberDataType::UInt32,
+            3 => NumberDataType::UInt64,
+            4 => NumberDataType::Int8,
+            5 => NumberDataType::Int16,
+            6 => NumberDataType::Int32,
+            7 => NumberDataType::Int64,
+            _ => unreachable!(),
+        }
+    }
+
+    // `AND`/`OR` chain like `NULL AND divide(c0, 0) > 0`, the left operands are often NULL
+    // or short-circuit the chain, and the right operands may fail if they are evaluated,
+    // to check the evaluation order of the engine.
+    pub(crate) fn gen_short_circuit_chain(&mut self) -> Expr {
+        let op = if self.flip_coin() {
+            BinaryOperator::And
+        } else {
+            BinaryOperator::Or
+        };
+        let mut expr = match self.rng.gen_range(0..=2) {
+            // `FALSE AND ...` and `TRUE OR ...` skip the right operands
+            0 => Expr::Literal {
+                span: None,
+                value: Literal::Boolean(op == BinaryOperator::Or),
+            },
+            1 => Expr::Literal {
+                span: None,
+                value: Literal::Null,
+            },
+            2 => self.gen_expr(&DataType::Boolean),
+            _ => unreachable!(),
+        };
+        let len = self.rng.gen_range(1..=3);
+        for _ in 0..len {
+            let right = if self.flip_coin() {
+                self.gen_erroring_predicate()
+            } else {
+                self.gen_expr(&DataType::Boolean)
+            };
+            expr = Expr::BinaryOp {
+                span: None,
+                op: op.clone(),
+                left: Box::new(expr),
+                right: Box::new(right),
+            };
+        }
+        expr
+    }
+
+    // `divide(x, 0) > 0` fails with division by zero if it is evaluated.
+    pub(crate) fn gen_erroring_predicate(&mut self) -> Expr {
+        let arg_ty = DataType::Number(NumberDataType::Int64);
+        let arg = self.gen_expr(&arg_ty);
+        let zero = Expr::Literal {
+            span: None,
+            value: Literal::UInt64(0),
+        };
+        Expr::BinaryOp {
+            span: None,
+            op: BinaryOperator::Gt,
+            left: Box::new(Expr::FunctionCall {
+                span: None,
+                func: FunctionCall {
+                    distinct: false,
+                    name: Identifier::from_name(None, "divide"),
+                    args: vec![arg, zero.clone()],
+                    params: vec![],
+                    window: None,
+                    lambda: None,
+                },
+            }),
+            right: Box::new(zero),
+        }
+    }
+
     fn gen_cast_expr(&mut self, ty: &DataType) -> Expr {
         // can't cast to nested types
         if matches!(
@@ -856,6 +1382,10 @@ impl<R: Rng> SqlGenerator<'_, R> {
             return self.gen_other_expr(ty);
         }
 
+        if self.gen_error_cases && self.rng.gen_bool(0.2) {
+            return self.gen_nullable_cast_chain(ty);
+        }
+
         let source_type = self.gen_data_type();
         let source_expr = self.gen_expr(&source_type);
         let target_type = convert_to_type_name(ty);
@@ -875,6 +1405,25 @@ impl<R: Rng> SqlGenerator<'_, R> {
             }
         }
     }
+
+    // cast chain like `CAST(CAST(x AS NULLABLE(INT)) AS INT)`,
+    // casting NULL values to a non-nullable type fails at runtime.
+    fn gen_nullable_cast_chain(&mut self, ty: &DataType) -> Expr {
+        let inner_ty = ty.remove_nullable();
+        let source_expr = self.gen_expr(&inner_ty.wrap_nullable());
+        let nullable_expr = Expr::Cast {
+            span: None,
+            expr: Box::new(source_expr),
+            target_type: TypeName::Nullable(Box::new(convert_to_type_name(&inner_ty))),
+            pg_style: self.rng.gen_bool(0.5),
+        };
+        Expr::Cast {
+            span: None,
+            expr: Box::new(nullable_expr),
+            target_type: convert_to_type_name(ty),
+            pg_style: self.rng.gen_bool(0.5),
+        }
+    }
 }
 
 fn convert_to_type_name(ty: &DataType) -> TypeName {
@@ -926,3 +1475,485 @@ fn convert_to_type_name(ty: &DataType) -> TypeName {
         _ => TypeName::String,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use databend_common_ast::ast::BinaryOperator;
+    use databend_common_ast::ast::Expr;
+    use databend_common_ast::ast::FunctionCall;
+    use databend_common_ast::ast::Literal;
+    use databend_common_ast::ast::TypeName;
+    use databend_common_ast::parser::parse_expr;
+    use databend_common_ast::parser::tokenize_sql;
+    use databend_common_ast::parser::Dialect;
+    use databend_common_expression::type_check::common_super_type;
+    use databend_common_expression::types::DataType;
+    use databend_common_expression::types::NumberDataType;
+    use databend_common_functions::BUILTIN_FUNCTIONS;
+    use derive_visitor::Drive;
+    use derive_visitor::Visitor;
+    use rand::Rng;
+
+    use super::FoldOperands;
+    use super::DATE_PART_UNITS;
+    use super::MAX_CASE_DEPTH;
+    use crate::sql_gen::mock_tables;
+    use crate::sql_gen::test_generator;
+
+    #[test]
+    fn test_tuple_in_list_arity() {
+        let mut generator = test_generator();
+        for _ in 0..100 {
+            let Expr::InList {
+                expr: box Expr::Tuple { exprs, .. },
+                list,
+                ..
+            } = generator.gen_tuple_in_list()
+            else {
+                panic!("expect tuple in list");
+            };
+            assert!(!list.is_empty());
+            for row in list {
+                let Expr::Tuple {
+                    exprs: row_exprs, ..
+                } = row
+                else {
+                    panic!("expect tuple");
+                };
+                assert_eq!(exprs.len(), row_exprs.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_distinct_from_operand_types() {
+        let mut generator = test_generator();
+        let table = mock_tables().remove(0);
+        generator.bound_table(table);
+        let ty = DataType::Number(NumberDataType::Int64);
+        for _ in 0..100 {
+            let Expr::IsDistinctFrom { left, right, .. } = generator.gen_is_distinct_from(&ty)
+            else {
+                panic!("expect is distinct from");
+            };
+            // the only column of the operand type is `c0` at position 1
+            for operand in [left, right] {
+                if let Expr::ColumnRef { column, .. } = *operand {
+                    assert!(["c0", "$1"].contains(&column.column.name()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_date_part_units() {
+        let mut generator = test_generator();
+        generator.bound_table(mock_tables().remove(0));
+
+        let mut kinds = HashSet::new();
+        let (mut num_extract, mut num_date_part) = (0, 0);
+        for _ in 0..200 {
+            let column = generator.gen_column(&DataType::Timestamp);
+            let (kind, expr) = match generator.gen_date_part(column.clone()) {
+                Expr::Extract { kind, expr, .. } => {
+                    num_extract += 1;
+                    (kind, expr)
+                }
+                Expr::DatePart { kind, expr, .. } => {
+                    num_date_part += 1;
+                    (kind, expr)
+                }
+                expr => panic!("unexpected expr {}", expr),
+            };
+            assert_eq!(*expr, column);
+            assert!(DATE_PART_UNITS.contains(&kind));
+            kinds.insert(kind.to_string());
+        }
+        assert!(num_extract > 0 && num_date_part > 0);
+        assert_eq!(kinds.len(), DATE_PART_UNITS.len());
+    }
+
+    #[test]
+    fn test_string_special_forms() {
+        let mut generator = test_generator();
+        generator.bound_table(mock_tables().remove(0));
+
+        let parse = |sql: &str| {
+            let tokens = tokenize_sql(sql).ok()?;
+            parse_expr(&tokens, Dialect::PostgreSQL).ok()
+        };
+
+        let mut num_parsed = [0; 3];
+        for _ in 0..100 {
+            let exprs = [
+                generator.gen_substring(),
+                generator.gen_trim(),
+                generator.gen_position(),
+            ];
+            for (i, expr) in exprs.into_iter().enumerate() {
+                let sql = expr.to_string();
+                // the operands are random expressions, which may be unparsable on their own
+                let Some(parsed) = parse(&sql) else {
+                    continue;
+                };
+                match (i, &parsed) {
+                    (0, Expr::Substring { .. })
+                    | (1, Expr::Trim { .. })
+                    | (2, Expr::Position { .. }) => {}
+                    _ => panic!("unexpected expr {} parsed from {}", parsed, sql),
+                }
+                assert_eq!(parsed.to_string(), sql);
+                num_parsed[i] += 1;
+            }
+        }
+        assert!(num_parsed.iter().all(|n| *n > 0));
+    }
+
+    #[derive(Visitor)]
+    #[visitor(Expr(enter, exit))]
+    struct CaseDepthVisitor {
+        depth: usize,
+        max_depth: usize,
+    }
+
+    impl CaseDepthVisitor {
+        fn enter_expr(&mut self, expr: &Expr) {
+            if matches!(expr, Expr::Case { .. }) {
+                self.depth += 1;
+                self.max_depth = self.max_depth.max(self.depth);
+            }
+        }
+
+        fn exit_expr(&mut self, expr: &Expr) {
+            if matches!(expr, Expr::Case { .. }) {
+                self.depth -= 1;
+            }
+        }
+    }
+
+    #[test]
+    fn test_conditional_branch_types() {
+        let mut generator = test_generator();
+        generator.bound_table(mock_tables().remove(0));
+
+        let int_ty = DataType::Number(NumberDataType::Int64);
+        let mut visitor = CaseDepthVisitor {
+            depth: 0,
+            max_depth: 0,
+        };
+        let mut names = HashSet::new();
+        for ty in [int_ty.clone(), int_ty.wrap_nullable(), DataType::String] {
+            for _ in 0..100 {
+                let len = generator.rng.gen_range(1..=4);
+                let mut args_type = generator.gen_coalesce_args_type(&ty, len).into_iter();
+                let first_ty = args_type.next().unwrap();
+                let common_ty = args_type.try_fold(first_ty, |acc, arg_ty| {
+                    common_super_type(acc, arg_ty, &BUILTIN_FUNCTIONS.default_cast_rules)
+                });
+                assert_eq!(common_ty, Some(ty.clone()));
+
+                let expr = generator.gen_conditional(&ty);
+                match &expr {
+                    Expr::Case {
+                        conditions,
+                        results,
+                        ..
+                    } => {
+                        assert_eq!(conditions.len(), results.len());
+                        names.insert("case".to_string());
+                    }
+                    Expr::FunctionCall {
+                        func: FunctionCall { name, args, .. },
+                        ..
+                    } => {
+                        if name.name == "nullif" {
+                            assert!(ty.is_nullable());
+                            assert_eq!(args.len(), 2);
+                        }
+                        names.insert(name.name.clone());
+                    }
+                    _ => panic!("unexpected expr {}", expr),
+                }
+                expr.drive(&mut visitor);
+                assert_eq!(generator.case_depth, 0);
+            }
+        }
+        assert_eq!(names.len(), 5);
+        assert!(visitor.max_depth > 1);
+        assert!(visitor.max_depth <= MAX_CASE_DEPTH);
+    }
+
+    #[test]
+    fn test_short_circuit_chain() {
+        let mut generator = test_generator();
+        generator.bound_table(mock_tables().remove(0));
+        generator.gen_error_cases = true;
+
+        let zero = Expr::Literal {
+            span: None,
+            value: Literal::UInt64(0),
+        };
+        let is_erroring = |expr: &Expr| {
+            matches!(expr, Expr::BinaryOp {
+                op: BinaryOperator::Gt,
+                left: box Expr::FunctionCall {
+                    func: FunctionCall { name, args, .. },
+                    ..
+                },
+                right,
+                ..
+            } if name.name == "divide" && args[1] == zero && **right == zero)
+        };
+
+        let mut num_erroring = 0;
+        for _ in 0..100 {
+            let Expr::BinaryOp {
+                op: chain_op,
+                left,
+                right,
+                ..
+            } = generator.gen_short_circuit_chain()
+            else {
+                panic!("expect binary op");
+            };
+            assert!(matches!(chain_op, BinaryOperator::And | BinaryOperator::Or));
+            if is_erroring(&right) {
+                num_erroring += 1;
+            }
+            // the erroring operands are never on the left of the chain
+            let mut left = *left;
+            while let Expr::BinaryOp {
+                op,
+                left: inner,
+                right,
+                ..
+            } = &left
+            {
+                if *op != chain_op {
+                    break;
+                }
+                if is_erroring(right) {
+                    num_erroring += 1;
+                }
+                let inner = (**inner).clone();
+                left = inner;
+            }
+            assert!(!is_erroring(&left));
+        }
+        assert!(num_erroring > 0);
+    }
+
+    #[test]
+    fn test_truth_test_variants() {
+        let mut generator = test_generator();
+        let table = mock_tables().remove(0);
+        generator.bound_table(table);
+
+        let mut variants = HashSet::new();
+        for operand_ty in [
+            DataType::Boolean,
+            DataType::Nullable(Box::new(DataType::Boolean)),
+        ] {
+            for _ in 0..100 {
+                let (variant, operand) = match generator.gen_truth_test(&operand_ty) {
+                    Expr::FunctionCall { func, .. } => match func.args[0].clone() {
+                        Expr::UnaryOp { expr, .. } => ("IS FALSE", *expr),
+                        operand => ("IS TRUE", operand),
+                    },
+                    Expr::UnaryOp { expr, .. } => {
+                        let Expr::FunctionCall { func, .. } = *expr else {
+                            panic!("expect is_true");
+                        };
+                        match func.args[0].clone() {
+                            Expr::UnaryOp { expr, .. } => ("IS NOT FALSE", *expr),
+                            operand => ("IS NOT TRUE", operand),
+                        }
+                    }
+                    Expr::IsNull { expr, not, .. } => {
+                        let variant = if not { "IS NOT UNKNOWN" } else { "IS UNKNOWN" };
+                        (variant, *expr)
+                    }
+                    expr => panic!("unexpected truth test {}", expr),
+                };
+                variants.insert(variant);
+                // the only boolean column is `c6` at position 7
+                if let Expr::ColumnRef { column, .. } = operand {
+                    assert!(["c6", "$7"].contains(&column.column.name()));
+                }
+            }
+        }
+        assert_eq!(variants.len(), 6);
+    }
+
+    #[test]
+    fn test_mixed_width_arithmetic() {
+        let mut generator = test_generator();
+        let integer_width = |ty: &TypeName| match ty {
+            TypeName::UInt8 => Some((false, 8)),
+            TypeName::UInt16 => Some((false, 16)),
+            TypeName::UInt32 => Some((false, 32)),
+            TypeName::UInt64 => Some((false, 64)),
+            TypeName::Int8 => Some((true, 8)),
+            TypeName::Int16 => Some((true, 16)),
+            TypeName::Int32 => Some((true, 32)),
+            TypeName::Int64 => Some((true, 64)),
+            _ => None,
+        };
+        let mut num_mixed_widths = 0;
+        let mut num_mixed_signs = 0;
+        for _ in 0..200 {
+            let Expr::BinaryOp {
+                op, left, right, ..
+            } = generator.gen_mixed_arithmetic()
+            else {
+                panic!("expect binary op");
+            };
+            assert!(matches!(
+                op,
+                BinaryOperator::Plus
+                    | BinaryOperator::Minus
+                    | BinaryOperator::Multiply
+                    | BinaryOperator::Divide
+                    | BinaryOperator::IntDiv
+                    | BinaryOperator::Modulo
+            ));
+            let (
+                Expr::Cast {
+                    target_type: left_ty,
+                    ..
+                },
+                Expr::Cast {
+                    target_type: right_ty,
+                    ..
+                },
+            ) = (*left, *right)
+            else {
+                panic!("expect casted operands");
+            };
+            assert_ne!(left_ty, right_ty);
+            if let (Some((left_signed, left_width)), Some((right_signed, right_width))) =
+                (integer_width(&left_ty), integer_width(&right_ty))
+            {
+                if left_width != right_width {
+                    num_mixed_widths += 1;
+                }
+                if left_signed != right_signed {
+                    num_mixed_signs += 1;
+                }
+            }
+        }
+        assert!(num_mixed_widths > 0);
+        assert!(num_mixed_signs > 0);
+    }
+
+    #[derive(Visitor, Default)]
+    #[visitor(Expr(enter))]
+    struct OperandVisitor {
+        num_columns: usize,
+        num_literals: usize,
+    }
+
+    impl OperandVisitor {
+        fn enter_expr(&mut self, expr: &Expr) {
+            match expr {
+                Expr::ColumnRef { .. } => self.num_columns += 1,
+                Expr::Literal { .. } => self.num_literals += 1,
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn test_constant_fold_operands() {
+        let mut generator = test_generator();
+        generator.bound_table(mock_tables().remove(0));
+
+        // the constant expressions have no column references on demand
+        let types = [
+            DataType::Number(NumberDataType::Int64),
+            DataType::Number(NumberDataType::UInt8).wrap_nullable(),
+            DataType::Number(NumberDataType::Float64),
+            DataType::Boolean,
+            DataType::String,
+        ];
+        for ty in &types {
+            for _ in 0..20 {
+                let mut visitor = OperandVisitor::default();
+                generator.gen_constant_expr(ty).drive(&mut visitor);
+                assert_eq!(visitor.num_columns, 0);
+                assert!(visitor.num_literals > 0);
+            }
+        }
+
+        let ty = DataType::Number(NumberDataType::Int32);
+        for _ in 0..20 {
+            let mut visitor = OperandVisitor::default();
+            generator
+                .gen_fold_arithmetic(&ty, FoldOperands::Runtime)
+                .drive(&mut visitor);
+            assert!(visitor.num_columns > 0);
+            assert_eq!(visitor.num_literals, 0);
+
+            // the column side is evaluated at runtime, the literal side is folded
+            let Expr::Cast {
+                expr: box Expr::BinaryOp { left, right, .. },
+                ..
+            } = generator.gen_fold_arithmetic(&ty, FoldOperands::Mixed)
+            else {
+                panic!("expect casted arithmetic");
+            };
+            let mut visitor = OperandVisitor::default();
+            left.drive(&mut visitor);
+            assert!(visitor.num_columns > 0);
+            assert_eq!(visitor.num_literals, 0);
+            let mut visitor = OperandVisitor::default();
+            right.drive(&mut visitor);
+            assert_eq!(visitor.num_columns, 0);
+            assert!(visitor.num_literals > 0);
+        }
+    }
+
+    #[test]
+    fn test_case_forms() {
+        let mut generator = test_generator();
+        generator.bound_table(mock_tables().remove(0));
+
+        // the WHEN values of the simple form are comparable with the operand
+        for _ in 0..500 {
+            let operand_ty = generator.gen_case_operand_type();
+            let value_ty = generator.gen_case_value_type(&operand_ty);
+            let common_ty = common_super_type(
+                operand_ty.clone(),
+                value_ty.clone(),
+                &BUILTIN_FUNCTIONS.default_cast_rules,
+            );
+            assert!(
+                common_ty.is_some(),
+                "{operand_ty} is not comparable with {value_ty}"
+            );
+        }
+
+        let mut num_simple = 0;
+        let mut num_searched = 0;
+        for _ in 0..100 {
+            let Expr::Case {
+                operand,
+                conditions,
+                results,
+                ..
+            } = generator.gen_case(&DataType::String)
+            else {
+                panic!("expect case expr");
+            };
+            assert_eq!(conditions.len(), results.len());
+            match operand {
+                Some(_) => num_simple += 1,
+                None => num_searched += 1,
+            }
+        }
+        assert!(num_simple > 0);
+        assert!(num_searched > 0);
+    }
+}
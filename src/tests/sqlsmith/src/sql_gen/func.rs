@@ -17,6 +17,7 @@ use std::mem;
 use databend_common_ast::ast::Expr;
 use databend_common_ast::ast::FunctionCall;
 use databend_common_ast::ast::Identifier;
+use databend_common_ast::ast::IntervalKind;
 use databend_common_ast::ast::Lambda;
 use databend_common_ast::ast::Literal;
 use databend_common_ast::ast::OrderByExpr;
@@ -34,6 +35,7 @@ use databend_common_expression::types::DecimalSize;
 use databend_common_expression::types::NumberDataType;
 use databend_common_expression::types::ALL_FLOAT_TYPES;
 use databend_common_expression::types::ALL_INTEGER_TYPES;
+use databend_common_functions::BUILTIN_FUNCTIONS;
 use rand::Rng;
 
 use crate::sql_gen::Column;
@@ -48,6 +50,11 @@ impl<R: Rng> SqlGenerator<'_, R> {
             }
         }
         if indices.is_empty() {
+            // complex types usually have no direct signatures,
+            // try the factory functions before bottoming out at a literal.
+            if self.allowed_funcs.is_none() && self.rng.gen_bool(0.5) {
+                return self.gen_factory_scalar_func(ty);
+            }
             return self.gen_scalar_value(ty);
         }
         let idx = self.rng.gen_range(0..indices.len());
@@ -65,6 +72,14 @@ impl<R: Rng> SqlGenerator<'_, R> {
     pub(crate) fn gen_factory_scalar_func(&mut self, ty: &DataType) -> Expr {
         let (name, params, args_type) = match ty.remove_nullable() {
             DataType::String => {
+                // the SQL-standard special syntaxes, parsed by dedicated rules
+                if self.rng.gen_bool(0.2) {
+                    return if self.rng.gen_bool(0.5) {
+                        self.gen_substring()
+                    } else {
+                        self.gen_trim()
+                    };
+                }
                 let idx = self.rng.gen_range(0..=5);
                 let name = match idx {
                     0 => "char".to_string(),
@@ -152,7 +167,10 @@ impl<R: Rng> SqlGenerator<'_, R> {
                 (name, vec![], args_type)
             }
             DataType::Boolean => {
-                let idx = self.rng.gen_range(0..=3);
+                if self.rng.gen_bool(0.25) {
+                    return self.gen_ignore_func();
+                }
+                let idx = self.rng.gen_range(0..=2);
                 let name = match idx {
                     0 => "and_filters".to_string(),
                     1 => "regexp_like".to_string(),
@@ -160,8 +178,6 @@ impl<R: Rng> SqlGenerator<'_, R> {
                         let comp_func = ["eq", "gt", "gte", "lt", "lte", "noteq"];
                         comp_func[self.rng.gen_range(0..=5)].to_string()
                     }
-                    3 => "ignore".to_string(),
-
                     _ => unreachable!(),
                 };
                 let args_type = match idx {
@@ -175,17 +191,14 @@ impl<R: Rng> SqlGenerator<'_, R> {
                         let ty = self.gen_data_type();
                         vec![ty; 2]
                     }
-                    3 => {
-                        let ty1 = self.gen_data_type();
-                        let ty2 = self.gen_data_type();
-                        let ty3 = self.gen_data_type();
-                        vec![ty1, ty2, ty3]
-                    }
                     _ => unreachable!(),
                 };
                 (name, vec![], args_type)
             }
             DataType::Number(_) => {
+                if self.rng.gen_bool(0.1) {
+                    return self.gen_position();
+                }
                 let idx = self.rng.gen_range(0..=4);
                 let name = match idx {
                     0 => "point_in_ellipses".to_string(),
@@ -365,6 +378,51 @@ impl<R: Rng> SqlGenerator<'_, R> {
         self.gen_func(name, params, args_type, None, None)
     }
 
+    // The separator of `string_agg(x, sep)`, a string literal like the separators written by
+    // hand, including the empty one.
+    fn gen_string_agg_separator(&mut self) -> Expr {
+        let separators = [",", ", ", " | ", ";", "", "\n"];
+        let separator = separators[self.rng.gen_range(0..separators.len())];
+        Expr::Literal {
+            span: None,
+            value: Literal::String(separator.to_string()),
+        }
+    }
+
+    // `ignore(..)` evaluates and discards its arguments, the arguments are often function calls
+    // which are expensive or fail like `divide(x, 0) > 0`, to check whether the engine evaluates
+    // or elides them. The arguments are of the simple types, the nested types can grow too deep.
+    pub(crate) fn gen_ignore_func(&mut self) -> Expr {
+        let len = self.rng.gen_range(1..=3);
+        let mut args = Vec::with_capacity(len);
+        for _ in 0..len {
+            let arg = match self.rng.gen_range(0..=2) {
+                0 => self.gen_erroring_predicate(),
+                1 => {
+                    let ty = self.gen_simple_data_type();
+                    self.gen_scalar_func(&ty)
+                }
+                2 => {
+                    let ty = self.gen_simple_data_type();
+                    self.gen_expr(&ty)
+                }
+                _ => unreachable!(),
+            };
+            args.push(arg);
+        }
+        Expr::FunctionCall {
+            span: None,
+            func: FunctionCall {
+                distinct: false,
+                name: Identifier::from_name(None, "ignore"),
+                args,
+                params: vec![],
+                window: None,
+                lambda: None,
+            },
+        }
+    }
+
     pub(crate) fn gen_agg_func(&mut self, ty: &DataType) -> Expr {
         let (name, params, mut args_type) = match ty.remove_nullable() {
             DataType::Number(NumberDataType::UInt8) => {
@@ -465,6 +523,10 @@ impl<R: Rng> SqlGenerator<'_, R> {
                 };
                 (name, params, args_type)
             }
+            DataType::Variant if self.flip_coin() => self.gen_json_agg_signature(),
+            DataType::Geometry if self.enable_spatial => {
+                return self.gen_spatial_agg_func();
+            }
             DataType::Decimal(_) => {
                 let name = "sum".to_string();
                 let params = vec![];
@@ -501,6 +563,9 @@ impl<R: Rng> SqlGenerator<'_, R> {
                     vec![self.gen_all_number_data_type()]
                 };
 
+                // TODO: generate the ordered-set aggregates `percentile_cont(p) WITHIN GROUP (ORDER BY x)`
+                // and `percentile_disc` once the parser supports the WITHIN GROUP clause,
+                // the same aggregates are generated as `quantile_cont(p)(x)` and `quantile_disc(p)(x)`.
                 let params = if idx >= 11 {
                     if self.rng.gen_bool(0.5) {
                         vec![Literal::Float64(self.rng.gen_range(0.01..=0.99))]
@@ -553,8 +618,27 @@ impl<R: Rng> SqlGenerator<'_, R> {
                 (name, params, args_type)
             }
         };
-        // test combinator, only need test _if and _distinct
-        let idx = self.rng.gen_range(0..=2);
+        if !self.is_func_allowed(&name) {
+            return self.gen_scalar_value(ty);
+        }
+        let window = if self.rng.gen_bool(0.8) {
+            None
+        } else {
+            self.gen_agg_window()
+        };
+        // test combinator, only need test _if and _distinct,
+        // they are applied to the plain and the windowed aggregates.
+        // TODO: generate `agg(x) FILTER (WHERE pred)` once the parser supports the FILTER clause
+        // TODO: generate `agg(x ORDER BY y)` for the order-sensitive aggregates, like `array_agg`,
+        // `string_agg`, `json_array_agg` and `group_array_moving_sum`, and the ordered forms
+        // `LISTAGG(x, sep) WITHIN GROUP (ORDER BY y)` and `GROUP_CONCAT(x ORDER BY y SEPARATOR sep)`,
+        // once the parser supports them
+        if self.gen_error_cases && self.rng.gen_bool(0.1) {
+            let combinators = self.gen_agg_combinator_stack();
+            let (name, args_type) = apply_agg_combinators(name, args_type, &combinators);
+            return self.gen_func(name, params, args_type, window, None);
+        }
+        let idx = self.rng.gen_range(0..=3);
         let (name, params, args_type) = match idx {
             0 => (name, params, args_type),
             1 => {
@@ -566,16 +650,95 @@ impl<R: Rng> SqlGenerator<'_, R> {
                 let name = name + "_distinct";
                 (name, params, args_type)
             }
+            3 if is_multi_args_distinct_func(&name) => {
+                return self.gen_multi_args_distinct_func(name, params, args_type, window);
+            }
+            3 => (name, params, args_type),
             _ => unreachable!(),
         };
 
-        let window = if self.rng.gen_bool(0.8) {
-            None
+        self.gen_func(name, params, args_type, window, None)
+    }
+
+    // Generate a stack of two combinators, the outer one is compatible with the inner one
+    // if any is, otherwise the stack is invalid and only generated for the error cases.
+    fn gen_agg_combinator_stack(&mut self) -> Vec<&'static str> {
+        let idx = self.rng.gen_range(0..AGG_COMBINATORS.len());
+        let (inner, compatible) = AGG_COMBINATORS[idx];
+        let outer = if !compatible.is_empty() {
+            compatible[self.rng.gen_range(0..compatible.len())]
         } else {
-            self.gen_window()
+            let idx = (idx + self.rng.gen_range(1..AGG_COMBINATORS.len())) % AGG_COMBINATORS.len();
+            AGG_COMBINATORS[idx].0
         };
+        vec![inner, outer]
+    }
 
-        self.gen_func(name, params, args_type, window, None)
+    // The JSON aggregate functions return Variant, `json_array_agg(value)` and
+    // `json_object_agg(key, value)`, the key of the object must be a String.
+    fn gen_json_agg_signature(&mut self) -> (String, Vec<Literal>, Vec<DataType>) {
+        let value_type = if self.rng.gen_bool(0.3) {
+            DataType::Variant
+        } else {
+            self.gen_simple_data_type()
+        };
+        if self.flip_coin() {
+            ("json_array_agg".to_string(), vec![], vec![value_type])
+        } else {
+            let args_type = vec![DataType::String, value_type];
+            ("json_object_agg".to_string(), vec![], args_type)
+        }
+    }
+
+    // Generate `st_collect` which aggregates the geometries into a collection,
+    // the argument is a geometry column or a geometry constructor function.
+    fn gen_spatial_agg_func(&mut self) -> Expr {
+        let name = "st_collect";
+        if !self.is_func_allowed(name) {
+            return self.gen_scalar_value(&DataType::Geometry);
+        }
+        let has_column = self
+            .bound_columns
+            .iter()
+            .any(|column| column.data_type == DataType::Geometry);
+        let arg = if has_column && self.flip_coin() {
+            self.gen_column(&DataType::Geometry)
+        } else {
+            self.gen_other_expr(&DataType::Geometry)
+        };
+        Expr::FunctionCall {
+            span: None,
+            func: FunctionCall {
+                distinct: false,
+                name: Identifier::from_name(None, name),
+                args: vec![arg],
+                params: vec![],
+                window: None,
+                lambda: None,
+            },
+        }
+    }
+
+    // Generate the aggregate function with `DISTINCT` over multiple arguments, like
+    // `count(DISTINCT a, b)`, which counts the distinct rows of all the arguments.
+    fn gen_multi_args_distinct_func(
+        &mut self,
+        name: String,
+        params: Vec<Literal>,
+        mut args_type: Vec<DataType>,
+        window: Option<WindowDesc>,
+    ) -> Expr {
+        if name == "count" {
+            let len = self.rng.gen_range(1..=3);
+            for _ in 0..len {
+                args_type.push(self.gen_data_type());
+            }
+        }
+        let mut expr = self.gen_func(name, params, args_type, window, None);
+        if let Expr::FunctionCall { func, .. } = &mut expr {
+            func.distinct = true;
+        }
+        expr
     }
 
     pub(crate) fn gen_window_func(&mut self, ty: &DataType) -> Expr {
@@ -609,7 +772,9 @@ impl<R: Rng> SqlGenerator<'_, R> {
                 ];
                 let name = name[self.rng.gen_range(0..=6)];
                 let args_type = if name == "lag" || name == "lead" {
-                    vec![ty; 3]
+                    // lag(expr [, offset [, default]])
+                    let len = self.rng.gen_range(1..=3);
+                    vec![ty; len]
                 } else if name == "nth_value" {
                     vec![ty, DataType::Number(NumberDataType::UInt64)]
                 } else {
@@ -620,6 +785,18 @@ impl<R: Rng> SqlGenerator<'_, R> {
         }
     }
 
+    // The aggregate window functions reject the IGNORE/RESPECT NULLS option,
+    // which is only kept to generate the error cases.
+    fn gen_agg_window(&mut self) -> Option<WindowDesc> {
+        let mut window = self.gen_window();
+        if let Some(window) = &mut window {
+            if !self.gen_error_cases || self.rng.gen_bool(0.8) {
+                window.ignore_nulls = None;
+            }
+        }
+        window
+    }
+
     fn gen_window(&mut self) -> Option<WindowDesc> {
         let ignore_nulls = Some(self.rng.gen_bool(0.2));
         if self.rng.gen_bool(0.2) && !self.windows_name.is_empty() {
@@ -646,6 +823,9 @@ impl<R: Rng> SqlGenerator<'_, R> {
     }
 
     pub(crate) fn gen_window_spec(&mut self) -> WindowSpec {
+        if self.rng.gen_bool(0.1) {
+            return self.gen_range_offset_window_spec();
+        }
         let ty = self.gen_data_type();
         let expr1 = self.gen_scalar_value(&ty);
         let expr2 = self.gen_scalar_value(&ty);
@@ -664,18 +844,121 @@ impl<R: Rng> SqlGenerator<'_, R> {
                 nulls_first: Some(true),
             },
         ];
+        let partition_by = vec![expr3, expr4];
+        // The shapes of the window clause are evaluated by different branches:
+        // `OVER ()` aggregates the whole input as a single partition,
+        // a window without ORDER BY has all the rows of the partition as peers.
+        let (partition_by, order_by) = match self.rng.gen_range(0..=3) {
+            0 => (vec![], vec![]),
+            1 => (partition_by, vec![]),
+            2 => (vec![], order_by),
+            3 => (partition_by, order_by),
+            _ => unreachable!(),
+        };
+        let window_frame = if order_by.is_empty() || self.rng.gen_bool(0.8) {
+            None
+        } else {
+            Some(WindowFrame {
+                units: WindowFrameUnits::Rows,
+                start_bound: WindowFrameBound::Preceding(None),
+                end_bound: WindowFrameBound::CurrentRow,
+            })
+        };
         WindowSpec {
             existing_window_name: None,
-            partition_by: vec![expr3, expr4],
+            partition_by,
             order_by,
-            window_frame: if self.rng.gen_bool(0.8) {
-                None
-            } else {
-                Some(WindowFrame {
-                    units: WindowFrameUnits::Rows,
-                    start_bound: WindowFrameBound::Preceding(None),
-                    end_bound: WindowFrameBound::CurrentRow,
-                })
+            window_frame,
+        }
+    }
+
+    // RANGE frames with offsets require exactly one order by key,
+    // and the offset type must match the order by key type.
+    fn gen_range_offset_window_spec(&mut self) -> WindowSpec {
+        let order_ty = self.gen_range_order_by_data_type();
+        let order_expr = self.gen_simple_expr(&order_ty);
+        let ty = self.gen_data_type();
+        let partition_expr = self.gen_scalar_value(&ty);
+
+        let order_by = vec![OrderByExpr {
+            expr: order_expr,
+            asc: Some(self.flip_coin()),
+            nulls_first: None,
+        }];
+        WindowSpec {
+            existing_window_name: None,
+            partition_by: vec![partition_expr],
+            order_by,
+            window_frame: Some(self.gen_range_frame(&order_ty)),
+        }
+    }
+
+    pub(crate) fn gen_range_order_by_data_type(&mut self) -> DataType {
+        match self.rng.gen_range(0..=3) {
+            0 => DataType::Timestamp,
+            1 => DataType::Date,
+            2..=3 => self.gen_all_number_data_type(),
+            _ => unreachable!(),
+        }
+    }
+
+    pub(crate) fn gen_range_frame(&mut self, order_ty: &DataType) -> WindowFrame {
+        let start_bound = match self.rng.gen_range(0..=2) {
+            0 => WindowFrameBound::Preceding(None),
+            1 => {
+                let offset = self.gen_range_frame_offset(order_ty);
+                WindowFrameBound::Preceding(Some(Box::new(offset)))
+            }
+            2 => WindowFrameBound::CurrentRow,
+            _ => unreachable!(),
+        };
+        let end_bound = match self.rng.gen_range(0..=2) {
+            0 => WindowFrameBound::CurrentRow,
+            1 => {
+                let offset = self.gen_range_frame_offset(order_ty);
+                WindowFrameBound::Following(Some(Box::new(offset)))
+            }
+            2 => WindowFrameBound::Following(None),
+            _ => unreachable!(),
+        };
+        WindowFrame {
+            units: WindowFrameUnits::Range,
+            start_bound,
+            end_bound,
+        }
+    }
+
+    // interval for date and timestamp, numeric literal for numbers
+    fn gen_range_frame_offset(&mut self, order_ty: &DataType) -> Expr {
+        match order_ty.remove_nullable() {
+            DataType::Date | DataType::Timestamp => {
+                let unit = match self.rng.gen_range(0..=6) {
+                    0 => IntervalKind::Year,
+                    1 => IntervalKind::Month,
+                    2 => IntervalKind::Week,
+                    3 => IntervalKind::Day,
+                    4 => IntervalKind::Hour,
+                    5 => IntervalKind::Minute,
+                    6 => IntervalKind::Second,
+                    _ => unreachable!(),
+                };
+                Expr::Interval {
+                    span: None,
+                    expr: Box::new(Expr::Literal {
+                        span: None,
+                        value: Literal::UInt64(self.rng.gen_range(1..=10)),
+                    }),
+                    unit,
+                }
+            }
+            DataType::Number(NumberDataType::Float32)
+            | DataType::Number(NumberDataType::Float64) => Expr::Literal {
+                span: None,
+                value: Literal::Float64(self.rng.gen_range(0.0..=10.0)),
+            },
+            _ => Expr::Literal {
+                span: None,
+                value: Literal::UInt64(self.rng.gen_range(0..=10)),
             },
         }
     }
@@ -725,6 +1008,74 @@ impl<R: Rng> SqlGenerator<'_, R> {
         self.gen_func(name, vec![], args_type, None, Some(lambda))
     }
 
+    // Some branches build `args_type` independently of the chosen function name,
+    // check that the arguments match one of the signatures if the function has any,
+    // to catch the generator bugs early.
+    fn check_args_type(&mut self, name: &str, args_type: &[DataType]) {
+        let mut known = false;
+        for func_sig in self.scalar_func_sigs.iter() {
+            if func_sig.name != name {
+                continue;
+            }
+            known = true;
+            if func_sig.args_type.len() == args_type.len()
+                && func_sig
+                    .args_type
+                    .iter()
+                    .zip(args_type)
+                    .all(|(sig_ty, ty)| sig_ty.remove_nullable() == ty.remove_nullable())
+            {
+                return;
+            }
+        }
+        if known {
+            self.args_type_mismatches += 1;
+            tracing::warn!(
+                "generated args type {:?} does not match any signature of function {}",
+                args_type,
+                name
+            );
+        }
+    }
+
+    // In coercion mode, generate a literal of another type category for the argument,
+    // a numeric String literal or a Boolean literal for a number, a number literal
+    // for a String, or a `0` or `1` literal for a Boolean.
+    // The unsupported coercions are only generated with `gen_error_cases`.
+    pub(crate) fn gen_coercion_arg(&mut self, func_name: &str, ty: &DataType) -> Option<Expr> {
+        if !self.coercion_mode || !self.rng.gen_bool(0.3) {
+            return None;
+        }
+        let dest_ty = ty.remove_nullable();
+        let (src_ty, value) = match dest_ty {
+            DataType::Number(_) if self.flip_coin() => {
+                let value = self.rng.gen_range(0..=1000).to_string();
+                (DataType::String, Literal::String(value))
+            }
+            DataType::Number(_) => (DataType::Boolean, Literal::Boolean(self.flip_coin())),
+            DataType::Boolean => (
+                DataType::Number(NumberDataType::UInt8),
+                Literal::UInt64(self.rng.gen_range(0..=1)),
+            ),
+            DataType::String => {
+                let value = self.rng.gen_range(0..=1000);
+                (
+                    DataType::Number(NumberDataType::UInt64),
+                    Literal::UInt64(value),
+                )
+            }
+            _ => return None,
+        };
+        if is_valid_coercion(func_name, &src_ty, &dest_ty) {
+            self.implicit_coercions += 1;
+        } else if self.gen_error_cases {
+            self.invalid_coercions += 1;
+        } else {
+            return None;
+        }
+        Some(Expr::Literal { span: None, value })
+    }
+
     fn gen_func(
         &mut self,
         name: String,
@@ -733,6 +1084,10 @@ impl<R: Rng> SqlGenerator<'_, R> {
         window: Option<WindowDesc>,
         lambda: Option<Lambda>,
     ) -> Expr {
+        if cfg!(debug_assertions) {
+            self.check_args_type(&name, &args_type);
+        }
+
         let distinct = if name == *"count" {
             self.rng.gen_bool(0.5)
         } else {
@@ -747,14 +1102,21 @@ impl<R: Rng> SqlGenerator<'_, R> {
                         span: None,
                         value: Literal::UInt64(self.rng.gen_range(1..=10)),
                     })
+                } else if i == 2 {
+                    // the default value must have the same type as the column
+                    args.push(self.gen_scalar_value(ty))
                 } else {
                     args.push(self.gen_expr(ty))
                 }
+            } else if name.starts_with("string_agg") && i == 1 && *ty == DataType::String {
+                args.push(self.gen_string_agg_separator())
             } else if name == "factorial" {
                 args.push(Expr::Literal {
                     span: None,
                     value: Literal::UInt64(self.rng.gen_range(0..=20)),
                 })
+            } else if let Some(arg) = self.gen_coercion_arg(&name, ty) {
+                args.push(arg)
             } else {
                 args.push(self.gen_expr(ty))
             }
@@ -782,3 +1144,702 @@ impl<R: Rng> SqlGenerator<'_, R> {
         }
     }
 }
+
+// Whether the function casts the argument from `src_ty` to `dest_ty` implicitly.
+pub(crate) fn is_valid_coercion(func_name: &str, src_ty: &DataType, dest_ty: &DataType) -> bool {
+    BUILTIN_FUNCTIONS
+        .get_auto_cast_rules(func_name)
+        .iter()
+        .any(|(src, dest)| src == src_ty && dest == dest_ty)
+}
+
+// The aggregate function combinators, each one with the combinators which can be applied
+// on top of it. The function factory only resolves a single combinator suffix over a plain
+// aggregate function, so no stack like `sum_distinct_if` is supported yet.
+const AGG_COMBINATORS: &[(&str, &[&str])] = &[("_if", &[]), ("_distinct", &[]), ("_state", &[])];
+
+// Whether the combinators can be applied in order, from the innermost one.
+pub(crate) fn is_valid_agg_combinator_stack(combinators: &[&str]) -> bool {
+    combinators.windows(2).all(|pair| {
+        AGG_COMBINATORS
+            .iter()
+            .any(|(inner, compatible)| *inner == pair[0] && compatible.contains(&pair[1]))
+    })
+}
+
+// Append the combinator suffixes to the name of the aggregate function,
+// each `_if` takes one more Boolean argument as the condition.
+pub(crate) fn apply_agg_combinators(
+    mut name: String,
+    mut args_type: Vec<DataType>,
+    combinators: &[&str],
+) -> (String, Vec<DataType>) {
+    for combinator in combinators {
+        name.push_str(combinator);
+        if *combinator == "_if" {
+            args_type.push(DataType::Boolean);
+        }
+    }
+    (name, args_type)
+}
+
+// The aggregate functions accept `DISTINCT` with more than one argument.
+// `count` counts the distinct rows of any number of arguments, the others
+// take two arguments and are applied to the distinct rows.
+fn is_multi_args_distinct_func(name: &str) -> bool {
+    matches!(
+        name,
+        "count" | "covar_pop" | "covar_samp" | "arg_min" | "arg_max"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use databend_common_ast::ast::ColumnID;
+    use databend_common_ast::ast::Expr;
+    use databend_common_ast::ast::FunctionCall;
+    use databend_common_ast::ast::Identifier;
+    use databend_common_ast::ast::Literal;
+    use databend_common_ast::ast::SetExpr;
+    use databend_common_ast::ast::WindowFrameBound;
+    use databend_common_ast::ast::WindowFrameUnits;
+    use databend_common_expression::types::DataType;
+    use databend_common_expression::types::NumberDataType;
+    use databend_common_expression::TableDataType;
+    use databend_common_expression::TableField;
+    use databend_common_expression::TableSchemaRefExt;
+    use derive_visitor::Drive;
+    use derive_visitor::Visitor;
+    use rand::rngs::SmallRng;
+    use rand::Rng;
+
+    use super::apply_agg_combinators;
+    use super::is_multi_args_distinct_func;
+    use super::is_valid_agg_combinator_stack;
+    use super::is_valid_coercion;
+    use crate::sql_gen::mock_tables;
+    use crate::sql_gen::test_generator;
+    use crate::sql_gen::SqlGenerator;
+    use crate::sql_gen::Table;
+
+    #[derive(Visitor)]
+    #[visitor(FunctionCall(enter))]
+    struct FuncNameVisitor {
+        names: HashSet<String>,
+    }
+
+    impl FuncNameVisitor {
+        fn enter_function_call(&mut self, func: &FunctionCall) {
+            self.names.insert(func.name.name.to_lowercase());
+        }
+    }
+
+    #[test]
+    fn test_allowed_funcs() {
+        // functions used to construct literals of some types
+        let literal_funcs = [
+            "to_date",
+            "to_timestamp",
+            "to_bitmap",
+            "parse_json",
+            "to_binary",
+            "to_geometry",
+            "st_geographyfromewkt",
+        ];
+        let allowed_funcs = vec!["plus".to_string(), "concat".to_string()];
+
+        let mut generator = test_generator();
+        generator.set_allowed_funcs(&allowed_funcs);
+
+        let mut visitor = FuncNameVisitor {
+            names: HashSet::new(),
+        };
+        for _ in 0..100 {
+            let query = generator.gen_query();
+            query.drive(&mut visitor);
+        }
+        for name in visitor.names {
+            assert!(
+                allowed_funcs.contains(&name) || literal_funcs.contains(&name.as_str()),
+                "unexpected function {}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_range_frame_offset_matches_order_key_type() {
+        let mut generator = test_generator();
+        for _ in 0..100 {
+            let order_ty = generator.gen_range_order_by_data_type();
+            let frame = generator.gen_range_frame(&order_ty);
+            assert!(matches!(frame.units, WindowFrameUnits::Range));
+
+            for bound in [frame.start_bound, frame.end_bound] {
+                let (WindowFrameBound::Preceding(Some(offset))
+                | WindowFrameBound::Following(Some(offset))) = bound
+                else {
+                    continue;
+                };
+                match order_ty {
+                    DataType::Date | DataType::Timestamp => {
+                        assert!(matches!(*offset, Expr::Interval { .. }))
+                    }
+                    DataType::Number(NumberDataType::Float32)
+                    | DataType::Number(NumberDataType::Float64) => {
+                        assert!(matches!(*offset, Expr::Literal {
+                            value: Literal::Float64(_),
+                            ..
+                        }))
+                    }
+                    _ => assert!(matches!(*offset, Expr::Literal {
+                        value: Literal::UInt64(_),
+                        ..
+                    })),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_lead_lag_arities() {
+        let mut generator = test_generator();
+
+        let mut arities = HashSet::new();
+        for _ in 0..500 {
+            let Expr::FunctionCall { func, .. } = generator.gen_window_func(&DataType::Boolean)
+            else {
+                continue;
+            };
+            if func.name.name != "lag" && func.name.name != "lead" {
+                continue;
+            }
+            arities.insert(func.args.len());
+            if let Some(offset) = func.args.get(1) {
+                assert!(matches!(offset, Expr::Literal {
+                    value: Literal::UInt64(_),
+                    ..
+                }));
+            }
+            if let Some(default) = func.args.get(2) {
+                assert!(matches!(default, Expr::Literal {
+                    value: Literal::Boolean(_),
+                    ..
+                }));
+            }
+        }
+        assert_eq!(arities, HashSet::from([1, 2, 3]));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_args_type_mismatch_reported() {
+        let mut generator = test_generator();
+
+        generator.gen_func(
+            "upper".to_string(),
+            vec![],
+            vec![DataType::String],
+            None,
+            None,
+        );
+        assert_eq!(generator.args_type_mismatches, 0);
+
+        generator.gen_func(
+            "upper".to_string(),
+            vec![],
+            vec![DataType::Number(NumberDataType::Int64)],
+            None,
+            None,
+        );
+        assert_eq!(generator.args_type_mismatches, 1);
+
+        // functions without known signatures are not checked
+        generator.gen_func(
+            "lag".to_string(),
+            vec![],
+            vec![DataType::Boolean],
+            None,
+            None,
+        );
+        assert_eq!(generator.args_type_mismatches, 1);
+    }
+
+    #[test]
+    fn test_scalar_func_fallback_to_factory() {
+        let mut generator = test_generator();
+        let ty = DataType::Tuple(vec![
+            DataType::Number(NumberDataType::Int64),
+            DataType::Array(Box::new(DataType::String)),
+        ]);
+        assert!(generator
+            .scalar_func_sigs
+            .iter()
+            .all(|func_sig| func_sig.return_type != ty));
+
+        let mut num_factory = 0;
+        for _ in 0..100 {
+            if let Expr::FunctionCall { func, .. } = generator.gen_scalar_func(&ty) {
+                assert_eq!(func.name.name, "tuple");
+                num_factory += 1;
+            }
+        }
+        assert!(num_factory > 0);
+    }
+
+    #[test]
+    fn test_multi_args_distinct_funcs() {
+        let mut generator = test_generator();
+
+        let mut names = HashSet::new();
+        for _ in 0..1000 {
+            let ty = match generator.rng.gen_range(0..=2) {
+                0 => DataType::Number(NumberDataType::UInt64),
+                1 => DataType::Number(NumberDataType::Float64),
+                2 => generator.gen_simple_data_type(),
+                _ => unreachable!(),
+            };
+            let Expr::FunctionCall { func, .. } = generator.gen_agg_func(&ty) else {
+                continue;
+            };
+            if !func.distinct || func.args.len() < 2 {
+                continue;
+            }
+            let name = func.name.name.to_lowercase();
+            assert!(
+                is_multi_args_distinct_func(&name),
+                "unexpected multi-args distinct function {}",
+                name
+            );
+            assert!(func.window.is_none());
+            if name != "count" {
+                assert_eq!(func.args.len(), 2);
+            }
+            names.insert(name);
+        }
+        assert!(names.contains("count"));
+    }
+
+    #[test]
+    fn test_spatial_agg_funcs() {
+        let mut generator = test_generator();
+        let schema = TableSchemaRefExt::create(vec![
+            TableField::new("g", TableDataType::Geometry),
+            TableField::new("c0", TableDataType::Number(NumberDataType::Int64)),
+        ]);
+        generator.bound_table(Table::new(None, Identifier::from_name(None, "t2"), schema));
+
+        // disabled by default
+        for _ in 0..100 {
+            if let Expr::FunctionCall { func, .. } = generator.gen_agg_func(&DataType::Geometry) {
+                assert!(!func.name.name.starts_with("st_collect"));
+            }
+        }
+
+        generator.enable_spatial = true;
+        let mut num_columns = 0;
+        let mut num_constructors = 0;
+        for _ in 0..100 {
+            let Expr::FunctionCall { func, .. } = generator.gen_agg_func(&DataType::Geometry)
+            else {
+                panic!("expect spatial aggregate function");
+            };
+            assert_eq!(func.name.name, "st_collect");
+            assert_eq!(func.args.len(), 1);
+            match &func.args[0] {
+                Expr::ColumnRef { column, .. } => {
+                    let ColumnID::Name(name) = &column.column else {
+                        continue;
+                    };
+                    assert_eq!(name.name, "g");
+                    num_columns += 1;
+                }
+                Expr::FunctionCall { func, .. } => {
+                    assert!(["st_makegeompoint", "st_geometryfromwkt"]
+                        .contains(&func.name.name.as_str()));
+                    num_constructors += 1;
+                }
+                arg => panic!("unexpected argument {}", arg),
+            }
+        }
+        assert!(num_columns > 0);
+        assert!(num_constructors > 0);
+    }
+
+    #[test]
+    fn test_json_agg_funcs() {
+        let mut generator = test_generator();
+
+        let mut names = HashSet::new();
+        for _ in 0..100 {
+            let (name, params, args_type) = generator.gen_json_agg_signature();
+            assert!(params.is_empty());
+            match name.as_str() {
+                "json_array_agg" => assert_eq!(args_type.len(), 1),
+                "json_object_agg" => {
+                    assert_eq!(args_type.len(), 2);
+                    assert_eq!(args_type[0], DataType::String);
+                }
+                _ => panic!("unexpected json aggregate function {}", name),
+            }
+            names.insert(name);
+        }
+        assert_eq!(names.len(), 2);
+
+        // the JSON aggregate functions are generated for the Variant type,
+        // along with the generic ones like `any` and `max`
+        let mut num_object_aggs = 0;
+        for _ in 0..100 {
+            let Expr::FunctionCall { func, .. } = generator.gen_agg_func(&DataType::Variant) else {
+                continue;
+            };
+            if func.name.name.starts_with("json_object_agg") {
+                // `_if` adds a condition argument
+                assert!(func.args.len() >= 2);
+                num_object_aggs += 1;
+            } else if func.name.name.starts_with("json_array_agg") {
+                assert!(!func.args.is_empty());
+            }
+        }
+        assert!(num_object_aggs > 0);
+    }
+
+    #[test]
+    fn test_windowed_agg_combinators() {
+        let mut generator = test_generator();
+
+        let ty = DataType::Number(NumberDataType::UInt64);
+        let mut num_if = 0;
+        let mut num_distinct = 0;
+        for _ in 0..1000 {
+            let Expr::FunctionCall { func, .. } = generator.gen_agg_func(&ty) else {
+                continue;
+            };
+            let Some(window) = &func.window else {
+                continue;
+            };
+            // the option is rejected by the aggregate window functions
+            assert!(window.ignore_nulls.is_none());
+            if func.name.name.ends_with("_if") {
+                num_if += 1;
+            } else if func.name.name.ends_with("_distinct") || func.distinct {
+                num_distinct += 1;
+            }
+        }
+        assert!(num_if > 0);
+        assert!(num_distinct > 0);
+
+        // the invalid option is only generated for the error cases
+        generator.gen_error_cases = true;
+        let num_ignore_nulls = (0..1000)
+            .filter(|_| {
+                matches!(
+                    generator.gen_agg_func(&ty),
+                    Expr::FunctionCall { func, .. }
+                        if func.window.as_ref().is_some_and(|w| w.ignore_nulls.is_some())
+                )
+            })
+            .count();
+        assert!(num_ignore_nulls > 0);
+    }
+
+    #[test]
+    fn test_window_spec_shapes() {
+        let mut generator = test_generator();
+
+        let mut shapes = HashSet::new();
+        for _ in 0..1000 {
+            let spec = generator.gen_window_spec();
+            assert!(spec.existing_window_name.is_none());
+            if spec.order_by.is_empty() {
+                assert!(spec.window_frame.is_none());
+            }
+            shapes.insert((spec.partition_by.is_empty(), spec.order_by.is_empty()));
+        }
+        // empty, partition only, order only and both
+        assert_eq!(shapes.len(), 4);
+    }
+
+    #[test]
+    fn test_coercion_mode() {
+        let string = DataType::String;
+        let int64 = DataType::Number(NumberDataType::Int64);
+        let int32 = DataType::Number(NumberDataType::Int32);
+        assert!(is_valid_coercion("plus", &string, &int64));
+        assert!(!is_valid_coercion("plus", &string, &int32));
+        // comparisons only cast strings to numbers for equality
+        assert!(is_valid_coercion("eq", &string, &int64));
+        assert!(!is_valid_coercion("lt", &string, &int64));
+        // numbers are never cast to strings implicitly
+        assert!(!is_valid_coercion("concat", &int64, &string));
+
+        let mut generator = test_generator();
+        for _ in 0..50 {
+            generator.gen_query();
+        }
+        assert_eq!(generator.implicit_coercions, 0);
+
+        generator.coercion_mode = true;
+        for _ in 0..50 {
+            generator.gen_query();
+        }
+        assert!(generator.implicit_coercions > 0);
+        assert_eq!(generator.invalid_coercions, 0);
+
+        // a numeric string literal is placed where the number is expected
+        let mut coerced = false;
+        for _ in 0..100 {
+            let Expr::FunctionCall { func, .. } = generator.gen_func(
+                "plus".to_string(),
+                vec![],
+                vec![int64.clone(), int64.clone()],
+                None,
+                None,
+            ) else {
+                panic!("expect function call");
+            };
+            coerced |= func.args.iter().any(|arg| {
+                matches!(arg, Expr::Literal { value: Literal::String(value), .. }
+                    if value.parse::<i64>().is_ok())
+            });
+        }
+        assert!(coerced);
+
+        // the unsupported coercions are only generated as error cases
+        generator.gen_error_cases = true;
+        for _ in 0..100 {
+            generator.gen_func(
+                "concat".to_string(),
+                vec![],
+                vec![string.clone()],
+                None,
+                None,
+            );
+        }
+        assert!(generator.invalid_coercions > 0);
+    }
+
+    #[test]
+    fn test_bool_number_coercions() {
+        let boolean = DataType::Boolean;
+        let uint8 = DataType::Number(NumberDataType::UInt8);
+        let int64 = DataType::Number(NumberDataType::Int64);
+        // numbers are cast to Boolean only by the logical functions and the filters
+        assert!(is_valid_coercion("and", &uint8, &boolean));
+        assert!(is_valid_coercion("and_filters", &uint8, &boolean));
+        assert!(!is_valid_coercion("if", &uint8, &boolean));
+        // Booleans are never cast to numbers implicitly
+        assert!(!is_valid_coercion("plus", &boolean, &int64));
+
+        let mut generator = test_generator();
+        generator.coercion_mode = true;
+
+        let is_bool_literal = |arg: &Expr| {
+            matches!(arg, Expr::Literal {
+                value: Literal::Boolean(_),
+                ..
+            })
+        };
+        let is_bit_literal = |arg: &Expr| matches!(arg, Expr::Literal { value: Literal::UInt64(value), .. } if *value <= 1);
+        fn gen_args(
+            generator: &mut SqlGenerator<SmallRng>,
+            name: &str,
+            ty: &DataType,
+        ) -> Vec<Expr> {
+            let Expr::FunctionCall { func, .. } = generator.gen_func(
+                name.to_string(),
+                vec![],
+                vec![ty.clone(), ty.clone()],
+                None,
+                None,
+            ) else {
+                panic!("expect function call");
+            };
+            func.args
+        }
+
+        // a `0` or `1` literal is placed where the Boolean is expected, like `1 AND c`
+        let mut coerced = false;
+        for _ in 0..100 {
+            coerced |= gen_args(&mut generator, "and", &boolean)
+                .iter()
+                .any(is_bit_literal);
+        }
+        assert!(coerced);
+        assert!(generator.implicit_coercions > 0);
+
+        // `c + true` is not supported, so it is only generated as an error case
+        for _ in 0..100 {
+            let args = gen_args(&mut generator, "plus", &int64);
+            assert!(!args.iter().any(is_bool_literal));
+        }
+        assert_eq!(generator.invalid_coercions, 0);
+        generator.gen_error_cases = true;
+        let mut coerced = false;
+        for _ in 0..100 {
+            coerced |= gen_args(&mut generator, "plus", &int64)
+                .iter()
+                .any(is_bool_literal);
+        }
+        assert!(coerced);
+        assert!(generator.invalid_coercions > 0);
+
+        // a number as the predicate, like `WHERE 1`
+        generator.gen_error_cases = false;
+        let mut num_where_bits = 0;
+        for _ in 0..100 {
+            let query = generator.gen_query();
+            if let SetExpr::Select(select) = &query.body {
+                if select.selection.as_ref().is_some_and(is_bit_literal) {
+                    num_where_bits += 1;
+                }
+            }
+        }
+        assert!(num_where_bits > 0);
+    }
+
+    #[test]
+    fn test_agg_combinator_stacks() {
+        assert!(is_valid_agg_combinator_stack(&["_if"]));
+        assert!(!is_valid_agg_combinator_stack(&["_distinct", "_if"]));
+        assert!(!is_valid_agg_combinator_stack(&["_if", "_state"]));
+
+        // each `_if` takes a trailing Boolean condition
+        let int64 = DataType::Number(NumberDataType::Int64);
+        let (name, args_type) = apply_agg_combinators("sum".to_string(), vec![int64.clone()], &[
+            "_distinct",
+            "_if",
+        ]);
+        assert_eq!(name, "sum_distinct_if");
+        assert_eq!(args_type, vec![int64.clone(), DataType::Boolean]);
+        let (name, args_type) = apply_agg_combinators(
+            "covar_pop".to_string(),
+            vec![int64.clone(), int64.clone()],
+            &["_if", "_state"],
+        );
+        assert_eq!(name, "covar_pop_if_state");
+        assert_eq!(args_type, vec![
+            int64.clone(),
+            int64.clone(),
+            DataType::Boolean
+        ]);
+
+        let mut generator = test_generator();
+        // the aggregate function of decimal is `sum(x)`
+        let ty = generator.gen_decimal_data_type();
+        fn gen_stacks(
+            generator: &mut SqlGenerator<SmallRng>,
+            ty: &DataType,
+        ) -> Vec<(Vec<&'static str>, usize)> {
+            let mut stacks = vec![];
+            for _ in 0..1000 {
+                let Expr::FunctionCall { func, .. } = generator.gen_agg_func(ty) else {
+                    continue;
+                };
+                let mut suffix = func.name.name.strip_prefix("sum").unwrap();
+                let mut combinators = vec![];
+                while let Some(combinator) = ["_if", "_distinct", "_state"]
+                    .into_iter()
+                    .find(|combinator| suffix.starts_with(combinator))
+                {
+                    combinators.push(combinator);
+                    suffix = &suffix[combinator.len()..];
+                }
+                assert!(suffix.is_empty(), "{}", func.name.name);
+                if combinators.len() > 1 {
+                    stacks.push((combinators, func.args.len()));
+                }
+            }
+            stacks
+        }
+
+        // the unsupported stacks are only generated as error cases
+        assert!(gen_stacks(&mut generator, &ty).is_empty());
+        generator.gen_error_cases = true;
+        let stacks = gen_stacks(&mut generator, &ty);
+        assert!(!stacks.is_empty());
+        for (combinators, num_args) in stacks {
+            assert_eq!(combinators.len(), 2);
+            assert!(!is_valid_agg_combinator_stack(&combinators));
+            let num_ifs = combinators.iter().filter(|c| **c == "_if").count();
+            assert_eq!(num_args, 1 + num_ifs, "{combinators:?}");
+        }
+    }
+
+    #[test]
+    fn test_ignore_func_args() {
+        let mut generator = test_generator();
+        generator.bound_table(mock_tables().remove(0));
+
+        let mut call_args = 0;
+        let mut erroring_args = 0;
+        for _ in 0..200 {
+            let Expr::FunctionCall { func, .. } = generator.gen_ignore_func() else {
+                panic!("expect function call");
+            };
+            assert_eq!(func.name.name, "ignore");
+            assert!((1..=3).contains(&func.args.len()));
+            for arg in &func.args {
+                let mut visitor = FuncNameVisitor {
+                    names: HashSet::new(),
+                };
+                arg.drive(&mut visitor);
+                if matches!(arg, Expr::FunctionCall { .. }) {
+                    call_args += 1;
+                }
+                if visitor.names.contains("divide") {
+                    erroring_args += 1;
+                }
+            }
+        }
+        // the arguments are function calls at least some of the time
+        assert!(call_args > 0);
+        assert!(erroring_args > 0);
+
+        // `ignore` is generated for the boolean expressions
+        let mut visitor = FuncNameVisitor {
+            names: HashSet::new(),
+        };
+        for _ in 0..500 {
+            generator.gen_expr(&DataType::Boolean).drive(&mut visitor);
+        }
+        assert!(visitor.names.contains("ignore"));
+    }
+
+    #[test]
+    fn test_string_agg_separator() {
+        let mut generator = test_generator();
+        generator.bound_table(mock_tables().remove(0));
+
+        let mut separators = HashSet::new();
+        for _ in 0..500 {
+            let Expr::FunctionCall { func, .. } = generator.gen_agg_func(&DataType::String) else {
+                continue;
+            };
+            if !func.name.name.starts_with("string_agg") || func.name.name.contains("_if_") {
+                continue;
+            }
+            // the separator follows the value, before the condition of `string_agg_if`
+            let num_args = if func.name.name.ends_with("_if") {
+                func.args.len() - 1
+            } else {
+                func.args.len()
+            };
+            if num_args < 2 {
+                continue;
+            }
+            match &func.args[1] {
+                Expr::Literal {
+                    value: Literal::String(separator),
+                    ..
+                } => {
+                    separators.insert(separator.clone());
+                }
+                arg => panic!("expect string separator, got {arg}"),
+            }
+        }
+        assert!(separators.len() > 1);
+    }
+}
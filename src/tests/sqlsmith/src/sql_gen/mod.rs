@@ -16,10 +16,18 @@ mod ddl;
 mod dml;
 mod expr;
 mod func;
+mod noise;
 mod query;
+mod show;
 mod sql_generator;
+mod statement;
 mod types;
 
+#[cfg(test)]
+pub(crate) use sql_generator::mock_tables;
+#[cfg(test)]
+pub(crate) use sql_generator::test_generator;
 pub(crate) use sql_generator::Column;
 pub(crate) use sql_generator::SqlGenerator;
 pub(crate) use sql_generator::Table;
+pub(crate) use statement::StatementWeights;
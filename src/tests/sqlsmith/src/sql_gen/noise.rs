@@ -0,0 +1,101 @@
+// Copyright 2021 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use databend_common_ast::parser::token::TokenKind;
+use databend_common_ast::parser::tokenize_sql;
+use rand::Rng;
+
+use crate::sql_gen::SqlGenerator;
+
+impl<R: Rng> SqlGenerator<'_, R> {
+    /// Inject random whitespaces and comments between the tokens of the SQL,
+    /// to fuzz the whitespace and comment handling of the lexer and parser.
+    /// The semantics of the SQL are not changed.
+    pub(crate) fn inject_noise(&mut self, sql: &str) -> String {
+        let Ok(tokens) = tokenize_sql(sql) else {
+            return sql.to_string();
+        };
+        let mut noisy_sql = String::with_capacity(sql.len() * 2);
+        for (i, token) in tokens.iter().enumerate() {
+            if token.kind == TokenKind::EOI {
+                break;
+            }
+            if i > 0 {
+                self.gen_noise(&mut noisy_sql);
+            }
+            noisy_sql.push_str(token.text());
+        }
+        noisy_sql
+    }
+
+    fn gen_noise(&mut self, sql: &mut String) {
+        match self.rng.gen_range(0..=9) {
+            0..=5 => sql.push(' '),
+            6 => {
+                let whitespaces = ["\t", "\n", "\r\n", "  ", " \t\n "];
+                sql.push_str(whitespaces[self.rng.gen_range(0..whitespaces.len())]);
+            }
+            7 => {
+                let comment = self.gen_random_name();
+                sql.push_str(&format!(" -- {comment}\n"));
+            }
+            8 => {
+                let comment = self.gen_random_name();
+                sql.push_str(&format!(" /* {comment} */ "));
+            }
+            9 => {
+                let comment = self.gen_random_name();
+                sql.push_str(&format!("\n/* {comment}\n{comment} */\t"));
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use databend_common_ast::parser::parse_sql;
+    use databend_common_ast::parser::tokenize_sql;
+    use databend_common_ast::parser::Dialect;
+
+    use crate::sql_gen::test_generator;
+
+    fn parse(sql: &str) -> Option<String> {
+        let tokens = tokenize_sql(sql).ok()?;
+        let (stmt, _) = parse_sql(&tokens, Dialect::PostgreSQL).ok()?;
+        Some(stmt.to_string())
+    }
+
+    #[test]
+    fn test_inject_noise() {
+        let mut generator = test_generator();
+
+        let mut num_checked = 0;
+        for _ in 0..100 {
+            let sql = generator.gen_query().to_string();
+            let Some(expected) = parse(&sql) else {
+                continue;
+            };
+            let noisy_sql = generator.inject_noise(&sql);
+            assert_eq!(
+                parse(&noisy_sql),
+                Some(expected),
+                "noisy sql: {}",
+                noisy_sql
+            );
+            num_checked += 1;
+        }
+        assert!(num_checked > 0);
+    }
+}
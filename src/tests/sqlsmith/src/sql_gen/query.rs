@@ -14,10 +14,16 @@
 
 use std::mem;
 
+use databend_common_ast::ast::BinaryOperator;
+use databend_common_ast::ast::ColumnFilter;
+use databend_common_ast::ast::ColumnID;
+use databend_common_ast::ast::ColumnRef;
 use databend_common_ast::ast::Expr;
 use databend_common_ast::ast::FunctionCall;
 use databend_common_ast::ast::GroupBy;
 use databend_common_ast::ast::Identifier;
+use databend_common_ast::ast::Indirection;
+use databend_common_ast::ast::IntervalKind;
 use databend_common_ast::ast::Join;
 use databend_common_ast::ast::JoinCondition;
 use databend_common_ast::ast::JoinOperator;
@@ -29,7 +35,13 @@ use databend_common_ast::ast::SelectTarget;
 use databend_common_ast::ast::SetExpr;
 use databend_common_ast::ast::TableAlias;
 use databend_common_ast::ast::TableReference;
+use databend_common_ast::ast::TemporalClause;
+use databend_common_ast::ast::TimeTravelPoint;
+use databend_common_ast::ast::UnaryOperator;
+use databend_common_ast::ast::Window;
 use databend_common_ast::ast::WindowDefinition;
+use databend_common_ast::ast::WindowDesc;
+use databend_common_ast::ast::WindowSpec;
 use databend_common_ast::ast::With;
 use databend_common_ast::ast::CTE;
 use databend_common_expression::infer_schema_type;
@@ -53,10 +65,16 @@ impl<R: Rng> SqlGenerator<'_, R> {
         self.is_join = false;
 
         let with = self.gen_with();
-        let body = self.gen_set_expr();
+        let mut body = self.gen_set_expr();
         let limit = self.gen_limit();
         let offset = self.gen_offset(limit.len());
-        let order_by = self.gen_order_by(self.group_by.clone());
+        let mut order_by = self.gen_order_by(self.group_by.clone());
+        if let SetExpr::Select(select) = &mut body {
+            if select.group_by.is_none() && self.rng.gen_bool(0.2) {
+                let order_by_expr = self.gen_window_order_by(select);
+                order_by.insert(0, order_by_expr);
+            }
+        }
 
         Query {
             span: None,
@@ -69,15 +87,78 @@ impl<R: Rng> SqlGenerator<'_, R> {
         }
     }
 
+    /// Generate a query whose SQL is at most `max_sql_bytes` long, the query is
+    /// regenerated with fewer select targets and shallower expressions until it fits.
+    pub(crate) fn gen_query_within(&mut self, max_sql_bytes: usize) -> Query {
+        let max_expr_depth = self.max_expr_depth;
+        let max_select_targets = self.max_select_targets;
+
+        let mut query = self.gen_query();
+        let mut retries = 0;
+        while query.to_string().len() > max_sql_bytes {
+            if self.max_select_targets > 1 {
+                self.max_select_targets /= 2;
+            } else if self.max_expr_depth > 0 {
+                self.max_expr_depth -= 1;
+            } else if retries < 10 {
+                // the least complex queries still vary in the tables and clauses
+                retries += 1;
+            } else {
+                query = Self::gen_trivial_query();
+                break;
+            }
+            self.expr_depth = self.max_expr_depth;
+            query = self.gen_query();
+        }
+
+        self.max_expr_depth = max_expr_depth;
+        self.max_select_targets = max_select_targets;
+        self.expr_depth = max_expr_depth;
+        query
+    }
+
+    // `SELECT 1`, the fallback if no generated query is short enough.
+    fn gen_trivial_query() -> Query {
+        let select = SelectStmt {
+            span: None,
+            hints: None,
+            distinct: false,
+            top_n: None,
+            select_list: vec![SelectTarget::AliasedExpr {
+                expr: Box::new(Expr::Literal {
+                    span: None,
+                    value: Literal::UInt64(1),
+                }),
+                alias: None,
+            }],
+            from: vec![],
+            selection: None,
+            group_by: None,
+            having: None,
+            window_list: None,
+            qualify: None,
+        };
+        Query {
+            span: None,
+            with: None,
+            body: SetExpr::Select(Box::new(select)),
+            order_by: vec![],
+            limit: vec![],
+            offset: None,
+            ignore_result: false,
+        }
+    }
+
     // Scalar, IN / NOT IN, ANY / SOME / ALL Subquery must return only one column
     // EXISTS / NOT EXISTS Subquery can return any columns
     pub(crate) fn gen_subquery(&mut self, one_column: bool) -> (Query, TableSchemaRef) {
-        let current_cte_tables = mem::take(&mut self.cte_tables);
+        // CTEs are visible in the subquery, including the CTEs defined earlier
+        // in the same `WITH` clause.
+        let current_cte_tables = self.cte_tables.clone();
         let current_bound_tables = mem::take(&mut self.bound_tables);
         let current_bound_columns = mem::take(&mut self.bound_columns);
         let current_is_join = self.is_join;
 
-        self.cte_tables = vec![];
         self.bound_tables = vec![];
         self.bound_columns = vec![];
         self.is_join = false;
@@ -144,6 +225,115 @@ impl<R: Rng> SqlGenerator<'_, R> {
         (query, schema)
     }
 
+    // EXISTS subquery correlated on two or more outer columns, like
+    // `SELECT 1 FROM t2 AS tx WHERE t1.a = tx.a AND t1.b = tx.b`,
+    // returns `None` if there are not enough columns in the outer scope.
+    pub(crate) fn gen_correlated_exists_subquery(&mut self) -> Option<Query> {
+        // the outer columns must be qualified to be distinguished from the inner columns
+        let mut outer_columns = self
+            .bound_columns
+            .iter()
+            .filter(|column| column.table_name.is_some())
+            .cloned()
+            .collect::<Vec<_>>();
+        if outer_columns.len() < 2 || self.tables.is_empty() {
+            return None;
+        }
+
+        let table = self.tables[self.rng.gen_range(0..self.tables.len())].clone();
+        let alias = Identifier::from_name(None, format!("t{}", self.gen_random_name()));
+        let inner_columns = table
+            .schema
+            .fields()
+            .iter()
+            .map(|field| (field.name.clone(), DataType::from(field.data_type())))
+            .collect::<Vec<_>>();
+
+        let len = self.rng.gen_range(2..=outer_columns.len().min(4));
+        let mut selection: Option<Expr> = None;
+        for _ in 0..len {
+            let outer_column = outer_columns.remove(self.rng.gen_range(0..outer_columns.len()));
+            let left = Expr::ColumnRef {
+                span: None,
+                column: ColumnRef {
+                    database: None,
+                    table: outer_column.table_name.clone(),
+                    column: ColumnID::Name(Identifier::from_name(None, outer_column.name)),
+                },
+            };
+            // compare with an inner column of the same type if there is one
+            let right = match inner_columns
+                .iter()
+                .find(|(_, ty)| *ty == outer_column.data_type)
+            {
+                Some((name, _)) => Expr::ColumnRef {
+                    span: None,
+                    column: ColumnRef {
+                        database: None,
+                        table: Some(alias.clone()),
+                        column: ColumnID::Name(Identifier::from_name(None, name)),
+                    },
+                },
+                None => self.gen_scalar_value(&outer_column.data_type),
+            };
+            let pred = Expr::BinaryOp {
+                span: None,
+                op: BinaryOperator::Eq,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+            selection = Some(match selection {
+                Some(selection) => Expr::BinaryOp {
+                    span: None,
+                    op: BinaryOperator::And,
+                    left: Box::new(selection),
+                    right: Box::new(pred),
+                },
+                None => pred,
+            });
+        }
+
+        let mut table_ref = Self::table_reference(&table);
+        if let TableReference::Table {
+            alias: table_alias, ..
+        } = &mut table_ref
+        {
+            *table_alias = Some(TableAlias {
+                name: alias,
+                columns: vec![],
+            });
+        }
+        let select = SelectStmt {
+            span: None,
+            hints: None,
+            distinct: false,
+            top_n: None,
+            select_list: vec![SelectTarget::AliasedExpr {
+                expr: Box::new(Expr::Literal {
+                    span: None,
+                    value: Literal::UInt64(1),
+                }),
+                alias: None,
+            }],
+            from: vec![table_ref],
+            selection,
+            group_by: None,
+            having: None,
+            window_list: None,
+            qualify: None,
+        };
+
+        Some(Query {
+            span: None,
+            with: None,
+            body: SetExpr::Select(Box::new(select)),
+            order_by: vec![],
+            limit: vec![],
+            offset: None,
+            ignore_result: false,
+        })
+    }
+
     fn gen_with(&mut self) -> Option<With> {
         if self.rng.gen_bool(0.8) {
             return None;
@@ -180,16 +370,397 @@ impl<R: Rng> SqlGenerator<'_, R> {
     }
 
     fn gen_set_expr(&mut self) -> SetExpr {
-        match self.rng.gen_range(0..=9) {
-            0..=9 => {
+        match self.rng.gen_range(0..=11) {
+            0..=8 => {
                 let select = self.gen_select();
                 SetExpr::Select(Box::new(select))
             }
+            9 => {
+                let select = self.gen_layered_window_select();
+                SetExpr::Select(Box::new(select))
+            }
+            10 => {
+                let select = self.gen_agg_window_select();
+                SetExpr::Select(Box::new(select))
+            }
+            11 => {
+                let select = self.gen_distinct_agg_select();
+                SetExpr::Select(Box::new(select))
+            }
             // TODO
             _ => unreachable!(),
         }
     }
 
+    // Window functions can't be nested directly, layer them with a subquery like
+    // `SELECT sum(tx.w) OVER (ORDER BY tx.w) FROM (SELECT row_number() OVER (..) AS w FROM t1) AS tx`,
+    // the outer window function takes the result of the inner window function.
+    pub(crate) fn gen_layered_window_select(&mut self) -> SelectStmt {
+        self.group_by = None;
+        let (subquery, schema) = self.gen_window_subquery();
+        let (table, alias) = self.gen_subquery_table(schema);
+        self.bound_table(table.clone());
+
+        let column_ref = |name: &str| Expr::ColumnRef {
+            span: None,
+            column: ColumnRef {
+                database: None,
+                table: Some(table.name.clone()),
+                column: ColumnID::Name(Identifier::from_name(None, name)),
+            },
+        };
+        // the first column is the result of the inner window function
+        let fields = table.schema.fields();
+        let window_column = column_ref(&fields[0].name);
+        // partition by some of the other columns which are comparable
+        let partition_by = fields[1..]
+            .iter()
+            .filter(|field| {
+                matches!(
+                    field.data_type().remove_nullable(),
+                    TableDataType::Boolean
+                        | TableDataType::String
+                        | TableDataType::Number(_)
+                        | TableDataType::Date
+                        | TableDataType::Timestamp
+                )
+            })
+            .filter(|_| self.flip_coin())
+            .map(|field| column_ref(&field.name))
+            .collect::<Vec<_>>();
+
+        let name = [
+            "sum",
+            "avg",
+            "min",
+            "max",
+            "first_value",
+            "last_value",
+            "lag",
+        ][self.rng.gen_range(0..=6)];
+        let window_func = Expr::FunctionCall {
+            span: None,
+            func: FunctionCall {
+                distinct: false,
+                name: Identifier::from_name(None, name),
+                args: vec![window_column.clone()],
+                params: vec![],
+                window: Some(WindowDesc {
+                    ignore_nulls: None,
+                    window: Window::WindowSpec(WindowSpec {
+                        existing_window_name: None,
+                        partition_by,
+                        order_by: vec![OrderByExpr {
+                            expr: window_column,
+                            asc: None,
+                            nulls_first: None,
+                        }],
+                        window_frame: None,
+                    }),
+                }),
+                lambda: None,
+            },
+        };
+
+        let mut select_list = fields
+            .iter()
+            .map(|field| SelectTarget::AliasedExpr {
+                expr: Box::new(column_ref(&field.name)),
+                alias: None,
+            })
+            .collect::<Vec<_>>();
+        select_list.push(SelectTarget::AliasedExpr {
+            expr: Box::new(window_func),
+            alias: None,
+        });
+
+        SelectStmt {
+            span: None,
+            hints: None,
+            distinct: false,
+            top_n: None,
+            select_list,
+            from: vec![TableReference::Subquery {
+                span: None,
+                lateral: false,
+                subquery: Box::new(subquery),
+                alias: Some(alias),
+                pivot: None,
+                unpivot: None,
+            }],
+            selection: None,
+            group_by: None,
+            having: None,
+            window_list: None,
+            qualify: None,
+        }
+    }
+
+    // The subquery of the layered window query, the first column is a window function.
+    fn gen_window_subquery(&mut self) -> (Query, TableSchemaRef) {
+        let current_bound_tables = mem::take(&mut self.bound_tables);
+        let current_bound_columns = mem::take(&mut self.bound_columns);
+        let current_is_join = self.is_join;
+        let current_windows_name = mem::take(&mut self.windows_name);
+        self.is_join = false;
+
+        let from = self.gen_from();
+
+        let name = self.gen_random_name();
+        let len = self.rng.gen_range(1..=3);
+        let mut fields = Vec::with_capacity(len);
+        let mut select_list = Vec::with_capacity(len);
+        for i in 0..len {
+            let (col_name, ty, expr) = if i == 0 {
+                let ty = if self.flip_coin() {
+                    DataType::Number(NumberDataType::UInt64)
+                } else {
+                    DataType::Number(NumberDataType::Float64)
+                };
+                let expr = self.gen_window_func(&ty);
+                (format!("w{}", name), ty, expr)
+            } else {
+                let ty = self.gen_simple_data_type();
+                let expr = self.gen_simple_expr(&ty);
+                (format!("c{}{}", name, i), ty, expr)
+            };
+            let table_type = infer_schema_type(&ty).unwrap();
+            fields.push(TableField::new(&col_name, table_type));
+            select_list.push(SelectTarget::AliasedExpr {
+                expr: Box::new(expr),
+                alias: Some(Identifier::from_name(None, col_name)),
+            });
+        }
+        let schema = TableSchemaRefExt::create(fields);
+
+        let select = SelectStmt {
+            span: None,
+            hints: None,
+            distinct: false,
+            top_n: None,
+            select_list,
+            from,
+            selection: None,
+            group_by: None,
+            having: None,
+            window_list: None,
+            qualify: None,
+        };
+        let query = Query {
+            span: None,
+            with: None,
+            body: SetExpr::Select(Box::new(select)),
+            order_by: vec![],
+            limit: vec![],
+            offset: None,
+            ignore_result: false,
+        };
+
+        self.bound_tables = current_bound_tables;
+        self.bound_columns = current_bound_columns;
+        self.is_join = current_is_join;
+        self.windows_name = current_windows_name;
+
+        (query, schema)
+    }
+
+    // Mix the aggregates, the window functions over the aggregates and the scalar expressions
+    // in one select list, like
+    // `SELECT count(..), count(..) + 1, sum(count(..)) OVER (PARTITION BY c1), c1 FROM t GROUP BY c1`,
+    // the window functions are evaluated after the aggregation, and only reference the
+    // grouping columns and the aggregates.
+    pub(crate) fn gen_agg_window_select(&mut self) -> SelectStmt {
+        self.windows_name.clear();
+        let from = self.gen_from();
+        // aggregate the whole input if there are no columns to group by
+        let group_items = self.gen_group_columns();
+
+        // the aggregate function returns a number, so it can be the argument of the window function
+        let mut agg_expr = self.gen_agg_func(&DataType::Number(NumberDataType::UInt64));
+        if let Expr::FunctionCall { func, .. } = &mut agg_expr {
+            func.window = None;
+        }
+        let scalar_expr = Expr::BinaryOp {
+            span: None,
+            op: BinaryOperator::Plus,
+            left: Box::new(agg_expr.clone()),
+            right: Box::new(Expr::Literal {
+                span: None,
+                value: Literal::UInt64(self.rng.gen_range(0..=10)),
+            }),
+        };
+
+        let partition_by = group_items
+            .iter()
+            .filter(|_| self.flip_coin())
+            .cloned()
+            .collect::<Vec<_>>();
+        let order_by = if group_items.is_empty() || self.flip_coin() {
+            agg_expr.clone()
+        } else {
+            group_items[self.rng.gen_range(0..group_items.len())].clone()
+        };
+        let name = ["sum", "avg", "min", "max", "rank", "row_number"][self.rng.gen_range(0..=5)];
+        let args = if name == "rank" || name == "row_number" {
+            vec![]
+        } else {
+            vec![agg_expr.clone()]
+        };
+        let window_expr = Expr::FunctionCall {
+            span: None,
+            func: FunctionCall {
+                distinct: false,
+                name: Identifier::from_name(None, name),
+                args,
+                params: vec![],
+                window: Some(WindowDesc {
+                    ignore_nulls: None,
+                    window: Window::WindowSpec(WindowSpec {
+                        existing_window_name: None,
+                        partition_by,
+                        order_by: vec![OrderByExpr {
+                            expr: order_by,
+                            asc: None,
+                            nulls_first: None,
+                        }],
+                        window_frame: None,
+                    }),
+                }),
+                lambda: None,
+            },
+        };
+
+        let mut select_list = Vec::with_capacity(group_items.len() + 3);
+        for expr in [agg_expr, scalar_expr, window_expr]
+            .into_iter()
+            .chain(group_items.iter().cloned())
+        {
+            select_list.push(SelectTarget::AliasedExpr {
+                expr: Box::new(expr),
+                alias: None,
+            });
+        }
+        let group_by = if group_items.is_empty() {
+            None
+        } else {
+            Some(GroupBy::Normal(group_items))
+        };
+        self.group_by = group_by.clone();
+
+        SelectStmt {
+            span: None,
+            hints: None,
+            distinct: false,
+            top_n: None,
+            select_list,
+            from,
+            selection: self.gen_selection(),
+            group_by,
+            having: None,
+            window_list: None,
+            qualify: None,
+        }
+    }
+
+    // Combine the top-level `DISTINCT` with the aggregation, like
+    // `SELECT DISTINCT count(..), c1 FROM t GROUP BY c1, c2`. The `DISTINCT` applies to the
+    // final projection rather than the groups, the groups that differ only in the unprojected
+    // grouping items are deduplicated if their aggregates are the same.
+    pub(crate) fn gen_distinct_agg_select(&mut self) -> SelectStmt {
+        self.windows_name.clear();
+        let from = self.gen_from();
+
+        let mut group_items = self.gen_group_columns();
+        if group_items.is_empty() {
+            group_items.push(self.gen_expr(&DataType::Boolean));
+        }
+
+        let agg_num = self.rng.gen_range(1..=2);
+        let mut select_list = Vec::with_capacity(agg_num + group_items.len());
+        for _ in 0..agg_num {
+            let ty = self.gen_data_type();
+            let mut agg_expr = self.gen_agg_func(&ty);
+            if let Expr::FunctionCall { func, .. } = &mut agg_expr {
+                func.window = None;
+            }
+            select_list.push(SelectTarget::AliasedExpr {
+                expr: Box::new(agg_expr),
+                alias: None,
+            });
+        }
+        // only some of the grouping items are projected, so the rows can be duplicated
+        let projected_items = group_items
+            .iter()
+            .filter(|_| self.flip_coin())
+            .cloned()
+            .collect::<Vec<_>>();
+        for item in projected_items.iter() {
+            select_list.push(SelectTarget::AliasedExpr {
+                expr: Box::new(item.clone()),
+                alias: None,
+            });
+        }
+        let selection = self.gen_selection();
+        // the ORDER BY of a `DISTINCT` query can only reference the projected items
+        self.group_by = Some(GroupBy::Normal(projected_items));
+
+        SelectStmt {
+            span: None,
+            hints: None,
+            distinct: true,
+            top_n: None,
+            select_list,
+            from,
+            selection,
+            group_by: Some(GroupBy::Normal(group_items)),
+            having: None,
+            window_list: None,
+            qualify: None,
+        }
+    }
+
+    // Group by some of the bound columns of the comparable types, the column references
+    // are never mistaken for the positions in the select list.
+    fn gen_group_columns(&mut self) -> Vec<Expr> {
+        let columns = self
+            .bound_columns
+            .iter()
+            .filter(|column| {
+                matches!(
+                    column.data_type.remove_nullable(),
+                    DataType::Boolean
+                        | DataType::String
+                        | DataType::Number(_)
+                        | DataType::Date
+                        | DataType::Timestamp
+                )
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        if columns.is_empty() {
+            return vec![];
+        }
+        let group_num = self.rng.gen_range(1..=columns.len().min(3));
+        let mut group_items = Vec::with_capacity(group_num);
+        for _ in 0..group_num {
+            let column = &columns[self.rng.gen_range(0..columns.len())];
+            let table = if self.is_join {
+                column.table_name.clone()
+            } else {
+                None
+            };
+            group_items.push(Expr::ColumnRef {
+                span: None,
+                column: ColumnRef {
+                    database: None,
+                    table,
+                    column: ColumnID::Name(Identifier::from_name(None, column.name.clone())),
+                },
+            });
+        }
+        group_items
+    }
+
     pub(crate) fn flip_coin(&mut self) -> bool {
         self.rng.gen_bool(0.5)
     }
@@ -262,6 +833,39 @@ impl<R: Rng> SqlGenerator<'_, R> {
         orders
     }
 
+    // Order by the result of a window function, which is added to the select list
+    // with an alias, so it is in scope of the ORDER BY. The ORDER BY refers to the alias
+    // or repeats the window expression, only the alias is valid with `DISTINCT`.
+    fn gen_window_order_by(&mut self, select: &mut SelectStmt) -> OrderByExpr {
+        let ty = self.gen_simple_data_type();
+        let window_expr = self.gen_window_func(&ty);
+        let alias = Identifier::from_name(None, format!("w{}", self.gen_random_name()));
+        select.select_list.push(SelectTarget::AliasedExpr {
+            expr: Box::new(window_expr.clone()),
+            alias: Some(alias.clone()),
+        });
+        let expr = if select.distinct || self.flip_coin() {
+            Expr::ColumnRef {
+                span: None,
+                column: ColumnRef {
+                    database: None,
+                    table: None,
+                    column: ColumnID::Name(alias),
+                },
+            }
+        } else {
+            window_expr
+        };
+        OrderByExpr {
+            expr,
+            asc: Some(self.flip_coin()),
+            nulls_first: Some(self.flip_coin()),
+        }
+    }
+
+    // TODO: generate `?` / `$1` placeholders in LIMIT, predicates and values with a matching
+    // parameter list once prepared statements are supported. The `?` placeholder is only parsed
+    // in the VALUES of an insert with an attachment, its values are taken from the attached file.
     fn gen_limit(&mut self) -> Vec<Expr> {
         let mut res = Vec::new();
         if self.flip_coin() {
@@ -294,8 +898,7 @@ impl<R: Rng> SqlGenerator<'_, R> {
     fn gen_select(&mut self) -> SelectStmt {
         self.windows_name.clear();
         let from = self.gen_from();
-        let group_by = self.gen_group_by();
-        self.group_by = group_by.clone();
+        let mut group_by = self.gen_group_by();
         let window_list = self.gen_window_list();
         if let Some(window_list) = window_list {
             for window in window_list {
@@ -303,6 +906,8 @@ impl<R: Rng> SqlGenerator<'_, R> {
             }
         }
         let select_list = self.gen_select_list(&group_by);
+        self.gen_group_by_positions(&mut group_by, select_list.len());
+        self.group_by = group_by.clone();
         let selection = self.gen_selection();
 
         let having = self.gen_selection();
@@ -358,13 +963,19 @@ impl<R: Rng> SqlGenerator<'_, R> {
         if self.rng.gen_bool(0.8) {
             return None;
         }
+        if self.rng.gen_bool(0.2) {
+            if let Some(bucket) = self.gen_time_bucket() {
+                return Some(GroupBy::Normal(vec![bucket]));
+            }
+        }
         let group_cap = self.rng.gen_range(1..=5);
         let mut groupby_items = Vec::with_capacity(group_cap);
 
-        for _ in 0..group_cap {
+        for i in 0..group_cap {
             let ty = self.gen_data_type();
             let groupby_item = self.gen_expr(&ty);
-            let groupby_item = self.rewrite_position_expr(groupby_item);
+            // the first select target is the aggregate function
+            let groupby_item = self.rewrite_position_expr(groupby_item, i as u64 + 2);
             groupby_items.push(groupby_item);
         }
 
@@ -376,6 +987,87 @@ impl<R: Rng> SqlGenerator<'_, R> {
         }
     }
 
+    // Replace some of the grouping items with their positions in the select list,
+    // like `GROUP BY 2, 3`, the aggregate function at position 1 is never referenced.
+    fn gen_group_by_positions(&mut self, group_by: &mut Option<GroupBy>, select_len: usize) {
+        let Some(GroupBy::Normal(items)) = group_by else {
+            return;
+        };
+        if self.rng.gen_bool(0.5) {
+            return;
+        }
+        for (i, item) in items.iter_mut().enumerate() {
+            if self.flip_coin() {
+                *item = Expr::Literal {
+                    span: None,
+                    value: Literal::UInt64(i as u64 + 2),
+                };
+            }
+        }
+        if self.gen_error_cases && self.rng.gen_bool(0.2) {
+            // position out of range of the select list
+            let pos = self.rng.gen_range(select_len + 1..=select_len + 3);
+            items.push(Expr::Literal {
+                span: None,
+                value: Literal::UInt64(pos as u64),
+            });
+        }
+    }
+
+    // Bucket a timestamp column by time units to fuzz time-series aggregation.
+    pub(crate) fn gen_time_bucket(&mut self) -> Option<Expr> {
+        if !self
+            .bound_columns
+            .iter()
+            .any(|column| column.data_type == DataType::Timestamp)
+        {
+            return None;
+        }
+        let column = self.gen_column(&DataType::Timestamp);
+        let bucket = match self.rng.gen_range(0..=1) {
+            0 => {
+                // DATE_TRUNC only supports these units
+                let unit = match self.rng.gen_range(0..=6) {
+                    0 => IntervalKind::Year,
+                    1 => IntervalKind::Quarter,
+                    2 => IntervalKind::Month,
+                    3 => IntervalKind::Day,
+                    4 => IntervalKind::Hour,
+                    5 => IntervalKind::Minute,
+                    6 => IntervalKind::Second,
+                    _ => unreachable!(),
+                };
+                Expr::DateTrunc {
+                    span: None,
+                    unit,
+                    date: Box::new(column),
+                }
+            }
+            1 => {
+                let name = match self.rng.gen_range(0..=3) {
+                    0 => "time_slot",
+                    1 => "to_start_of_five_minutes",
+                    2 => "to_start_of_ten_minutes",
+                    3 => "to_start_of_fifteen_minutes",
+                    _ => unreachable!(),
+                };
+                Expr::FunctionCall {
+                    span: None,
+                    func: FunctionCall {
+                        distinct: false,
+                        name: Identifier::from_name(None, name),
+                        args: vec![column],
+                        params: vec![],
+                        window: None,
+                        lambda: None,
+                    },
+                }
+            }
+            _ => unreachable!(),
+        };
+        Some(bucket)
+    }
+
     fn gen_select_list(&mut self, group_by: &Option<GroupBy>) -> Vec<SelectTarget> {
         let mut targets = Vec::with_capacity(5);
 
@@ -398,7 +1090,7 @@ impl<R: Rng> SqlGenerator<'_, R> {
                 }));
             }
             Some(GroupBy::All) => {
-                let select_num = self.rng.gen_range(1..=5);
+                let select_num = self.rng.gen_range(1..=self.max_select_targets.min(5));
                 for _ in 0..select_num {
                     let ty = self.gen_data_type();
                     let expr = if self.rng.gen_bool(0.8) {
@@ -422,25 +1114,110 @@ impl<R: Rng> SqlGenerator<'_, R> {
                     alias: None,
                 }));
             }
-            _ => {
-                let select_num = self.rng.gen_range(1..=7);
-                for _ in 0..select_num {
-                    let ty = self.gen_data_type();
-                    let expr = self.gen_expr(&ty);
-                    let target = generate_target(expr);
-                    targets.push(target);
+            _ => {
+                if !self.is_join && self.bound_tables.len() == 1 && self.rng.gen_bool(0.1) {
+                    let expand = self.flip_coin();
+                    return self.gen_star_targets(expand);
+                }
+                if self.rng.gen_bool(0.2) {
+                    if let Some(target) = self.gen_star_exclude_target() {
+                        targets.push(target);
+                    }
+                }
+                let select_num = self.rng.gen_range(1..=self.max_select_targets);
+                for _ in 0..select_num {
+                    let ty = self.gen_data_type();
+                    let expr = self.gen_expr(&ty);
+                    let target = generate_target(expr);
+                    targets.push(target);
+                }
+            }
+        }
+
+        targets
+    }
+
+    // `SELECT *` or the equivalent list of all the columns of the bound tables in order,
+    // the two forms of the same query should return the same result, which tests the star expansion.
+    pub(crate) fn gen_star_targets(&mut self, expand: bool) -> Vec<SelectTarget> {
+        if !expand {
+            return vec![SelectTarget::StarColumns {
+                qualified: vec![Indirection::Star(None)],
+                column_filter: None,
+            }];
+        }
+        let mut targets = vec![];
+        for table in &self.bound_tables {
+            for field in table.schema.fields() {
+                let column = Expr::ColumnRef {
+                    span: None,
+                    column: ColumnRef {
+                        database: None,
+                        table: Some(table.name.clone()),
+                        column: ColumnID::Name(Identifier::from_name(None, field.name())),
+                    },
+                };
+                targets.push(SelectTarget::AliasedExpr {
+                    expr: Box::new(column),
+                    alias: None,
+                });
+            }
+        }
+        targets
+    }
+
+    // `t.* EXCLUDE (c1, c2)` on a bound table, excluding all columns is an error case.
+    pub(crate) fn gen_star_exclude_target(&mut self) -> Option<SelectTarget> {
+        if self.bound_tables.is_empty() {
+            return None;
+        }
+        let idx = self.rng.gen_range(0..self.bound_tables.len());
+        let table = self.bound_tables[idx].clone();
+        let fields = table.schema.fields();
+
+        let excludes = if self.gen_error_cases && self.rng.gen_bool(0.2) {
+            fields
+                .iter()
+                .map(|f| Identifier::from_name(None, f.name()))
+                .collect::<Vec<_>>()
+        } else {
+            // at least one column must be left
+            if fields.len() < 2 {
+                return None;
+            }
+            let len = self.rng.gen_range(1..fields.len());
+            let mut excludes: Vec<Identifier> = Vec::with_capacity(len);
+            for _ in 0..len {
+                let name = fields[self.rng.gen_range(0..fields.len())].name();
+                if !excludes.iter().any(|e| &e.name == name) {
+                    excludes.push(Identifier::from_name(None, name));
                 }
             }
-        }
+            excludes
+        };
 
-        targets
+        Some(SelectTarget::StarColumns {
+            qualified: vec![
+                Indirection::Identifier(table.name.clone()),
+                Indirection::Star(None),
+            ],
+            column_filter: Some(ColumnFilter::Excludes(excludes)),
+        })
     }
 
     fn gen_from(&mut self) -> Vec<TableReference> {
         let mut table_refs = vec![];
+        // reference a CTE multiple times
+        if !self.cte_tables.is_empty() && self.rng.gen_bool(0.3) {
+            self.is_join = true;
+            let join = self.gen_cte_self_join();
+            table_refs.push(join);
+            return table_refs;
+        }
         // TODO: generate more table reference
+        // TODO: generate `MATCH_RECOGNIZE` once the parser supports row pattern matching
         // let table_ref_num = self.rng.gen_range(1..=3);
-        match self.rng.gen_range(0..=10) {
+        match self.rng.gen_range(0..=12) {
             0..=6 => {
                 let (table_ref, _) = self.gen_table_ref();
                 table_refs.push(table_ref);
@@ -460,6 +1237,14 @@ impl<R: Rng> SqlGenerator<'_, R> {
                 let table_func = self.gen_table_func();
                 table_refs.push(table_func);
             }
+            11 => {
+                let system_table = self.gen_system_table_ref();
+                table_refs.push(system_table);
+            }
+            12 => {
+                let (unnest, _) = self.gen_unnest_table_ref();
+                table_refs.push(unnest);
+            }
             // TODO
             _ => unreachable!(),
         }
@@ -477,8 +1262,77 @@ impl<R: Rng> SqlGenerator<'_, R> {
         };
         let schema = table.schema.clone();
         self.bound_table(table.clone());
+        let mut table_ref = Self::table_reference(&table);
+        // only the user tables have the history to travel to
+        if i < self.tables.len() && self.rng.gen_bool(0.1) {
+            if let TableReference::Table { temporal, .. } = &mut table_ref {
+                let point = self.gen_time_travel_point();
+                *temporal = Some(TemporalClause::TimeTravel(point));
+            }
+        }
+        (table_ref, schema)
+    }
+
+    // The point of the history of a table, like `AT (TIMESTAMP => now())`.
+    // The tables are created at the start of the run, so only the current time is always
+    // in their history. The old timestamps, the offsets and the made-up snapshot ids
+    // are only generated for the error cases.
+    fn gen_time_travel_point(&mut self) -> TimeTravelPoint {
+        if !self.gen_error_cases || self.flip_coin() {
+            let now = Expr::FunctionCall {
+                span: None,
+                func: FunctionCall {
+                    distinct: false,
+                    name: Identifier::from_name(None, "now"),
+                    args: vec![],
+                    params: vec![],
+                    window: None,
+                    lambda: None,
+                },
+            };
+            return TimeTravelPoint::Timestamp(Box::new(now));
+        }
+        match self.rng.gen_range(0..=2) {
+            0 => {
+                let snapshot_id = (0..32)
+                    .map(|_| char::from_digit(self.rng.gen_range(0..16), 16).unwrap())
+                    .collect();
+                TimeTravelPoint::Snapshot(snapshot_id)
+            }
+            1 => {
+                let timestamp = Expr::Literal {
+                    span: None,
+                    value: Literal::String("2000-01-01 00:00:00".to_string()),
+                };
+                TimeTravelPoint::Timestamp(Box::new(timestamp))
+            }
+            2 => {
+                let seconds = Expr::Literal {
+                    span: None,
+                    value: Literal::UInt64(self.rng.gen_range(3600..=86400)),
+                };
+                let offset = Expr::UnaryOp {
+                    span: None,
+                    op: UnaryOperator::Minus,
+                    expr: Box::new(seconds),
+                };
+                TimeTravelPoint::Offset(Box::new(offset))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    // Query the system tables to fuzz the metadata query paths,
+    // which are different from the scans of user tables.
+    pub(crate) fn gen_system_table_ref(&mut self) -> TableReference {
+        let mut tables = system_tables();
+        let table = tables.remove(self.rng.gen_range(0..tables.len()));
+        self.bound_table(table.clone());
+        Self::table_reference(&table)
+    }
 
-        let table_ref = TableReference::Table {
+    fn table_reference(table: &Table) -> TableReference {
+        TableReference::Table {
             span: None,
             // TODO
             catalog: None,
@@ -495,8 +1349,7 @@ impl<R: Rng> SqlGenerator<'_, R> {
             // TODO
             unpivot: None,
             sample: None,
-        };
-        (table_ref, schema)
+        }
     }
 
     // Only test:
@@ -612,6 +1465,117 @@ impl<R: Rng> SqlGenerator<'_, R> {
         }
     }
 
+    // `unnest` as a table source, the only output column `value` has the element type of the array.
+    // Either `FROM unnest(array_expr)`, or a lateral join that unnests an array column of a table,
+    // maps are unnested into their keys or values.
+    // TODO: generate `WITH ORDINALITY` once the parser supports it
+    pub(crate) fn gen_unnest_table_ref(&mut self) -> (TableReference, TableSchemaRef) {
+        let name = self.gen_random_name();
+        let alias_name = Identifier::from_name(None, format!("t{}", name));
+        let alias = Some(TableAlias {
+            name: alias_name.clone(),
+            columns: vec![],
+        });
+
+        let left = if self.flip_coin() {
+            let (table_ref, schema) = self.gen_table_ref();
+            let table_name = self.bound_tables.last().unwrap().name.clone();
+            let columns = schema
+                .fields()
+                .iter()
+                .filter(|field| {
+                    matches!(
+                        field.data_type.remove_nullable(),
+                        TableDataType::Array(_) | TableDataType::Map(_)
+                    )
+                })
+                .map(|field| (field.name.clone(), DataType::from(&field.data_type)))
+                .collect::<Vec<_>>();
+            // no array or map columns to unnest, use the table directly
+            if columns.is_empty() {
+                return (table_ref, schema);
+            }
+            let (column_name, column_ty) = columns[self.rng.gen_range(0..columns.len())].clone();
+            Some((table_ref, table_name, column_name, column_ty))
+        } else {
+            None
+        };
+
+        let (lateral, param, elem_ty) = match left {
+            Some((_, ref table_name, ref column_name, ref column_ty)) => {
+                let column = Expr::ColumnRef {
+                    span: None,
+                    column: ColumnRef {
+                        database: None,
+                        table: Some(table_name.clone()),
+                        column: ColumnID::Name(Identifier::from_name(None, column_name)),
+                    },
+                };
+                match column_ty.remove_nullable() {
+                    DataType::Array(box elem_ty) => (true, column, elem_ty),
+                    DataType::Map(box DataType::Tuple(kv_tys)) => {
+                        let (func_name, elem_ty) = if self.flip_coin() {
+                            ("map_keys", kv_tys[0].clone())
+                        } else {
+                            ("map_values", kv_tys[1].clone())
+                        };
+                        let param = Expr::FunctionCall {
+                            span: None,
+                            func: FunctionCall {
+                                distinct: false,
+                                name: Identifier::from_name(None, func_name),
+                                args: vec![column],
+                                params: vec![],
+                                window: None,
+                                lambda: None,
+                            },
+                        };
+                        (true, param, elem_ty)
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            None => {
+                let elem_ty = self.gen_simple_data_type();
+                let param = self.gen_expr(&DataType::Array(Box::new(elem_ty.clone())));
+                (false, param, elem_ty)
+            }
+        };
+
+        let schema = TableSchemaRefExt::create(vec![TableField::new(
+            "value",
+            infer_schema_type(&elem_ty).unwrap(),
+        )]);
+        let table = Table::new(None, alias_name, schema.clone());
+        self.bound_table(table);
+
+        let unnest = TableReference::TableFunction {
+            span: None,
+            lateral,
+            name: Identifier::from_name(None, "unnest"),
+            params: vec![param],
+            named_params: vec![],
+            alias,
+            sample: None,
+        };
+        let table_ref = match left {
+            Some((table_ref, ..)) => {
+                self.is_join = true;
+                TableReference::Join {
+                    span: None,
+                    join: Join {
+                        op: JoinOperator::CrossJoin,
+                        condition: JoinCondition::None,
+                        left: Box::new(table_ref),
+                        right: Box::new(unnest),
+                    },
+                }
+            }
+            None => unnest,
+        };
+        (table_ref, schema)
+    }
+
     fn gen_join_table_ref(&mut self) -> TableReference {
         let (left_table, left_schema) = self.gen_table_ref();
         let (right_table, right_schema) = self.gen_table_ref();
@@ -691,10 +1655,65 @@ impl<R: Rng> SqlGenerator<'_, R> {
         TableReference::Join { span: None, join }
     }
 
+    // `cte AS a JOIN cte AS b ON ...`, fuzz whether the CTE is materialized or inlined.
+    pub(crate) fn gen_cte_self_join(&mut self) -> TableReference {
+        let idx = self.rng.gen_range(0..self.cte_tables.len());
+        let cte_table = self.cte_tables[idx].clone();
+
+        let mut table_refs = Vec::with_capacity(2);
+        for i in 0..2 {
+            let alias_name = Identifier::from_name(None, format!("{}_{}", cte_table.name.name, i));
+            let table = Table::new(None, alias_name.clone(), cte_table.schema.clone());
+            self.bound_table(table);
+            table_refs.push(TableReference::Table {
+                span: None,
+                catalog: None,
+                database: None,
+                table: cte_table.name.clone(),
+                alias: Some(TableAlias {
+                    name: alias_name,
+                    columns: vec![],
+                }),
+                temporal: None,
+                with_options: None,
+                pivot: None,
+                unpivot: None,
+                sample: None,
+            });
+        }
+        let right = table_refs.pop().unwrap();
+        let left = table_refs.pop().unwrap();
+
+        let op = match self.rng.gen_range(0..=2) {
+            0 => JoinOperator::Inner,
+            1 => JoinOperator::LeftOuter,
+            2 => JoinOperator::CrossJoin,
+            _ => unreachable!(),
+        };
+        let condition = if op == JoinOperator::CrossJoin {
+            JoinCondition::None
+        } else {
+            self.only_scalar_expr = true;
+            JoinCondition::On(Box::new(self.gen_expr(&DataType::Boolean)))
+        };
+
+        let join = Join {
+            op,
+            condition,
+            left: Box::new(left),
+            right: Box::new(right),
+        };
+        TableReference::Join { span: None, join }
+    }
+
     fn gen_subquery_table_ref(&mut self) -> TableReference {
         let (subquery, schema) = self.gen_subquery(false);
 
-        let (table, alias) = self.gen_subquery_table(schema);
+        let (table, alias) = if self.flip_coin() {
+            self.gen_renamed_subquery_table(schema)
+        } else {
+            self.gen_subquery_table(schema)
+        };
         self.bound_table(table);
 
         TableReference::Subquery {
@@ -708,6 +1727,10 @@ impl<R: Rng> SqlGenerator<'_, R> {
     }
 
     fn gen_selection(&mut self) -> Option<Expr> {
+        // a number as the predicate, like `WHERE 1`, is cast to Boolean by the filter
+        if let Some(predicate) = self.gen_coercion_arg("and_filters", &DataType::Boolean) {
+            return Some(predicate);
+        }
         match self.rng.gen_range(0..=9) {
             0..=5 => Some(self.gen_expr(&DataType::Boolean)),
             6 => {
@@ -735,6 +1758,27 @@ impl<R: Rng> SqlGenerator<'_, R> {
         (table, alias)
     }
 
+    // Derived table `FROM (SELECT .. AS c1, .. AS c2) AS t(a1, a2)`, the column aliases rename
+    // the columns of the subquery, the outer query can only reference them by the aliases.
+    fn gen_renamed_subquery_table(&mut self, schema: TableSchemaRef) -> (Table, TableAlias) {
+        let name = self.gen_random_name();
+        let table_name = Identifier::from_name(None, format!("t{}", name));
+        let mut fields = Vec::with_capacity(schema.num_fields());
+        let mut columns = Vec::with_capacity(schema.num_fields());
+        for (i, field) in schema.fields().iter().enumerate() {
+            let column_name = format!("a{}{}", name, i);
+            fields.push(TableField::new(&column_name, field.data_type().clone()));
+            columns.push(Identifier::from_name(None, column_name));
+        }
+        let alias = TableAlias {
+            name: table_name.clone(),
+            columns,
+        };
+        let table = Table::new(None, table_name, TableSchemaRefExt::create(fields));
+
+        (table, alias)
+    }
+
     pub(crate) fn bound_table(&mut self, table: Table) {
         for (i, field) in table.schema.fields().iter().enumerate() {
             let column = Column::new(
@@ -748,15 +1792,14 @@ impl<R: Rng> SqlGenerator<'_, R> {
         self.bound_tables.push(table);
     }
 
-    // rewrite position expr in group by and order by,
+    // rewrite position expr in group by to the position of the item itself in select list,
     // avoiding `GROUP BY position n is not in select list` errors
-    fn rewrite_position_expr(&mut self, expr: Expr) -> Expr {
+    fn rewrite_position_expr(&mut self, expr: Expr, pos: u64) -> Expr {
         if let Expr::Literal {
-            value: Literal::UInt64(n),
+            value: Literal::UInt64(_),
             ..
         } = expr
         {
-            let pos = n % 3 + 1;
             Expr::Literal {
                 span: None,
                 value: Literal::UInt64(pos),
@@ -766,3 +1809,765 @@ impl<R: Rng> SqlGenerator<'_, R> {
         }
     }
 }
+
+// Some of the system tables with the leading columns of their schemas,
+// the columns after the ones named by keywords are omitted
+// to keep the column positions valid.
+fn system_tables() -> Vec<Table> {
+    let system_table = |db_name: &str, name: &str, fields: Vec<TableField>| {
+        Table::new(
+            Some(Identifier::from_name(None, db_name)),
+            Identifier::from_name(None, name),
+            TableSchemaRefExt::create(fields),
+        )
+    };
+    vec![
+        system_table("system", "one", vec![TableField::new(
+            "dummy",
+            TableDataType::Number(NumberDataType::UInt8),
+        )]),
+        system_table("system", "functions", vec![
+            TableField::new("name", TableDataType::String),
+            TableField::new("is_aggregate", TableDataType::Boolean),
+            TableField::new("description", TableDataType::String),
+            TableField::new("syntax", TableDataType::String),
+            TableField::new("example", TableDataType::String),
+        ]),
+        system_table("system", "settings", vec![
+            TableField::new("name", TableDataType::String),
+            TableField::new("value", TableDataType::String),
+        ]),
+        system_table("system", "databases", vec![
+            TableField::new("catalog", TableDataType::String),
+            TableField::new("name", TableDataType::String),
+            TableField::new("database_id", TableDataType::Number(NumberDataType::UInt64)),
+            TableField::new(
+                "owner",
+                TableDataType::Nullable(Box::new(TableDataType::String)),
+            ),
+            TableField::new(
+                "dropped_on",
+                TableDataType::Nullable(Box::new(TableDataType::Timestamp)),
+            ),
+        ]),
+        system_table("information_schema", "schemata", vec![
+            TableField::new("catalog_name", TableDataType::String),
+            TableField::new("schema_name", TableDataType::String),
+            TableField::new("schema_owner", TableDataType::String),
+        ]),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use databend_common_ast::ast::BinaryOperator;
+    use databend_common_ast::ast::ColumnFilter;
+    use databend_common_ast::ast::ColumnID;
+    use databend_common_ast::ast::ColumnRef;
+    use databend_common_ast::ast::Expr;
+    use databend_common_ast::ast::GroupBy;
+    use databend_common_ast::ast::Identifier;
+    use databend_common_ast::ast::IntervalKind;
+    use databend_common_ast::ast::Literal;
+    use databend_common_ast::ast::SelectTarget;
+    use databend_common_ast::ast::SetExpr;
+    use databend_common_ast::ast::TableReference;
+    use databend_common_ast::ast::TemporalClause;
+    use databend_common_ast::ast::TimeTravelPoint;
+    use databend_common_ast::parser::parse_sql;
+    use databend_common_ast::parser::tokenize_sql;
+    use databend_common_ast::parser::Dialect;
+    use databend_common_expression::types::DataType;
+    use databend_common_expression::types::NumberDataType;
+    use databend_common_expression::TableDataType;
+    use databend_common_expression::TableField;
+    use databend_common_expression::TableSchemaRefExt;
+
+    use crate::sql_gen::mock_tables;
+    use crate::sql_gen::test_generator;
+    use crate::sql_gen::Table;
+
+    #[test]
+    fn test_star_exclude_target() {
+        let mut generator = test_generator();
+        let table = mock_tables().remove(0);
+        generator.bound_table(table.clone());
+        for _ in 0..100 {
+            let target = generator.gen_star_exclude_target().unwrap();
+            assert!(target.is_star());
+            assert!(target.to_string().contains(" EXCLUDE ("));
+            let SelectTarget::StarColumns {
+                column_filter: Some(ColumnFilter::Excludes(excludes)),
+                ..
+            } = target
+            else {
+                panic!("expect star columns with excludes");
+            };
+            assert!(!excludes.is_empty());
+            assert!(excludes.len() < table.schema.num_fields());
+            for exclude in excludes {
+                assert!(table.schema.has_field(&exclude.name));
+            }
+        }
+    }
+
+    #[test]
+    fn test_cte_referenced_twice() {
+        let mut generator = test_generator();
+        let cte_table = mock_tables().remove(0);
+        generator.cte_tables = vec![cte_table.clone()];
+        generator.is_join = true;
+
+        let TableReference::Join { join, .. } = generator.gen_cte_self_join() else {
+            panic!("expect join");
+        };
+        let mut aliases = vec![];
+        for table_ref in [*join.left, *join.right] {
+            let TableReference::Table {
+                table,
+                alias: Some(alias),
+                ..
+            } = table_ref
+            else {
+                panic!("expect table reference with alias");
+            };
+            assert_eq!(table.name, cte_table.name.name);
+            aliases.push(alias.name.name);
+        }
+        assert_ne!(aliases[0], aliases[1]);
+
+        // all references resolve to the columns of the CTE
+        assert_eq!(
+            generator.bound_columns.len(),
+            cte_table.schema.num_fields() * 2
+        );
+        for column in &generator.bound_columns {
+            let table_name = column.table_name.as_ref().unwrap();
+            assert!(aliases.contains(&table_name.name));
+            let field = cte_table.schema.field_with_name(&column.name).unwrap();
+            assert_eq!(column.data_type, DataType::from(field.data_type()));
+        }
+    }
+
+    #[test]
+    fn test_time_bucket() {
+        let mut generator = test_generator();
+        assert!(generator.gen_time_bucket().is_none());
+
+        generator.bound_table(mock_tables().remove(0));
+        for _ in 0..100 {
+            let bucket = generator.gen_time_bucket().unwrap();
+            let column = match bucket {
+                Expr::DateTrunc { unit, date, .. } => {
+                    assert!(matches!(
+                        unit,
+                        IntervalKind::Year
+                            | IntervalKind::Quarter
+                            | IntervalKind::Month
+                            | IntervalKind::Day
+                            | IntervalKind::Hour
+                            | IntervalKind::Minute
+                            | IntervalKind::Second
+                    ));
+                    *date
+                }
+                Expr::FunctionCall { mut func, .. } => {
+                    assert!(
+                        func.name.name == "time_slot" || func.name.name.starts_with("to_start_of_")
+                    );
+                    assert_eq!(func.args.len(), 1);
+                    func.args.remove(0)
+                }
+                _ => panic!("unexpected time bucket {}", bucket),
+            };
+            let Expr::ColumnRef { column, .. } = column else {
+                panic!("expect the timestamp column");
+            };
+            assert!(column.column.name() == "c3" || column.column.name() == "$4");
+        }
+    }
+
+    #[test]
+    fn test_system_table_columns() {
+        let mut generator = test_generator();
+        for _ in 0..50 {
+            generator.bound_tables.clear();
+            generator.bound_columns.clear();
+            let TableReference::Table {
+                database: Some(database),
+                table,
+                ..
+            } = generator.gen_system_table_ref()
+            else {
+                panic!("expect system table reference");
+            };
+            assert!(database.name == "system" || database.name == "information_schema");
+            let schema = generator.bound_tables[0].schema.clone();
+            assert_eq!(generator.bound_tables[0].name, table);
+
+            let types = generator
+                .bound_columns
+                .iter()
+                .map(|column| column.data_type.clone())
+                .collect::<Vec<_>>();
+            for ty in types {
+                let Expr::ColumnRef { column, .. } = generator.gen_column(&ty) else {
+                    panic!("expect column reference");
+                };
+                match column.column {
+                    ColumnID::Name(name) => assert!(schema.has_field(&name.name)),
+                    ColumnID::Position(pos) => assert!(pos.pos <= schema.num_fields()),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_correlated_exists_subquery() {
+        let mut generator = test_generator();
+        let outer = mock_tables().remove(0);
+        generator.bound_table(outer.clone());
+
+        for _ in 0..100 {
+            let query = generator.gen_correlated_exists_subquery().unwrap();
+            let SetExpr::Select(select) = &query.body else {
+                panic!("expect select");
+            };
+            let TableReference::Table { table, alias, .. } = &select.from[0] else {
+                panic!("expect table reference");
+            };
+            let inner = generator
+                .tables
+                .iter()
+                .find(|t| t.name.name == table.name)
+                .unwrap();
+            let alias = alias.as_ref().unwrap().name.name.clone();
+
+            let mut outer_columns = HashSet::new();
+            let mut pending = vec![select.selection.clone().unwrap()];
+            while let Some(expr) = pending.pop() {
+                let Expr::BinaryOp {
+                    op, left, right, ..
+                } = expr
+                else {
+                    panic!("expect binary predicate");
+                };
+                if op == BinaryOperator::And {
+                    pending.push(*left);
+                    pending.push(*right);
+                    continue;
+                }
+                assert_eq!(op, BinaryOperator::Eq);
+                let Expr::ColumnRef {
+                    column:
+                        ColumnRef {
+                            table: Some(outer_table),
+                            column: ColumnID::Name(outer_name),
+                            ..
+                        },
+                    ..
+                } = *left
+                else {
+                    panic!("expect outer column");
+                };
+                assert_eq!(outer_table.name, outer.name.name);
+                let outer_field = outer.schema.field_with_name(&outer_name.name).unwrap();
+                outer_columns.insert(outer_name.name);
+
+                if let Expr::ColumnRef {
+                    column:
+                        ColumnRef {
+                            table: Some(inner_table),
+                            column: ColumnID::Name(inner_name),
+                            ..
+                        },
+                    ..
+                } = *right
+                {
+                    assert_eq!(inner_table.name, alias);
+                    let inner_field = inner.schema.field_with_name(&inner_name.name).unwrap();
+                    assert_eq!(inner_field.data_type(), outer_field.data_type());
+                }
+            }
+            assert!(outer_columns.len() >= 2);
+        }
+    }
+
+    #[test]
+    fn test_group_by_positions() {
+        let mut generator = test_generator();
+
+        let mut group_by_all = 0;
+        let mut positions = 0;
+        for _ in 0..2000 {
+            generator.bound_tables.clear();
+            generator.bound_columns.clear();
+            let select = generator.gen_select();
+            match &select.group_by {
+                Some(GroupBy::All) => group_by_all += 1,
+                Some(GroupBy::Normal(items)) => {
+                    for item in items {
+                        if let Expr::Literal {
+                            value: Literal::UInt64(pos),
+                            ..
+                        } = item
+                        {
+                            // position 1 is the aggregate function
+                            assert!(*pos >= 2 && *pos as usize <= select.select_list.len());
+                            positions += 1;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        assert!(group_by_all > 0);
+        assert!(positions > 0);
+    }
+
+    #[test]
+    fn test_layered_window_select() {
+        let mut generator = test_generator();
+
+        let window_func = |target: &SelectTarget| match target {
+            SelectTarget::AliasedExpr { expr, .. } => match expr.as_ref() {
+                Expr::FunctionCall { func, .. } if func.window.is_some() => Some(func.clone()),
+                _ => None,
+            },
+            _ => None,
+        };
+        for _ in 0..100 {
+            generator.bound_tables.clear();
+            generator.bound_columns.clear();
+            let select = generator.gen_layered_window_select();
+            let TableReference::Subquery {
+                subquery, alias, ..
+            } = &select.from[0]
+            else {
+                panic!("expect subquery");
+            };
+            let SetExpr::Select(inner) = &subquery.body else {
+                panic!("expect select");
+            };
+            // the inner scope computes a window function
+            assert!(window_func(&inner.select_list[0]).is_some());
+
+            // the outer window function takes the column of the inner window function
+            let outer = select
+                .select_list
+                .iter()
+                .filter_map(window_func)
+                .collect::<Vec<_>>();
+            assert_eq!(outer.len(), 1);
+            let Expr::ColumnRef { column, .. } = &outer[0].args[0] else {
+                panic!("expect column reference");
+            };
+            let alias = alias.as_ref().unwrap();
+            assert_eq!(column.table.as_ref().unwrap().name, alias.name.name);
+            let SelectTarget::AliasedExpr {
+                alias: Some(inner_alias),
+                ..
+            } = &inner.select_list[0]
+            else {
+                panic!("expect aliased window function");
+            };
+            let ColumnID::Name(name) = &column.column else {
+                panic!("expect column name");
+            };
+            assert_eq!(name.name, inner_alias.name);
+        }
+    }
+
+    #[test]
+    fn test_unnest_table_ref() {
+        let mut generator = test_generator();
+        let schema = TableSchemaRefExt::create(vec![
+            TableField::new(
+                "m",
+                TableDataType::Map(Box::new(TableDataType::Tuple {
+                    fields_name: vec!["key".to_string(), "value".to_string()],
+                    fields_type: vec![
+                        TableDataType::String,
+                        TableDataType::Number(NumberDataType::Int64),
+                    ],
+                })),
+            ),
+            TableField::new(
+                "a",
+                TableDataType::Nullable(Box::new(TableDataType::Array(Box::new(
+                    TableDataType::String,
+                )))),
+            ),
+        ]);
+        generator
+            .tables
+            .push(Table::new(None, Identifier::from_name(None, "t2"), schema));
+
+        let mut laterals = 0;
+        let mut sources = 0;
+        for _ in 0..200 {
+            generator.bound_tables.clear();
+            generator.bound_columns.clear();
+            let (table_ref, schema) = generator.gen_unnest_table_ref();
+            let unnest = match &table_ref {
+                TableReference::Join { join, .. } => join.right.as_ref().clone(),
+                table_ref => table_ref.clone(),
+            };
+            let TableReference::TableFunction {
+                lateral,
+                name,
+                params,
+                ..
+            } = unnest
+            else {
+                continue;
+            };
+            assert_eq!(name.name, "unnest");
+            assert_eq!(schema.num_fields(), 1);
+            assert_eq!(schema.field(0).name, "value");
+            assert_eq!(generator.bound_tables.last().unwrap().schema, schema);
+            if !lateral {
+                sources += 1;
+                continue;
+            }
+            laterals += 1;
+            // the unnested column has the element type of the array
+            let expected = match &params[0] {
+                Expr::ColumnRef { column, .. } => match column.column.name() {
+                    "c5" => TableDataType::Number(NumberDataType::Float64),
+                    "a" => TableDataType::String,
+                    name => panic!("unexpected column {}", name),
+                },
+                Expr::FunctionCall { func, .. } => match func.name.name.as_str() {
+                    "map_keys" => TableDataType::String,
+                    "map_values" => TableDataType::Number(NumberDataType::Int64),
+                    name => panic!("unexpected function {}", name),
+                },
+                expr => panic!("unexpected unnest argument {}", expr),
+            };
+            assert_eq!(schema.field(0).data_type, expected);
+        }
+        assert!(laterals > 0);
+        assert!(sources > 0);
+    }
+
+    #[test]
+    fn test_agg_window_select() {
+        let mut generator = test_generator();
+
+        let mut grouped = 0;
+        for _ in 0..100 {
+            generator.bound_tables.clear();
+            generator.bound_columns.clear();
+            generator.is_join = false;
+            let select = generator.gen_agg_window_select();
+            let exprs = select
+                .select_list
+                .iter()
+                .map(|target| match target {
+                    SelectTarget::AliasedExpr { expr, .. } => expr.as_ref().clone(),
+                    _ => panic!("expect aliased expr"),
+                })
+                .collect::<Vec<_>>();
+
+            // a plain aggregate, a scalar expression over it and a window function over it
+            let Expr::FunctionCall { func: agg, .. } = &exprs[0] else {
+                panic!("expect aggregate function");
+            };
+            assert!(agg.window.is_none());
+            assert!(matches!(&exprs[1], Expr::BinaryOp { left, .. } if left.as_ref() == &exprs[0]));
+            let Expr::FunctionCall { func: window, .. } = &exprs[2] else {
+                panic!("expect window function");
+            };
+            assert!(window.window.is_some());
+            assert!(window.args.is_empty() || window.args[0] == exprs[0]);
+
+            // the rest of the select list are the grouping columns
+            match &select.group_by {
+                Some(GroupBy::Normal(items)) => {
+                    grouped += 1;
+                    assert_eq!(items.as_slice(), &exprs[3..]);
+                    assert!(items
+                        .iter()
+                        .all(|item| matches!(item, Expr::ColumnRef { .. })));
+                }
+                None => assert_eq!(exprs.len(), 3),
+                Some(group_by) => panic!("unexpected group by {}", group_by),
+            }
+        }
+        assert!(grouped > 0);
+    }
+
+    #[test]
+    fn test_star_targets() {
+        let mut generator = test_generator();
+        let table = mock_tables().remove(0);
+        generator.bound_table(table.clone());
+
+        let targets = generator.gen_star_targets(false);
+        assert_eq!(targets.len(), 1);
+        assert!(targets[0].is_star());
+
+        // the expanded list is all the columns of the table in order
+        let targets = generator.gen_star_targets(true);
+        let names = targets
+            .iter()
+            .map(|target| match target {
+                SelectTarget::AliasedExpr { expr, alias: None } => match expr.as_ref() {
+                    Expr::ColumnRef { column, .. } => {
+                        assert_eq!(column.table.as_ref().unwrap().name, table.name.name);
+                        column.column.name().to_string()
+                    }
+                    expr => panic!("expect column reference, got {}", expr),
+                },
+                target => panic!("unexpected target {}", target),
+            })
+            .collect::<Vec<_>>();
+        let expected = table
+            .schema
+            .fields()
+            .iter()
+            .map(|field| field.name().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, expected);
+    }
+
+    #[test]
+    fn test_query_within_max_sql_bytes() {
+        let mut generator = test_generator();
+
+        let max_sql_bytes = 300;
+        let mut num_long = 0;
+        for _ in 0..200 {
+            let query = generator.gen_query_within(max_sql_bytes);
+            let sql = query.to_string();
+            assert!(sql.len() <= max_sql_bytes, "{} bytes: {sql}", sql.len());
+            // the complexity is restored for the next query
+            assert_eq!(generator.max_expr_depth, 2);
+            assert_eq!(generator.max_select_targets, 7);
+
+            if generator.gen_query().to_string().len() > max_sql_bytes {
+                num_long += 1;
+            }
+        }
+        // the budget is smaller than many unbounded queries
+        assert!(num_long > 0);
+    }
+
+    #[test]
+    fn test_order_by_window_result() {
+        let mut generator = test_generator();
+
+        let is_window =
+            |expr: &Expr| matches!(expr, Expr::FunctionCall { func, .. } if func.window.is_some());
+        let mut num_aliases = 0;
+        let mut num_exprs = 0;
+        for _ in 0..500 {
+            let query = generator.gen_query();
+            let SetExpr::Select(select) = &query.body else {
+                continue;
+            };
+            let Some(order_by) = query.order_by.first() else {
+                continue;
+            };
+            // the window result is in the select list with an alias
+            let window_targets = select
+                .select_list
+                .iter()
+                .filter_map(|target| match target {
+                    SelectTarget::AliasedExpr {
+                        expr,
+                        alias: Some(alias),
+                    } if is_window(expr) => Some((alias.name.clone(), expr.as_ref())),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            match &order_by.expr {
+                Expr::ColumnRef {
+                    column:
+                        ColumnRef {
+                            column: ColumnID::Name(name),
+                            ..
+                        },
+                    ..
+                } if window_targets.iter().any(|(alias, _)| *alias == name.name) => {
+                    num_aliases += 1;
+                }
+                expr if window_targets.iter().any(|(_, target)| *target == expr) => {
+                    num_exprs += 1;
+                }
+                _ => {}
+            }
+        }
+        assert!(num_aliases > 0);
+        assert!(num_exprs > 0);
+    }
+
+    #[test]
+    fn test_time_travel_table_ref() {
+        let mut generator = test_generator();
+
+        // the time travel points are emitted in a form accepted by the parser
+        let parse_point = |point: TimeTravelPoint| {
+            let sql = format!("SELECT * FROM t1 {}", TemporalClause::TimeTravel(point));
+            let tokens = tokenize_sql(&sql).unwrap();
+            let (stmt, _) = parse_sql(&tokens, Dialect::PostgreSQL)
+                .unwrap_or_else(|e| panic!("failed to parse {sql}: {e}"));
+            assert!(stmt.to_string().contains(" AT ("), "{stmt}");
+        };
+        let mut kinds = HashSet::new();
+        for _ in 0..100 {
+            let point = generator.gen_time_travel_point();
+            assert_eq!(point.to_string(), "(TIMESTAMP => now())");
+            parse_point(point);
+        }
+        generator.gen_error_cases = true;
+        for _ in 0..100 {
+            let point = generator.gen_time_travel_point();
+            kinds.insert(std::mem::discriminant(&point));
+            parse_point(point);
+        }
+        assert_eq!(kinds.len(), 3);
+
+        // only the current time is generated if not for the error cases
+        generator.gen_error_cases = false;
+        let mut num_time_travels = 0;
+        for _ in 0..500 {
+            let sql = generator.gen_query().to_string();
+            let num_points = sql.matches(" AT (").count();
+            assert_eq!(sql.matches(" AT (TIMESTAMP => now())").count(), num_points);
+            num_time_travels += num_points;
+        }
+        assert!(num_time_travels > 0);
+    }
+
+    #[test]
+    fn test_distinct_agg_select() {
+        let mut generator = test_generator();
+
+        let mut num_agg_funcs = 0;
+        let mut deduplicated = 0;
+        for _ in 0..100 {
+            generator.bound_tables.clear();
+            generator.bound_columns.clear();
+            generator.is_join = false;
+            let select = generator.gen_distinct_agg_select();
+            let exprs = select
+                .select_list
+                .iter()
+                .map(|target| match target {
+                    SelectTarget::AliasedExpr { expr, .. } => expr.as_ref().clone(),
+                    _ => panic!("expect aliased expr"),
+                })
+                .collect::<Vec<_>>();
+
+            // the distinct modifier applies to the aggregates and the projected grouping items
+            assert!(select.distinct);
+            let Some(GroupBy::Normal(items)) = &select.group_by else {
+                panic!("expect group by");
+            };
+            let num_aggs = exprs.len() - exprs.iter().filter(|expr| items.contains(expr)).count();
+            assert!((1..=2).contains(&num_aggs));
+            for expr in &exprs[..num_aggs] {
+                if let Expr::FunctionCall { func, .. } = expr {
+                    assert!(func.window.is_none());
+                    num_agg_funcs += 1;
+                }
+            }
+            assert!(exprs[num_aggs..].iter().all(|expr| items.contains(expr)));
+            if exprs.len() - num_aggs < items.len() {
+                deduplicated += 1;
+            }
+            // the ORDER BY only references the projected items
+            assert_eq!(
+                generator.group_by,
+                Some(GroupBy::Normal(exprs[num_aggs..].to_vec()))
+            );
+
+            // the statement is valid SQL
+            let sql = select.to_string();
+            assert!(sql.starts_with("SELECT DISTINCT "), "{sql}");
+            let tokens = tokenize_sql(&sql).unwrap();
+            parse_sql(&tokens, Dialect::PostgreSQL)
+                .unwrap_or_else(|e| panic!("failed to parse {sql}: {e}"));
+        }
+        assert!(num_agg_funcs > 0);
+        assert!(deduplicated > 0);
+    }
+
+    #[test]
+    fn test_renamed_subquery_table_ref() {
+        let mut generator = test_generator();
+
+        let mut renamed = 0;
+        for _ in 0..50 {
+            generator.bound_tables.clear();
+            generator.bound_columns.clear();
+            generator.is_join = false;
+            let TableReference::Subquery {
+                subquery, alias, ..
+            } = generator.gen_subquery_table_ref()
+            else {
+                panic!("expect subquery");
+            };
+            let alias = alias.unwrap();
+            let SetExpr::Select(select) = &subquery.body else {
+                panic!("expect select");
+            };
+            let inner_names = select
+                .select_list
+                .iter()
+                .map(|target| match target {
+                    SelectTarget::AliasedExpr { alias, .. } => alias.clone().unwrap().name,
+                    _ => panic!("expect aliased expr"),
+                })
+                .collect::<Vec<_>>();
+            let alias_names = alias
+                .columns
+                .iter()
+                .map(|column| column.name.clone())
+                .collect::<Vec<_>>();
+            assert_eq!(alias_names.len(), inner_names.len());
+            if alias_names != inner_names {
+                renamed += 1;
+                assert!(alias_names.iter().all(|name| !inner_names.contains(name)));
+            }
+
+            // the outer scope has the columns of the aliases with the types of the subquery
+            let bound_columns = generator.bound_columns.clone();
+            assert_eq!(
+                bound_columns
+                    .iter()
+                    .map(|column| column.name.clone())
+                    .collect::<Vec<_>>(),
+                alias_names
+            );
+            for column in bound_columns {
+                assert_eq!(column.table_name.as_ref(), Some(&alias.name));
+                // the outer references resolve to the aliased columns
+                match generator.gen_column(&column.data_type) {
+                    Expr::ColumnRef {
+                        column:
+                            ColumnRef {
+                                column: ColumnID::Name(name),
+                                ..
+                            },
+                        ..
+                    } => {
+                        let bound = generator
+                            .bound_columns
+                            .iter()
+                            .find(|bound| bound.name == name.name)
+                            .unwrap();
+                        assert_eq!(bound.data_type, column.data_type);
+                    }
+                    Expr::ColumnRef { .. } => {}
+                    expr => panic!("expect column ref, got {expr}"),
+                }
+            }
+        }
+        assert!(renamed > 0);
+    }
+}
@@ -0,0 +1,244 @@
+// Copyright 2021 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use databend_common_ast::ast::DescribeTableStmt;
+use databend_common_ast::ast::ShowColumnsStmt;
+use databend_common_ast::ast::ShowCreateDatabaseStmt;
+use databend_common_ast::ast::ShowCreateTableStmt;
+use databend_common_ast::ast::ShowDatabasesStmt;
+use databend_common_ast::ast::ShowLimit;
+use databend_common_ast::ast::ShowTablesStatusStmt;
+use databend_common_ast::ast::ShowTablesStmt;
+use databend_common_ast::ast::Statement;
+use rand::Rng;
+
+use crate::sql_gen::SqlGenerator;
+
+impl<R: Rng> SqlGenerator<'_, R> {
+    /// Generate the `DESCRIBE` and `SHOW` statements of the metadata of a generated table,
+    /// optionally qualified by its database and filtered by a LIKE pattern.
+    pub(crate) fn gen_show_stmt(&mut self) -> Statement {
+        if self.tables.is_empty() {
+            return Statement::ShowDatabases(ShowDatabasesStmt {
+                catalog: None,
+                full: self.flip_coin(),
+                limit: None,
+            });
+        }
+        let idx = self.rng.gen_range(0..self.tables.len());
+        let table = self.tables[idx].clone();
+        let database = if self.flip_coin() {
+            table.db_name.clone()
+        } else {
+            None
+        };
+
+        match self.rng.gen_range(0..=6) {
+            0 => Statement::DescribeTable(DescribeTableStmt {
+                catalog: None,
+                database,
+                table: table.name,
+            }),
+            1 => {
+                let limit = if self.flip_coin() {
+                    let idx = self.rng.gen_range(0..table.schema.num_fields());
+                    let name = table.schema.field(idx).name().clone();
+                    Some(self.gen_like_limit(&name))
+                } else {
+                    None
+                };
+                Statement::ShowColumns(ShowColumnsStmt {
+                    catalog: None,
+                    database,
+                    table: table.name,
+                    full: self.flip_coin(),
+                    limit,
+                })
+            }
+            2 => Statement::ShowCreateTable(ShowCreateTableStmt {
+                catalog: None,
+                database,
+                table: table.name,
+            }),
+            3 => {
+                let limit = if self.rng.gen_bool(0.8) {
+                    Some(self.gen_like_limit(&table.name.name))
+                } else {
+                    None
+                };
+                Statement::ShowTables(ShowTablesStmt {
+                    catalog: None,
+                    database,
+                    full: self.flip_coin(),
+                    limit,
+                    with_history: false,
+                })
+            }
+            4 => {
+                let limit = if self.flip_coin() {
+                    Some(self.gen_like_limit(&table.name.name))
+                } else {
+                    None
+                };
+                Statement::ShowTablesStatus(ShowTablesStatusStmt { database, limit })
+            }
+            5 => {
+                let limit = table
+                    .db_name
+                    .as_ref()
+                    .map(|db_name| self.gen_like_limit(&db_name.name));
+                Statement::ShowDatabases(ShowDatabasesStmt {
+                    catalog: None,
+                    full: self.flip_coin(),
+                    limit,
+                })
+            }
+            6 => match table.db_name {
+                Some(db_name) => Statement::ShowCreateDatabase(ShowCreateDatabaseStmt {
+                    catalog: None,
+                    database: db_name,
+                }),
+                None => Statement::ShowCreateTable(ShowCreateTableStmt {
+                    catalog: None,
+                    database: None,
+                    table: table.name,
+                }),
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    // A pattern matching the name by a prefix, a suffix or a single character wildcard,
+    // or a pattern which matches nothing.
+    fn gen_like_limit(&mut self, name: &str) -> ShowLimit {
+        let chars = name.chars().collect::<Vec<_>>();
+        let len = chars.len();
+        let pattern = match self.rng.gen_range(0..=4) {
+            0 => "%".to_string(),
+            1 => {
+                let end = self.rng.gen_range(0..=len);
+                format!("{}%", chars[..end].iter().collect::<String>())
+            }
+            2 => {
+                let start = self.rng.gen_range(0..=len);
+                format!("%{}", chars[start..].iter().collect::<String>())
+            }
+            3 if len > 0 => {
+                let pos = self.rng.gen_range(0..len);
+                chars
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| if i == pos { '_' } else { *c })
+                    .collect()
+            }
+            3 => "_%".to_string(),
+            4 => format!("{}_not_exist", name),
+            _ => unreachable!(),
+        };
+        ShowLimit::Like { pattern }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use databend_common_ast::ast::Identifier;
+    use databend_common_ast::ast::ShowLimit;
+    use databend_common_ast::ast::Statement;
+    use databend_common_ast::parser::parse_sql;
+    use databend_common_ast::parser::tokenize_sql;
+    use databend_common_ast::parser::Dialect;
+
+    use crate::sql_gen::mock_tables;
+    use crate::sql_gen::test_generator;
+    use crate::sql_gen::StatementWeights;
+
+    #[test]
+    fn test_show_stmts() {
+        let mut generator = test_generator();
+        let mut tables = mock_tables();
+        tables[0].db_name = Some(Identifier::from_name(None, "db1"));
+        generator.tables = tables;
+
+        let weights = StatementWeights {
+            show: 1,
+            ..Default::default()
+        };
+        let is_table = |table: &Identifier| table.name == "t1";
+        let is_database = |database: &Option<Identifier>| {
+            database
+                .as_ref()
+                .is_none_or(|database| database.name == "db1")
+        };
+        let mut kinds = HashSet::new();
+        let mut num_likes = 0;
+        for _ in 0..500 {
+            let stmt = generator.gen_statement(&weights);
+            let sql = stmt.to_string();
+            let tokens = tokenize_sql(&sql).unwrap();
+            let (parsed, _) = parse_sql(&tokens, Dialect::PostgreSQL)
+                .unwrap_or_else(|e| panic!("failed to parse {sql}: {e}"));
+            assert_eq!(
+                std::mem::discriminant(&parsed),
+                std::mem::discriminant(&stmt)
+            );
+
+            let limit = match &stmt {
+                Statement::DescribeTable(stmt) => {
+                    assert!(
+                        is_table(&stmt.table) && is_database(&stmt.database),
+                        "{sql}"
+                    );
+                    None
+                }
+                Statement::ShowColumns(stmt) => {
+                    assert!(
+                        is_table(&stmt.table) && is_database(&stmt.database),
+                        "{sql}"
+                    );
+                    stmt.limit.as_ref()
+                }
+                Statement::ShowCreateTable(stmt) => {
+                    assert!(
+                        is_table(&stmt.table) && is_database(&stmt.database),
+                        "{sql}"
+                    );
+                    None
+                }
+                Statement::ShowTables(stmt) => {
+                    assert!(is_database(&stmt.database), "{sql}");
+                    stmt.limit.as_ref()
+                }
+                Statement::ShowTablesStatus(stmt) => {
+                    assert!(is_database(&stmt.database), "{sql}");
+                    stmt.limit.as_ref()
+                }
+                Statement::ShowDatabases(stmt) => stmt.limit.as_ref(),
+                Statement::ShowCreateDatabase(stmt) => {
+                    assert_eq!(stmt.database.name, "db1");
+                    None
+                }
+                _ => panic!("unexpected statement: {sql}"),
+            };
+            if let Some(limit) = limit {
+                assert!(matches!(limit, ShowLimit::Like { .. }), "{sql}");
+                num_likes += 1;
+            }
+            kinds.insert(std::mem::discriminant(&stmt));
+        }
+        assert_eq!(kinds.len(), 7);
+        assert!(num_likes > 0);
+    }
+}
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashSet;
+
 use databend_common_ast::ast::GroupBy;
 use databend_common_ast::ast::Identifier;
 use databend_common_expression::types::DataType;
@@ -73,8 +75,31 @@ pub(crate) struct SqlGenerator<'a, R: Rng> {
     // TODO: Generate expressions of the required type
     pub(crate) only_scalar_expr: bool,
     pub(crate) expr_depth: usize,
+    // The depth `expr_depth` is reset to after each expression.
+    pub(crate) max_expr_depth: usize,
+    // The maximum number of the generated select targets.
+    pub(crate) max_select_targets: usize,
     pub(crate) group_by: Option<GroupBy>,
     pub(crate) windows_name: Vec<String>,
+    // If set, only generate function calls in this list.
+    pub(crate) allowed_funcs: Option<HashSet<String>>,
+    // Generate invalid expressions on purpose to test error handling.
+    pub(crate) gen_error_cases: bool,
+    // The number of generated function calls whose arguments
+    // do not match any known signature of the function.
+    pub(crate) args_type_mismatches: usize,
+    // The nesting depth of the CASE expressions being generated.
+    pub(crate) case_depth: usize,
+    // Generate spatial aggregate functions over geometry expressions,
+    // disabled by default as the geometry type is still experimental.
+    pub(crate) enable_spatial: bool,
+    // Place literals of another type category in the function arguments to test
+    // the implicit casts, like a String literal where a number is expected.
+    pub(crate) coercion_mode: bool,
+    // The number of generated implicit casts which are supported by the function.
+    pub(crate) implicit_coercions: usize,
+    // The number of generated implicit casts which are not supported, only with `gen_error_cases`.
+    pub(crate) invalid_coercions: usize,
 }
 
 impl<'a, R: Rng> SqlGenerator<'a, R> {
@@ -111,8 +136,74 @@ impl<'a, R: Rng> SqlGenerator<'a, R> {
             is_join: false,
             only_scalar_expr: false,
             expr_depth: 2,
+            max_expr_depth: 2,
+            max_select_targets: 7,
             group_by: None,
             windows_name: vec![],
+            allowed_funcs: None,
+            gen_error_cases: false,
+            args_type_mismatches: 0,
+            case_depth: 0,
+            enable_spatial: false,
+            coercion_mode: false,
+            implicit_coercions: 0,
+            invalid_coercions: 0,
+        }
+    }
+
+    /// Restrict the generated function calls to the given function names,
+    /// the surrounding expressions are still generated as usual.
+    pub(crate) fn set_allowed_funcs(&mut self, names: &[String]) {
+        let names = names
+            .iter()
+            .map(|name| name.trim().to_lowercase())
+            .collect::<HashSet<_>>();
+        self.scalar_func_sigs
+            .retain(|func_sig| names.contains(&func_sig.name));
+        self.allowed_funcs = Some(names);
+    }
+
+    pub(crate) fn is_func_allowed(&self, name: &str) -> bool {
+        match &self.allowed_funcs {
+            Some(allowed_funcs) => allowed_funcs.contains(name),
+            None => true,
         }
     }
 }
+
+#[cfg(test)]
+pub(crate) fn mock_tables() -> Vec<Table> {
+    use databend_common_expression::types::NumberDataType;
+    use databend_common_expression::TableDataType;
+    use databend_common_expression::TableField;
+    use databend_common_expression::TableSchemaRefExt;
+
+    let schema = TableSchemaRefExt::create(vec![
+        TableField::new("c0", TableDataType::Number(NumberDataType::Int64)),
+        TableField::new(
+            "c1",
+            TableDataType::Nullable(Box::new(TableDataType::Number(NumberDataType::UInt32))),
+        ),
+        TableField::new("c2", TableDataType::String),
+        TableField::new("c3", TableDataType::Timestamp),
+        TableField::new("c4", TableDataType::Variant),
+        TableField::new(
+            "c5",
+            TableDataType::Array(Box::new(TableDataType::Number(NumberDataType::Float64))),
+        ),
+        TableField::new("c6", TableDataType::Boolean),
+    ]);
+    vec![Table::new(None, Identifier::from_name(None, "t1"), schema)]
+}
+
+/// A generator over the mock tables, with a fixed seed so that the tests are deterministic.
+#[cfg(test)]
+pub(crate) fn test_generator() -> SqlGenerator<'static, rand::rngs::SmallRng> {
+    use rand::SeedableRng;
+
+    // leaked to return the generator along with its rng, only a few bytes for each test
+    let rng = Box::leak(Box::new(rand::rngs::SmallRng::seed_from_u64(0)));
+    let mut generator = SqlGenerator::new(rng, vec![]);
+    generator.tables = mock_tables();
+    generator
+}
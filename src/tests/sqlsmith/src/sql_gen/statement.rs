@@ -0,0 +1,378 @@
+// Copyright 2021 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use databend_common_ast::ast::Expr;
+use databend_common_ast::ast::Identifier;
+use databend_common_ast::ast::Literal;
+use databend_common_ast::ast::SetType;
+use databend_common_ast::ast::SetValues;
+use databend_common_ast::ast::Settings;
+use databend_common_ast::ast::Statement;
+use rand::Rng;
+
+use crate::sql_gen::SqlGenerator;
+
+/// Relative weights of the statement types generated by `gen_statement`,
+/// a statement type with weight zero is never generated.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StatementWeights {
+    pub(crate) query: u32,
+    pub(crate) insert: u32,
+    pub(crate) update: u32,
+    pub(crate) delete: u32,
+    pub(crate) replace: u32,
+    pub(crate) merge: u32,
+    pub(crate) alter: u32,
+    pub(crate) show: u32,
+}
+
+impl StatementWeights {
+    /// The mix of DML statements run after the base tables are created.
+    pub(crate) fn dml() -> Self {
+        Self {
+            update: 4,
+            delete: 1,
+            replace: 5,
+            merge: 11,
+            ..Default::default()
+        }
+    }
+
+    fn total(&self) -> u32 {
+        self.query
+            + self.insert
+            + self.update
+            + self.delete
+            + self.replace
+            + self.merge
+            + self.alter
+            + self.show
+    }
+}
+
+/// The valid values of a setting in the `SETTINGS` clause.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum SettingValues {
+    Numeric(u64, u64),
+    String(&'static [&'static str]),
+}
+
+/// The settings which are fuzzed by the `SETTINGS (k = v, ...)` clause of the statements.
+pub(crate) const QUERY_SETTINGS: &[(&str, SettingValues)] = &[
+    ("max_threads", SettingValues::Numeric(1, 16)),
+    ("max_block_size", SettingValues::Numeric(1, 65536)),
+    ("enable_dphyp", SettingValues::Numeric(0, 1)),
+    ("disable_join_reorder", SettingValues::Numeric(0, 1)),
+    ("join_spilling_memory_ratio", SettingValues::Numeric(0, 100)),
+    ("sort_spilling_memory_ratio", SettingValues::Numeric(0, 100)),
+    ("enable_bloom_runtime_filter", SettingValues::Numeric(0, 1)),
+    ("inlist_to_join_threshold", SettingValues::Numeric(0, 2048)),
+    ("lazy_read_threshold", SettingValues::Numeric(0, 2000)),
+    ("max_result_rows", SettingValues::Numeric(0, 100)),
+    (
+        "numeric_cast_option",
+        SettingValues::String(&["rounding", "truncating"]),
+    ),
+    (
+        "group_by_shuffle_mode",
+        SettingValues::String(&["before_partial", "before_merge"]),
+    ),
+    (
+        "timezone",
+        SettingValues::String(&["UTC", "Asia/Shanghai", "America/New_York"]),
+    ),
+];
+
+impl<R: Rng> SqlGenerator<'_, R> {
+    pub(crate) fn gen_statement(&mut self, weights: &StatementWeights) -> Statement {
+        let total = weights.total();
+        if total == 0 {
+            return Statement::Query(Box::new(self.gen_query()));
+        }
+
+        let mut n = self.rng.gen_range(0..total);
+        if n < weights.query {
+            return Statement::Query(Box::new(self.gen_query()));
+        }
+        n -= weights.query;
+        if n < weights.insert {
+            let idx = self.rng.gen_range(0..self.tables.len());
+            let table = self.tables[idx].clone();
+            let row_count = self.rng.gen_range(1..=10);
+            return Statement::Insert(self.gen_insert(&table, row_count));
+        }
+        n -= weights.insert;
+        if n < weights.update {
+            return Statement::Update(self.gen_update());
+        }
+        n -= weights.update;
+        if n < weights.delete {
+            return Statement::Delete(self.gen_delete());
+        }
+        n -= weights.delete;
+        if n < weights.replace {
+            // skip the tables which have no column to be used as a conflict key
+            return match self.gen_replace() {
+                Some(replace_stmt) => Statement::Replace(replace_stmt),
+                None => Statement::Query(Box::new(self.gen_query())),
+            };
+        }
+        n -= weights.replace;
+        if n < weights.merge {
+            return Statement::MergeInto(self.gen_merge());
+        }
+        n -= weights.merge;
+        if n < weights.show {
+            return self.gen_show_stmt();
+        }
+
+        let idx = self.rng.gen_range(0..self.tables.len());
+        let table = self.tables[idx].clone();
+        match self.gen_alter(&table, 0) {
+            Some((alter_stmt, new_table, _)) => {
+                // keep the table schema in sync for the following statements
+                self.tables[idx] = new_table;
+                Statement::AlterTable(alter_stmt)
+            }
+            None => Statement::Query(Box::new(self.gen_query())),
+        }
+    }
+
+    /// Attach a `SETTINGS (k = v, ...)` clause to the statement, the settings are
+    /// applied only while the statement is executed.
+    ///
+    /// If `gen_error_cases` is set, a setting may be unknown or out of its valid range.
+    pub(crate) fn gen_statement_with_settings(&mut self, stmt: Statement) -> Statement {
+        let len = self.rng.gen_range(1..=3);
+        let mut identifiers: Vec<Identifier> = Vec::with_capacity(len);
+        let mut values = Vec::with_capacity(len);
+        for _ in 0..len {
+            let (name, value) = if self.gen_error_cases && self.rng.gen_bool(0.2) {
+                self.gen_invalid_setting()
+            } else {
+                let (name, setting_values) =
+                    QUERY_SETTINGS[self.rng.gen_range(0..QUERY_SETTINGS.len())];
+                let value = match setting_values {
+                    SettingValues::Numeric(min, max) => {
+                        Literal::UInt64(self.rng.gen_range(min..=max))
+                    }
+                    SettingValues::String(strs) => {
+                        Literal::String(strs[self.rng.gen_range(0..strs.len())].to_string())
+                    }
+                };
+                (name.to_string(), value)
+            };
+            // the later value of the same setting would override the former one
+            if identifiers.iter().any(|id| id.name == name) {
+                continue;
+            }
+            identifiers.push(Identifier::from_name(None, name));
+            values.push(Box::new(Expr::Literal { span: None, value }));
+        }
+        Statement::StatementWithSettings {
+            settings: Some(Settings {
+                set_type: SetType::SettingsQuery,
+                identifiers,
+                values: SetValues::Expr(values),
+            }),
+            stmt: Box::new(stmt),
+        }
+    }
+
+    fn gen_invalid_setting(&mut self) -> (String, Literal) {
+        let (name, setting_values) = QUERY_SETTINGS[self.rng.gen_range(0..QUERY_SETTINGS.len())];
+        match self.rng.gen_range(0..=2) {
+            0 => (format!("{}_not_exist", name), Literal::UInt64(1)),
+            1 => {
+                let value = match setting_values {
+                    SettingValues::Numeric(_, max) => Literal::UInt64(max + 1),
+                    SettingValues::String(_) => Literal::String("invalid".to_string()),
+                };
+                (name.to_string(), value)
+            }
+            // a value of the wrong type
+            2 => {
+                let value = match setting_values {
+                    SettingValues::Numeric(_, _) => Literal::String("invalid".to_string()),
+                    SettingValues::String(_) => Literal::Boolean(true),
+                };
+                (name.to_string(), value)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Generate a script of statements wrapped in an explicit transaction,
+    /// `BEGIN; <stmt>; ...; COMMIT` or `ABORT`.
+    ///
+    /// DDL is not allowed inside an explicit transaction, so `ALTER TABLE` is never generated
+    /// in the script. Savepoints and nested transactions are not supported by the parser.
+    /// If `gen_error_cases` is set, the `BEGIN` may be omitted to generate a stray `COMMIT`.
+    pub(crate) fn gen_transaction(&mut self, weights: &StatementWeights) -> Vec<Statement> {
+        let weights = StatementWeights {
+            alter: 0,
+            ..weights.clone()
+        };
+
+        let mut stmts = Vec::new();
+        if !self.gen_error_cases || self.rng.gen_bool(0.8) {
+            stmts.push(Statement::Begin);
+        }
+        let len = self.rng.gen_range(1..=3);
+        for _ in 0..len {
+            stmts.push(self.gen_statement(&weights));
+        }
+        if self.rng.gen_bool(0.8) {
+            stmts.push(Statement::Commit);
+        } else {
+            stmts.push(Statement::Abort);
+        }
+        stmts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use databend_common_ast::ast::Expr;
+    use databend_common_ast::ast::Literal;
+    use databend_common_ast::ast::SetValues;
+    use databend_common_ast::ast::Statement;
+    use databend_common_ast::parser::parse_sql;
+    use databend_common_ast::parser::tokenize_sql;
+    use databend_common_ast::parser::Dialect;
+
+    use super::SettingValues;
+    use super::QUERY_SETTINGS;
+    use crate::sql_gen::test_generator;
+    use crate::sql_gen::StatementWeights;
+
+    #[test]
+    fn test_statement_weights() {
+        let mut generator = test_generator();
+
+        let weights = StatementWeights {
+            update: 9,
+            delete: 1,
+            ..Default::default()
+        };
+        let (mut updates, mut deletes) = (0, 0);
+        for _ in 0..200 {
+            match generator.gen_statement(&weights) {
+                Statement::Update(_) => updates += 1,
+                Statement::Delete(_) => deletes += 1,
+                stmt => panic!("unexpected statement: {stmt}"),
+            }
+        }
+        assert!(deletes > 0);
+        assert!(updates > deletes);
+    }
+
+    #[test]
+    fn test_transaction() {
+        let mut generator = test_generator();
+
+        let is_control = |stmt: &Statement| {
+            matches!(
+                stmt,
+                Statement::Begin | Statement::Commit | Statement::Abort
+            )
+        };
+
+        let weights = StatementWeights {
+            alter: 1,
+            ..StatementWeights::dml()
+        };
+        for _ in 0..50 {
+            let stmts = generator.gen_transaction(&weights);
+            assert!(stmts.len() >= 3);
+            assert!(matches!(stmts.first(), Some(Statement::Begin)));
+            assert!(matches!(
+                stmts.last(),
+                Some(Statement::Commit | Statement::Abort)
+            ));
+            for stmt in &stmts[1..stmts.len() - 1] {
+                assert!(!is_control(stmt), "unexpected statement: {stmt}");
+                assert!(!matches!(stmt, Statement::AlterTable(_)));
+            }
+        }
+
+        generator.gen_error_cases = true;
+        let mut stray_commits = 0;
+        for _ in 0..50 {
+            let stmts = generator.gen_transaction(&weights);
+            if !matches!(stmts.first(), Some(Statement::Begin)) {
+                assert!(!is_control(&stmts[0]));
+                stray_commits += 1;
+            }
+            assert!(matches!(
+                stmts.last(),
+                Some(Statement::Commit | Statement::Abort)
+            ));
+        }
+        assert!(stray_commits > 0);
+    }
+
+    #[test]
+    fn test_statement_with_settings() {
+        let mut generator = test_generator();
+
+        let weights = StatementWeights {
+            query: 1,
+            ..StatementWeights::dml()
+        };
+        for _ in 0..100 {
+            let stmt = generator.gen_statement(&weights);
+            let stmt = generator.gen_statement_with_settings(stmt);
+            let sql = stmt.to_string();
+            let tokens = tokenize_sql(&sql).unwrap();
+            let (parsed, _) = parse_sql(&tokens, Dialect::PostgreSQL)
+                .unwrap_or_else(|e| panic!("failed to parse {sql}: {e}"));
+            let Statement::StatementWithSettings {
+                settings: Some(settings),
+                ..
+            } = parsed
+            else {
+                panic!("expect settings: {sql}");
+            };
+            let SetValues::Expr(values) = settings.values else {
+                panic!("expect setting values: {sql}");
+            };
+            assert!(!settings.identifiers.is_empty());
+            assert_eq!(settings.identifiers.len(), values.len());
+            for (id, value) in settings.identifiers.iter().zip(values) {
+                let (_, setting_values) = QUERY_SETTINGS
+                    .iter()
+                    .find(|(name, _)| *name == id.name)
+                    .unwrap_or_else(|| panic!("unknown setting {}: {sql}", id.name));
+                match (setting_values, *value) {
+                    (
+                        SettingValues::Numeric(min, max),
+                        Expr::Literal {
+                            value: Literal::UInt64(v),
+                            ..
+                        },
+                    ) => assert!((*min..=*max).contains(&v), "{sql}"),
+                    (
+                        SettingValues::String(strs),
+                        Expr::Literal {
+                            value: Literal::String(v),
+                            ..
+                        },
+                    ) => assert!(strs.contains(&v.as_str()), "{sql}"),
+                    (_, value) => panic!("invalid value {value} of {}: {sql}", id.name),
+                }
+            }
+        }
+    }
+}